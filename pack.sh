@@ -11,12 +11,14 @@ mkdir ${DIR_NAME}/windows
 
 # 1. First pack all the commercial assets
 echo "Build the assets pack"
+rm -f ./packed.bin ./packed_audio.bin
 cargo run --release --bin pack_assets
-FILE=./packed.bin
-if [  ! -f "$FILE" ]; then
-    echo "$FILE was not created..."
-    exit 1
-fi
+for FILE in ./packed.bin ./packed_audio.bin; do
+    if [  ! -f "$FILE" ]; then
+        echo "$FILE was not created..."
+        exit 1
+    fi
+done
 
 # 2. Compile for linux and for windows.
 echo "Compile for linux and windows"
@@ -1,13 +1,30 @@
-use crate::assets::{Asset, Loader};
+use crate::assets::{Asset, AssetError, Loader};
 use luminance::context::GraphicsContext;
 use luminance_gl::GL33;
+use rodio::Source;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs::File;
-use std::io::Read;
+use std::io::{BufReader, Cursor, Read};
 use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Files bigger than this are streamed instead of being decoded in memory.
+const STREAM_SIZE_THRESHOLD: u64 = 1024 * 1024;
+
+/// Number of threads that read and decode the audio files.
+const DECODING_THREADS: usize = 2;
+
+pub type AudioSource = Box<dyn Source<Item = i16> + Send>;
 
 pub enum Audio {
     Empty,
-    File(Vec<u8>),
+    /// Short sound, decoded once when loaded.
+    Buffered(DecodedAudio),
+    /// Long sound (e.g. music), decoded while it is played.
+    Stream(AudioStream),
 }
 
 impl Default for Audio {
@@ -16,8 +33,130 @@ impl Default for Audio {
     }
 }
 
+impl Audio {
+    /// Create a new source to play this audio.
+    pub fn source(&self) -> Result<AudioSource, AssetError> {
+        match self {
+            Audio::Empty => Ok(Box::new(rodio::source::Empty::new())),
+            Audio::Buffered(decoded) => Ok(Box::new(decoded.source(false))),
+            Audio::Stream(AudioStream::File(path)) => {
+                let file = File::open(path)?;
+                Ok(Box::new(rodio::Decoder::new(BufReader::new(file))?))
+            }
+            Audio::Stream(AudioStream::Memory(bytes)) => Ok(Box::new(rodio::Decoder::new(
+                Cursor::new(SharedBytes(bytes.clone())),
+            )?)),
+        }
+    }
+
+    /// Create a new source that repeats this audio forever without gap.
+    pub fn looped_source(&self) -> Result<AudioSource, AssetError> {
+        match self {
+            Audio::Empty => Ok(Box::new(rodio::source::Empty::new())),
+            Audio::Buffered(decoded) => Ok(Box::new(decoded.source(true))),
+            Audio::Stream(AudioStream::File(path)) => {
+                let file = File::open(path)?;
+                Ok(Box::new(rodio::Decoder::new_looped(BufReader::new(file))?))
+            }
+            Audio::Stream(AudioStream::Memory(bytes)) => Ok(Box::new(rodio::Decoder::new_looped(
+                Cursor::new(SharedBytes(bytes.clone())),
+            )?)),
+        }
+    }
+}
+
+/// Samples of a decoded sound. They are shared between all the sources that play the sound.
+#[derive(Clone)]
+pub struct DecodedAudio {
+    samples: Arc<Vec<i16>>,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl DecodedAudio {
+    pub fn decode(content: Vec<u8>) -> Result<Self, AssetError> {
+        let decoder = rodio::Decoder::new(Cursor::new(content))?;
+        let channels = decoder.channels();
+        let sample_rate = decoder.sample_rate();
+        Ok(Self {
+            samples: Arc::new(decoder.collect()),
+            channels,
+            sample_rate,
+        })
+    }
+
+    fn source(&self, looping: bool) -> DecodedSource {
+        DecodedSource {
+            audio: self.clone(),
+            position: 0,
+            looping,
+        }
+    }
+}
+
+struct DecodedSource {
+    audio: DecodedAudio,
+    position: usize,
+    /// If true, go back to the first sample at the end.
+    looping: bool,
+}
+
+impl Iterator for DecodedSource {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        if self.looping && self.position >= self.audio.samples.len() {
+            self.position = 0;
+        }
+        let sample = self.audio.samples.get(self.position).copied();
+        self.position += 1;
+        sample
+    }
+}
+
+impl Source for DecodedSource {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.audio.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.audio.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        if self.looping {
+            return None;
+        }
+        let frames = self.audio.samples.len() as f32 / self.audio.channels as f32;
+        Some(Duration::from_secs_f32(
+            frames / self.audio.sample_rate as f32,
+        ))
+    }
+}
+
+pub enum AudioStream {
+    /// Read from disk while playing.
+    File(PathBuf),
+    /// Encoded data that is already in memory (e.g. packed in the binary).
+    Memory(Arc<Vec<u8>>),
+}
+
+/// So that encoded data can be decoded by several sources without copying it.
+struct SharedBytes(Arc<Vec<u8>>);
+
+impl AsRef<[u8]> for SharedBytes {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_slice()
+    }
+}
+
 pub struct AudioSyncLoader {
     base_path: PathBuf,
+    decoding: DecodingPool,
 }
 
 impl AudioSyncLoader {
@@ -25,6 +164,7 @@ impl AudioSyncLoader {
         let base_path = base_path.as_ref();
         Self {
             base_path: base_path.to_path_buf(),
+            decoding: DecodingPool::new(),
         }
     }
 }
@@ -34,30 +174,144 @@ where
     S: GraphicsContext<Backend = GL33>,
 {
     fn load(&mut self, asset_name: String) -> Asset<Audio> {
-        let mut asset = Asset::new();
+        let asset = Asset::new();
         let path = self.base_path.join(asset_name);
         info!("Will load audio at {:?}", path);
 
-        match File::open(path) {
-            Ok(mut file) => {
-                let mut content = vec![];
-                match file.read_to_end(&mut content) {
-                    Ok(_) => {
-                        info!("Finished loading");
-                        asset.set_loaded(Audio::File(content))
-                    }
-                    Err(e) => {
-                        error!("Error while loading file");
-                        asset.set_error(e.into())
-                    }
-                }
+        let mut loading = asset.clone();
+        self.decoding.spawn(move || match load_audio(path) {
+            Ok(audio) => {
+                info!("Finished loading");
+                loading.set_loaded(audio)
             }
             Err(e) => {
-                error!("Error while loading file");
-                asset.set_error(e.into())
+                error!("Error while loading file = {:?}", e);
+                loading.set_error(e)
+            }
+        });
+
+        asset
+    }
+}
+
+type DecodingJob = Box<dyn FnOnce() + Send>;
+
+/// Reading and decoding audio is slow, so it is done by a few worker threads. Jobs wait in a
+/// queue so that loading a whole bank does not start a thread per file. The asset is ready after
+/// the asset manager is updated, once a worker is done with it.
+struct DecodingPool {
+    jobs: Sender<DecodingJob>,
+}
+
+impl DecodingPool {
+    fn new() -> Self {
+        let (jobs, receiver) = channel::<DecodingJob>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for i in 0..DECODING_THREADS {
+            let receiver = Arc::clone(&receiver);
+            let spawned = std::thread::Builder::new()
+                .name(format!("audio_loader_{}", i))
+                .spawn(move || loop {
+                    // The workers stop when the loader is dropped.
+                    let job = match receiver.lock().unwrap().recv() {
+                        Ok(job) => job,
+                        Err(_) => break,
+                    };
+                    job();
+                });
+            if let Err(e) = spawned {
+                error!("Cannot start the audio loading thread = {:?}", e);
             }
         }
+        Self { jobs }
+    }
+
+    /// Queue the job. It runs on the current thread if there is no worker.
+    fn spawn<F: FnOnce() + Send + 'static>(&self, decode: F) {
+        if let Err(job) = self.jobs.send(Box::new(decode)) {
+            (job.0)();
+        }
+    }
+}
+
+fn load_audio(path: PathBuf) -> Result<Audio, AssetError> {
+    let mut file = File::open(&path)?;
+    if file.metadata()?.len() > STREAM_SIZE_THRESHOLD {
+        // check that the file can be decoded so that errors are reported when loading.
+        rodio::Decoder::new(BufReader::new(file))?;
+        Ok(Audio::Stream(AudioStream::File(path)))
+    } else {
+        let mut content = vec![];
+        file.read_to_end(&mut content)?;
+        Ok(Audio::Buffered(DecodedAudio::decode(content)?))
+    }
+}
+
+/// Encoded audio files, packed by `pack_assets`.
+#[derive(Serialize, Deserialize)]
+pub struct PackedAudio {
+    pub content: HashMap<String, Vec<u8>>,
+}
+
+#[cfg(feature = "packed")]
+const AUDIO: &[u8] = include_bytes!("../../packed_audio.bin");
+
+/// Load the audio from the packed data. Long tracks are streamed from memory. If an asset is not
+/// in the pack, it is loaded from disk.
+#[cfg(feature = "packed")]
+pub struct AudioPackLoader {
+    packed: HashMap<String, Arc<Vec<u8>>>,
+    fallback_loader: AudioSyncLoader,
+}
+
+#[cfg(feature = "packed")]
+impl AudioPackLoader {
+    pub fn new<P: AsRef<Path>>(base_path: P) -> Self {
+        let packed: PackedAudio = bincode::deserialize(AUDIO).unwrap();
+        Self {
+            packed: packed
+                .content
+                .into_iter()
+                .map(|(name, content)| (name, Arc::new(content)))
+                .collect(),
+            fallback_loader: AudioSyncLoader::new(base_path),
+        }
+    }
+}
+
+#[cfg(feature = "packed")]
+impl<S> Loader<S, Audio, String> for AudioPackLoader
+where
+    S: GraphicsContext<Backend = GL33>,
+{
+    fn load(&mut self, asset_name: String) -> Asset<Audio> {
+        let content = match self.packed.get(&asset_name) {
+            Some(content) => content,
+            None => {
+                return <AudioSyncLoader as Loader<S, Audio, String>>::load(
+                    &mut self.fallback_loader,
+                    asset_name,
+                )
+            }
+        };
 
+        let asset = Asset::new();
+        let mut loading = asset.clone();
+        let content = content.clone();
+        self.fallback_loader.decoding.spawn(move || {
+            let audio = if content.len() as u64 > STREAM_SIZE_THRESHOLD {
+                Ok(Audio::Stream(AudioStream::Memory(content)))
+            } else {
+                DecodedAudio::decode(content.to_vec()).map(Audio::Buffered)
+            };
+            match audio {
+                Ok(audio) => loading.set_loaded(audio),
+                Err(e) => {
+                    error!("Error while loading {} = {:?}", asset_name, e);
+                    loading.set_error(e)
+                }
+            }
+        });
         asset
     }
 }
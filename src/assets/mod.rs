@@ -1,4 +1,5 @@
 use crate::assets::audio::Audio;
+use crate::assets::particle::ParticleEffectManager;
 use crate::assets::prefab::PrefabManager;
 use crate::assets::shader::ShaderManager;
 use crate::assets::sprite::SpriteAsset;
@@ -17,7 +18,9 @@ use std::sync::{Arc, Mutex};
 use thiserror::Error;
 
 pub mod audio;
+pub mod particle;
 pub mod prefab;
+pub mod preload;
 pub mod shader;
 pub mod sprite;
 
@@ -37,21 +40,65 @@ where
         sprite::SpritePackLoader::new(PathBuf::from(&base_path).join("assets/sprites")),
     ));
 
-    let prefab_loader: PrefabManager<S> = AssetManager::from_loader(Box::new(
-        prefab::PrefabSyncLoader::new(PathBuf::from(&base_path).join("assets/prefab")),
-    ));
+    let prefab_loader: PrefabManager<S> =
+        AssetManager::from_loader(Box::new(prefab::PrefabSyncLoader::new(
+            PathBuf::from(&base_path).join("assets/prefab"),
+            crate::prefab::component_registry(),
+        )));
 
+    #[cfg(not(feature = "packed"))]
     let audio_loader: AssetManager<S, Audio> = AssetManager::from_loader(Box::new(
         audio::AudioSyncLoader::new(PathBuf::from(&base_path).join("assets")),
     ));
 
+    #[cfg(feature = "packed")]
+    let audio_loader: AssetManager<S, Audio> = AssetManager::from_loader(Box::new(
+        audio::AudioPackLoader::new(PathBuf::from(&base_path).join("assets")),
+    ));
+
     let shader_loader: ShaderManager<S> = AssetManager::from_loader(Box::new(
         shader::ShaderLoader::new(PathBuf::from(&base_path).join("assets/shaders")),
     ));
+
+    let particle_loader: ParticleEffectManager<S> = AssetManager::from_loader(Box::new(
+        particle::ParticleSyncLoader::new(PathBuf::from(&base_path).join("assets/particle")),
+    ));
     resources.insert(sprite_manager);
     resources.insert(prefab_loader);
     resources.insert(audio_loader);
     resources.insert(shader_loader);
+    resources.insert(particle_loader);
+}
+
+/// Load again all the assets from the disk. They are uploaded to the GPU during the next
+/// `update_asset_managers`. Returns the number of assets reloaded.
+pub fn reload_asset_managers<S>(resources: &Resources) -> usize
+where
+    S: GraphicsContext<Backend = GL33> + 'static,
+{
+    fn reload_all<S, T, H>(resources: &Resources) -> usize
+    where
+        S: GraphicsContext<Backend = GL33> + 'static,
+        T: Default + 'static,
+        H: Clone + Hash + Eq + 'static,
+    {
+        match resources.fetch_mut::<AssetManager<S, T, H>>() {
+            Some(mut manager) => {
+                let keys = manager.keys().cloned().collect::<Vec<_>>();
+                for k in &keys {
+                    manager.reload(k.0.clone());
+                }
+                keys.len()
+            }
+            None => 0,
+        }
+    }
+
+    reload_all::<S, SpriteAsset<S>, String>(resources)
+        + reload_all::<S, prefab::Prefab, String>(resources)
+        + reload_all::<S, Audio, String>(resources)
+        + reload_all::<S, shader::ShaderAsset<S>, shader::ShaderHandle>(resources)
+        + reload_all::<S, crate::render::particle::ParticleEmitter, String>(resources)
 }
 
 pub fn update_asset_managers<S>(surface: &mut S, resources: &Resources)
@@ -78,6 +125,11 @@ where
         let mut shader_loader = resources.fetch_mut::<ShaderManager<S>>().unwrap();
         shader_loader.upload_all(surface);
     }
+
+    {
+        let mut particle_loader = resources.fetch_mut::<ParticleEffectManager<S>>().unwrap();
+        particle_loader.upload_all(surface);
+    }
 }
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
@@ -100,8 +152,14 @@ pub enum AssetError {
     #[error(transparent)]
     TextureError(#[from] luminance::texture::TextureError),
 
+    #[error(transparent)]
+    AudioError(#[from] rodio::decoder::DecoderError),
+
     #[error("Cannot find {0} in packed data")]
     PackedError(String),
+
+    #[error(transparent)]
+    PrefabError(#[from] prefab::PrefabError),
 }
 
 pub struct Asset<T> {
@@ -0,0 +1,55 @@
+use crate::assets::{Asset, AssetManager, Loader};
+use crate::render::particle::ParticleEmitter;
+use luminance::context::GraphicsContext;
+use luminance_gl::GL33;
+use std::path::{Path, PathBuf};
+
+/// Particle effects by name (e.g. "explosion" for assets/particle/explosion.json).
+pub type ParticleEffectManager<S> = AssetManager<S, ParticleEmitter>;
+
+pub struct ParticleSyncLoader {
+    base_path: PathBuf,
+}
+
+impl ParticleSyncLoader {
+    pub fn new<P: AsRef<Path>>(base_path: P) -> Self {
+        let base_path = base_path.as_ref();
+        Self {
+            base_path: base_path.to_path_buf(),
+        }
+    }
+}
+
+impl<S> Loader<S, ParticleEmitter, String> for ParticleSyncLoader
+where
+    S: GraphicsContext<Backend = GL33>,
+{
+    fn load(&mut self, asset_name: String) -> Asset<ParticleEmitter> {
+        let mut asset = Asset::new();
+        let asset_path = self.base_path.join(&asset_name).with_extension("json");
+        info!("Will load at path = {}", asset_path.display());
+
+        match std::fs::read_to_string(asset_path) {
+            Ok(asset_str) => {
+                let res: Result<ParticleEmitter, _> = serde_json::from_str(&asset_str);
+                match res {
+                    Ok(mut emitter) => {
+                        info!("Finished loading {}", asset_name);
+                        emitter.init_pool();
+                        asset.set_loaded(emitter)
+                    }
+                    Err(e) => {
+                        error!("Error while converting particle effect from json = {:?}", e);
+                        asset.set_error(e.into())
+                    }
+                }
+            }
+            Err(e) => {
+                error!("Error while reading from file = {:?}", e);
+                asset.set_error(e.into())
+            }
+        }
+
+        asset
+    }
+}
@@ -1,17 +1,327 @@
-use crate::assets::{Asset, AssetManager, Loader};
-use crate::core::transform::Transform;
-use hecs::{Entity, World};
+//! Prefabs are entity templates written in JSON. A prefab is a map of component name to component
+//! data, and the `ComponentRegistry` knows how to deserialize each component. Prefabs can extend
+//! another prefab (its components are used unless they are overridden) and have children, spawned
+//! with `HasParent`/`HasChildren`.
+//!
+//! ```json
+//! {
+//!   "extends": "base_enemy",
+//!   "components": {
+//!     "sprite": { "id": "spaceships/blue_05.png" }
+//!   },
+//!   "children": [
+//!     { "components": { "transform": { ... }, "sprite": { ... } } }
+//!   ]
+//! }
+//! ```
+//!
+//! The `transform` of a child is relative to its parent.
+//!
+//! The registry can also describe an existing entity (see `ComponentRegistry::describe`), so that
+//! entities edited in game can be saved as prefabs.
+use crate::assets::{Asset, AssetError, AssetManager, Loader};
+use crate::core::lifetime::DespawnWithParent;
+use crate::core::transform::{HasChildren, HasParent, LocalTransform, Transform};
+use hecs::{Entity, EntityBuilder, World};
 use luminance::context::GraphicsContext;
 use luminance_gl::GL33;
+use serde::de::DeserializeOwned;
 use serde_derive::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
 use std::path::{Path, PathBuf};
+use thiserror::Error;
 
-pub type PrefabManager<S> = AssetManager<S, Box<dyn Prefab>>;
+pub type PrefabManager<S> = AssetManager<S, Prefab>;
 
-#[typetag::serde]
-pub trait Prefab: std::fmt::Debug {
-    fn spawn(&self, world: &mut hecs::World) -> hecs::Entity;
-    fn spawn_with_transform(&self, world: &mut hecs::World, transform: Transform) -> hecs::Entity {
+#[derive(Debug, Error)]
+pub enum PrefabError {
+    #[error("Cannot read prefab {name}: {source}")]
+    Io {
+        name: String,
+        source: std::io::Error,
+    },
+
+    #[error("Cannot parse prefab {name}: {source}")]
+    Json {
+        name: String,
+        source: serde_json::Error,
+    },
+
+    #[error("Unknown component {0}")]
+    UnknownComponent(String),
+
+    #[error("Cannot deserialize component {name}: {source}")]
+    Component {
+        name: String,
+        source: serde_json::Error,
+    },
+
+    #[error("Prefabs extend each other: {0:?}")]
+    Cycle(Vec<String>),
+
+    #[error("Child entities should have a transform")]
+    ChildWithoutTransform,
+}
+
+/// Prefab as written in the JSON files.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PrefabDescription {
+    /// Name of the prefab to extend.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    /// Component name to component data. Replace the components of the extended prefab.
+    #[serde(default)]
+    pub components: BTreeMap<String, serde_json::Value>,
+    /// Added after the children of the extended prefab.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<PrefabDescription>,
+}
+
+impl PrefabDescription {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_base<S: Into<String>>(mut self, base: S) -> Self {
+        self.extends = Some(base.into());
+        self
+    }
+
+    pub fn with_component<S: Into<String>, T: serde::Serialize>(
+        mut self,
+        name: S,
+        component: &T,
+    ) -> Self {
+        let value = serde_json::to_value(component).expect("Component should be serializable");
+        self.components.insert(name.into(), value);
+        self
+    }
+
+    pub fn with_child(mut self, child: PrefabDescription) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Copy the components and children of the base prefab that are not in this one.
+    fn merge_base(&mut self, base: PrefabDescription) {
+        for (name, value) in base.components {
+            self.components.entry(name).or_insert(value);
+        }
+        let mut children = base.children;
+        children.append(&mut self.children);
+        self.children = children;
+    }
+}
+
+/// Component of a prefab, ready to be added to new entities.
+trait PrefabComponent: Debug + Send + Sync {
+    fn add_to(&self, builder: &mut EntityBuilder);
+}
+
+#[derive(Debug)]
+struct ComponentValue<T> {
+    value: T,
+    prepare: Option<fn(&mut T)>,
+}
+
+impl<T> PrefabComponent for ComponentValue<T>
+where
+    T: hecs::Component + Clone + Debug,
+{
+    fn add_to(&self, builder: &mut EntityBuilder) {
+        let mut value = self.value.clone();
+        if let Some(prepare) = self.prepare {
+            prepare(&mut value);
+        }
+        builder.add(value);
+    }
+}
+
+type ComponentLoader = Box<
+    dyn Fn(&serde_json::Value) -> Result<Box<dyn PrefabComponent>, serde_json::Error> + Send + Sync,
+>;
+
+/// Serialize the component of an entity, if it has one.
+type ComponentSaver = Box<
+    dyn Fn(&World, Entity) -> Option<Result<serde_json::Value, serde_json::Error>> + Send + Sync,
+>;
+
+struct RegisteredComponent {
+    load: ComponentLoader,
+    save: ComponentSaver,
+}
+
+/// Components that can be used in prefabs, by name.
+#[derive(Default)]
+pub struct ComponentRegistry {
+    components: HashMap<String, RegisteredComponent>,
+}
+
+impl ComponentRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_component<T>(self, name: &str) -> Self
+    where
+        T: hecs::Component + Clone + Debug + DeserializeOwned + serde::Serialize,
+    {
+        self.register::<T>(name, None)
+    }
+
+    /// `prepare` is called on the component of each new entity. (e.g. to allocate the
+    /// particles of an emitter)
+    pub fn with_prepared_component<T>(self, name: &str, prepare: fn(&mut T)) -> Self
+    where
+        T: hecs::Component + Clone + Debug + DeserializeOwned + serde::Serialize,
+    {
+        self.register::<T>(name, Some(prepare))
+    }
+
+    fn register<T>(mut self, name: &str, prepare: Option<fn(&mut T)>) -> Self
+    where
+        T: hecs::Component + Clone + Debug + DeserializeOwned + serde::Serialize,
+    {
+        self.components.insert(
+            name.to_string(),
+            RegisteredComponent {
+                load: Box::new(move |value| {
+                    let value: T = serde_json::from_value(value.clone())?;
+                    Ok(Box::new(ComponentValue { value, prepare }))
+                }),
+                save: Box::new(|world, entity| {
+                    world
+                        .get::<T>(entity)
+                        .ok()
+                        .map(|component| serde_json::to_value(&*component))
+                }),
+            },
+        );
+        self
+    }
+
+    /// Prefab description of an entity and its children, with all the registered components.
+    /// The `transform` of the children is their `LocalTransform`.
+    pub fn describe(
+        &self,
+        world: &World,
+        entity: Entity,
+    ) -> Result<PrefabDescription, PrefabError> {
+        let mut description = PrefabDescription::new();
+        for (name, component) in &self.components {
+            if let Some(value) = (component.save)(world, entity) {
+                let value = value.map_err(|source| PrefabError::Component {
+                    name: name.clone(),
+                    source,
+                })?;
+                description.components.insert(name.clone(), value);
+            }
+        }
+
+        if let Ok(children) = world.get::<HasChildren>(entity) {
+            for child in &children.children {
+                let mut child_description = self.describe(world, *child)?;
+                if let Some(local) = child_description.components.remove("local_transform") {
+                    child_description
+                        .components
+                        .insert("transform".to_string(), local);
+                }
+                description.children.push(child_description);
+            }
+        }
+
+        Ok(description)
+    }
+
+    fn deserialize(
+        &self,
+        name: &str,
+        value: &serde_json::Value,
+    ) -> Result<Box<dyn PrefabComponent>, PrefabError> {
+        let component = self
+            .components
+            .get(name)
+            .ok_or_else(|| PrefabError::UnknownComponent(name.to_string()))?;
+        (component.load)(value).map_err(|source| PrefabError::Component {
+            name: name.to_string(),
+            source,
+        })
+    }
+}
+
+/// Prefab ready to be spawned. The components are deserialized when the prefab is loaded.
+#[derive(Debug, Default)]
+pub struct Prefab {
+    components: Vec<Box<dyn PrefabComponent>>,
+    children: Vec<Prefab>,
+}
+
+impl Prefab {
+    pub fn from_description(
+        description: &PrefabDescription,
+        registry: &ComponentRegistry,
+    ) -> Result<Self, PrefabError> {
+        let components = description
+            .components
+            .iter()
+            .map(|(name, value)| registry.deserialize(name, value))
+            .collect::<Result<Vec<_>, _>>()?;
+        let children = description
+            .children
+            .iter()
+            .map(|child| {
+                if child.components.contains_key("transform") {
+                    Self::from_description(child, registry)
+                } else {
+                    Err(PrefabError::ChildWithoutTransform)
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            components,
+            children,
+        })
+    }
+
+    pub fn spawn(&self, world: &mut World) -> Entity {
+        let mut builder = EntityBuilder::new();
+        for component in &self.components {
+            component.add_to(&mut builder);
+        }
+        let entity = world.spawn(builder.build());
+
+        if !self.children.is_empty() {
+            let children = self
+                .children
+                .iter()
+                .map(|child| {
+                    let child_entity = child.spawn(world);
+                    let local_transform = LocalTransform::from(
+                        *world
+                            .get::<Transform>(child_entity)
+                            .expect("Child should have a transform"),
+                    );
+                    // Children are parts of the prefab and are deleted with it.
+                    world
+                        .insert(
+                            child_entity,
+                            (local_transform, HasParent { entity }, DespawnWithParent),
+                        )
+                        .expect("Child should exist");
+                    child_entity
+                })
+                .collect();
+            world
+                .insert_one(entity, HasChildren { children })
+                .expect("Prefab entity should exist");
+        }
+
+        entity
+    }
+
+    pub fn spawn_with_transform(&self, world: &mut World, transform: Transform) -> Entity {
         let e = self.spawn(world);
         world
             .insert_one(e, transform)
@@ -20,7 +330,7 @@ pub trait Prefab: std::fmt::Debug {
     }
 
     /// set the position only if the transform is already there
-    fn spawn_at_pos(&self, world: &mut hecs::World, pos: glam::Vec2) -> hecs::Entity {
+    pub fn spawn_at_pos(&self, world: &mut World, pos: glam::Vec2) -> Entity {
         let e = self.spawn(world);
 
         if let Ok(mut t) = world.get_mut::<Transform>(e) {
@@ -31,62 +341,80 @@ pub trait Prefab: std::fmt::Debug {
     }
 }
 
-impl Default for Box<dyn Prefab> {
-    fn default() -> Self {
-        Box::new(EmptyPrefab)
-    }
-}
-
-// That is necessary for the default implementation. Necessary for std::mem::swap when swapping asset
-// from loaded to ready.
-#[derive(Debug, Serialize, Deserialize)]
-pub struct EmptyPrefab;
-#[typetag::serde]
-impl Prefab for EmptyPrefab {
-    fn spawn(&self, _world: &mut World) -> Entity {
-        unimplemented!()
-    }
-}
-
 pub struct PrefabSyncLoader {
     base_path: PathBuf,
+    registry: ComponentRegistry,
 }
 
 impl PrefabSyncLoader {
-    pub fn new<P: AsRef<Path>>(base_path: P) -> Self {
+    pub fn new<P: AsRef<Path>>(base_path: P, registry: ComponentRegistry) -> Self {
         let base_path = base_path.as_ref();
         Self {
             base_path: base_path.to_path_buf(),
+            registry,
+        }
+    }
+
+    fn read_description(&self, name: &str) -> Result<PrefabDescription, PrefabError> {
+        let asset_path = self.base_path.join(name).with_extension("json");
+        info!("Will load at path = {}", asset_path.display());
+        let content = std::fs::read_to_string(asset_path).map_err(|source| PrefabError::Io {
+            name: name.to_string(),
+            source,
+        })?;
+        serde_json::from_str(&content).map_err(|source| PrefabError::Json {
+            name: name.to_string(),
+            source,
+        })
+    }
+
+    /// Merge the prefabs that are extended into the description. `chain` is the list of prefabs
+    /// being resolved, to detect cycles.
+    fn resolve(
+        &self,
+        mut description: PrefabDescription,
+        chain: &mut Vec<String>,
+    ) -> Result<PrefabDescription, PrefabError> {
+        if let Some(base_name) = description.extends.take() {
+            if chain.contains(&base_name) {
+                chain.push(base_name);
+                return Err(PrefabError::Cycle(chain.clone()));
+            }
+            chain.push(base_name.clone());
+            let base = self.resolve(self.read_description(&base_name)?, chain)?;
+            chain.pop();
+            description.merge_base(base);
         }
+
+        description.children = description
+            .children
+            .into_iter()
+            .map(|child| self.resolve(child, chain))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(description)
+    }
+
+    fn load_prefab(&self, asset_name: &str) -> Result<Prefab, PrefabError> {
+        let description = self.read_description(asset_name)?;
+        let description = self.resolve(description, &mut vec![asset_name.to_string()])?;
+        Prefab::from_description(&description, &self.registry)
     }
 }
 
-impl<S> Loader<S, Box<dyn Prefab>, String> for PrefabSyncLoader
+impl<S> Loader<S, Prefab, String> for PrefabSyncLoader
 where
     S: GraphicsContext<Backend = GL33>,
 {
-    fn load(&mut self, asset_name: String) -> Asset<Box<dyn Prefab>> {
+    fn load(&mut self, asset_name: String) -> Asset<Prefab> {
         let mut asset = Asset::new();
-        let asset_path = self.base_path.join(&asset_name).with_extension("json");
-        info!("Will load at path = {}", asset_path.display());
-
-        match std::fs::read_to_string(asset_path) {
-            Ok(asset_str) => {
-                let res: Result<Box<dyn Prefab>, _> = serde_json::from_str(&asset_str);
-                match res {
-                    Ok(val) => {
-                        info!("Finished loading {}", asset_name);
-                        asset.set_loaded(val)
-                    }
-                    Err(e) => {
-                        error!("Error while converting prefab from json = {:?}", e);
-                        asset.set_error(e.into())
-                    }
-                }
+        match self.load_prefab(&asset_name) {
+            Ok(prefab) => {
+                info!("Finished loading {}", asset_name);
+                asset.set_loaded(prefab)
             }
             Err(e) => {
-                error!("Error while reading from file = {:?}", e);
-                asset.set_error(e.into())
+                error!("Error while loading prefab {} = {}", asset_name, e);
+                asset.set_error(AssetError::from(e))
             }
         }
 
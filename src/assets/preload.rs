@@ -0,0 +1,197 @@
+//! Assets that a scene needs before it is created (see `Scene::assets`). They are requested while
+//! the transition to the scene is playing and the previous scene keeps rendering in the meantime.
+//!
+//! Audio is read and decoded on worker threads by its loader. Prefabs, sound banks and particle
+//! effects are small JSON files read on the main thread, a few per frame.
+use crate::assets::audio::Audio;
+use crate::assets::particle::ParticleEffectManager;
+use crate::assets::prefab::PrefabManager;
+use crate::assets::{AssetManager, Handle};
+use crate::core::audio::bank::{SoundBank, SoundBanks};
+use crate::resources::Resources;
+use luminance_glfw::GlfwSurface;
+use std::collections::VecDeque;
+
+/// Number of assets requested per frame. Keeps the JSON files from stalling a frame.
+const REQUESTS_PER_FRAME: usize = 2;
+
+#[derive(Debug, Clone, Default)]
+pub struct SceneAssets {
+    pub prefabs: Vec<String>,
+    /// Sound banks to load. All their clips are loaded as well.
+    pub banks: Vec<String>,
+    /// Other audio assets (e.g. music).
+    pub audio: Vec<String>,
+    /// Particle effects that are spawned during the scene.
+    pub effects: Vec<String>,
+}
+
+impl SceneAssets {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_prefabs(mut self, prefabs: Vec<String>) -> Self {
+        self.prefabs = prefabs;
+        self
+    }
+
+    pub fn with_banks(mut self, banks: Vec<String>) -> Self {
+        self.banks = banks;
+        self
+    }
+
+    /// Audio assets to load that are not in a sound bank.
+    pub fn with_audio(mut self, audio: Vec<String>) -> Self {
+        self.audio = audio;
+        self
+    }
+
+    /// Particle effects to load so that they can be spawned without delay.
+    pub fn with_effects(mut self, effects: Vec<String>) -> Self {
+        self.effects = effects;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PreloadStatus {
+    /// Fraction of the assets that are ready.
+    Loading(f32),
+    Done,
+    /// At least one asset could not be loaded. The others are ready.
+    Failed,
+}
+
+enum Request {
+    Prefab(String),
+    Bank(String),
+    Audio(String),
+    Effect(String),
+}
+
+pub struct Preload {
+    requests: VecDeque<Request>,
+    prefab_handles: Vec<Handle>,
+    audio_handles: Vec<Handle>,
+    effect_handles: Vec<Handle>,
+    bank_error: bool,
+}
+
+impl Preload {
+    pub fn new(assets: SceneAssets) -> Self {
+        // Banks first as their clips are added to the requests.
+        let requests = assets
+            .banks
+            .into_iter()
+            .map(Request::Bank)
+            .chain(assets.prefabs.into_iter().map(Request::Prefab))
+            .chain(assets.audio.into_iter().map(Request::Audio))
+            .chain(assets.effects.into_iter().map(Request::Effect))
+            .collect();
+        Self {
+            requests,
+            prefab_handles: vec![],
+            audio_handles: vec![],
+            effect_handles: vec![],
+            bank_error: false,
+        }
+    }
+
+    /// Request the next assets and check the ones requested before. Assets become ready after the
+    /// asset managers are updated at the end of the frame.
+    pub fn poll(&mut self, resources: &Resources) -> PreloadStatus {
+        for _ in 0..REQUESTS_PER_FRAME {
+            match self.requests.pop_front() {
+                Some(request) => self.request(request, resources),
+                None => break,
+            }
+        }
+
+        let prefab_manager = resources.fetch::<PrefabManager<GlfwSurface>>().unwrap();
+        let audio_manager = resources
+            .fetch::<AssetManager<GlfwSurface, Audio>>()
+            .unwrap();
+        let particle_manager = resources
+            .fetch::<ParticleEffectManager<GlfwSurface>>()
+            .unwrap();
+
+        let mut nb_loaded = self
+            .prefab_handles
+            .iter()
+            .filter(|h| prefab_manager.is_loaded(h))
+            .count();
+        let mut nb_error = self
+            .prefab_handles
+            .iter()
+            .filter(|h| prefab_manager.is_error(h))
+            .count();
+        nb_loaded += self
+            .audio_handles
+            .iter()
+            .filter(|h| audio_manager.is_loaded(h))
+            .count();
+        nb_error += self
+            .audio_handles
+            .iter()
+            .filter(|h| audio_manager.is_error(h))
+            .count();
+        nb_loaded += self
+            .effect_handles
+            .iter()
+            .filter(|h| particle_manager.is_loaded(h))
+            .count();
+        nb_error += self
+            .effect_handles
+            .iter()
+            .filter(|h| particle_manager.is_error(h))
+            .count();
+
+        let nb_requested =
+            self.prefab_handles.len() + self.audio_handles.len() + self.effect_handles.len();
+        if !self.requests.is_empty() || nb_loaded + nb_error < nb_requested {
+            let total = nb_requested + self.requests.len();
+            PreloadStatus::Loading(nb_loaded as f32 / total as f32)
+        } else if nb_error > 0 || self.bank_error {
+            PreloadStatus::Failed
+        } else {
+            PreloadStatus::Done
+        }
+    }
+
+    fn request(&mut self, request: Request, resources: &Resources) {
+        match request {
+            Request::Prefab(name) => {
+                let mut prefab_manager =
+                    resources.fetch_mut::<PrefabManager<GlfwSurface>>().unwrap();
+                self.prefab_handles.push(prefab_manager.load(name));
+            }
+            Request::Bank(name) => match SoundBank::load_from_path(SoundBank::path(&name)) {
+                Ok(bank) => {
+                    self.requests
+                        .extend(bank.clips().into_iter().map(Request::Audio));
+                    resources
+                        .fetch_mut::<SoundBanks>()
+                        .unwrap()
+                        .insert(name, bank);
+                }
+                Err(e) => {
+                    error!("Cannot load sound bank {} = {:?}", name, e);
+                    self.bank_error = true;
+                }
+            },
+            Request::Audio(name) => {
+                let mut audio_manager = resources
+                    .fetch_mut::<AssetManager<GlfwSurface, Audio>>()
+                    .unwrap();
+                self.audio_handles.push(audio_manager.load(name));
+            }
+            Request::Effect(name) => {
+                let mut particle_manager = resources
+                    .fetch_mut::<ParticleEffectManager<GlfwSurface>>()
+                    .unwrap();
+                self.effect_handles.push(particle_manager.load(name));
+            }
+        }
+    }
+}
@@ -1,7 +1,7 @@
 #![allow(warnings)]
 
 use downcast_rs::__std::collections::HashMap;
-use spacegame::assets::prefab::Prefab;
+use spacegame::assets::prefab::PrefabDescription;
 use spacegame::core::animation::{Animation, AnimationController};
 use spacegame::core::timer::Timer;
 use spacegame::core::transform::Transform;
@@ -9,15 +9,133 @@ use spacegame::gameplay::collision::{BoundingBox, CollisionLayer};
 use spacegame::gameplay::enemy::{
     Boss1, Enemy, EnemyType, LastBoss, MovementBehavior, Satellite, Spammer,
 };
-use spacegame::gameplay::health::Health;
+use spacegame::gameplay::health::{Health, Shield};
 use spacegame::gameplay::physics::DynamicBody;
-use spacegame::gameplay::player::{Player, Stats};
-use spacegame::prefab::enemies::EnemyPrefab;
-use spacegame::prefab::player::PlayerPrefab;
+use spacegame::gameplay::player::{Player, Stats, Weapon};
+use spacegame::gameplay::trail::Trail;
+use spacegame::render::mesh::{Material, MeshRender};
 use spacegame::render::particle::ParticleEmitter;
 use spacegame::render::sprite::Sprite;
 use std::path::PathBuf;
 
+/// Components of an enemy. Written as a generic prefab.
+struct EnemyPrefab {
+    dynamic_body: DynamicBody,
+    transform: Transform,
+    sprite: Sprite,
+    bounding_box: BoundingBox,
+    health: Option<Health>,
+    shield: Option<Shield>,
+    enemy: Enemy,
+    trail: Option<ParticleEmitter>,
+    animation: Option<AnimationController>,
+}
+
+impl EnemyPrefab {
+    fn description(&self) -> PrefabDescription {
+        let mut description = PrefabDescription::new()
+            .with_component("dynamic_body", &self.dynamic_body)
+            .with_component("transform", &self.transform)
+            .with_component("sprite", &self.sprite)
+            .with_component("bounding_box", &self.bounding_box)
+            .with_component("enemy", &self.enemy);
+        if let Some(ref health) = self.health {
+            description = description.with_component("health", health);
+        }
+        if let Some(ref shield) = self.shield {
+            description = description.with_component("shield", shield);
+        }
+        if let Some(ref trail) = self.trail {
+            description = description
+                .with_component("particle_emitter", trail)
+                .with_component(
+                    "trail",
+                    &Trail {
+                        should_display: true,
+                        offset: 0.0,
+                    },
+                );
+        }
+        if let Some(ref animation) = self.animation {
+            description = description.with_component("animation", animation);
+        }
+        description
+    }
+}
+
+/// Keep only what is different from base_enemy.
+fn extend_base_enemy(mut description: PrefabDescription) -> PrefabDescription {
+    description
+        .components
+        .retain(|name, _| ["dynamic_body", "sprite", "enemy"].contains(&name.as_str()));
+    description.with_base("base_enemy")
+}
+
+/// Components of the player. Written as a generic prefab, with the shield as a child.
+struct PlayerPrefab {
+    dynamic_body: DynamicBody,
+    transform: Transform,
+    sprite: Sprite,
+    bounding_box: BoundingBox,
+    health: Health,
+    shield: Option<Shield>,
+    trail: ParticleEmitter,
+    stats: Stats,
+}
+
+impl PlayerPrefab {
+    fn description(&self) -> PrefabDescription {
+        let shield = PrefabDescription::new()
+            .with_component(
+                "transform",
+                &Transform {
+                    translation: glam::Vec2::zero(),
+                    scale: self.transform.scale * 2.0,
+                    rotation: 0.0,
+                    dirty: true,
+                },
+            )
+            .with_component(
+                "mesh_render",
+                &MeshRender {
+                    enabled: true,
+                    material: Material::Shader {
+                        vertex_shader_id: "simple-vs.glsl".to_string(),
+                        fragment_shader_id: "simple-fs.glsl".to_string(),
+                    },
+                },
+            );
+
+        let mut description = PrefabDescription::new()
+            .with_component("dynamic_body", &self.dynamic_body)
+            .with_component("transform", &self.transform)
+            .with_component("sprite", &self.sprite)
+            .with_component("bounding_box", &self.bounding_box)
+            .with_component("health", &self.health)
+            .with_component("particle_emitter", &self.trail)
+            .with_component(
+                "trail",
+                &Trail {
+                    should_display: true,
+                    offset: 20.0,
+                },
+            )
+            .with_component(
+                "player",
+                &Player {
+                    weapon: Weapon::Simple,
+                    stats: self.stats.clone(),
+                    direction: glam::vec2(0.0, 1.0),
+                },
+            )
+            .with_child(shield);
+        if let Some(ref shield) = self.shield {
+            description = description.with_component("shield", shield);
+        }
+        description
+    }
+}
+
 fn gen_player() {
     let player = {
         let base_path = std::env::var("ASSET_PATH").unwrap_or("assets/".to_string());
@@ -60,11 +178,11 @@ fn gen_player() {
                 missile_percent: 0,
                 boost_timer: Timer::of_seconds(1.0),
                 boost_magnitude: 500.0,
+                fire_timer: Timer::of_seconds(0.25),
             },
         };
 
-        let prefab = &player_prefab as &dyn Prefab;
-        serde_json::to_string_pretty(prefab).unwrap()
+        serde_json::to_string_pretty(&player_prefab.description()).unwrap()
     };
 
     std::fs::write("assets/prefab/player.json", player);
@@ -134,14 +252,13 @@ fn gen_mine() {
                 let mut animation_controller = AnimationController {
                     animations,
                     current_animation: None,
-                    delete_on_finished: false,
+                    play_once: false,
                 };
                 animation_controller
             }),
         };
 
-        let prefab = &enemy_prefab as &dyn Prefab;
-        serde_json::to_string_pretty(prefab).unwrap()
+        serde_json::to_string_pretty(&enemy_prefab.description()).unwrap()
     };
 
     std::fs::write("assets/prefab/mine.json", mine);
@@ -185,8 +302,7 @@ fn gen_mine_lander() {
             animation: None,
         };
 
-        let prefab = &enemy_prefab as &dyn Prefab;
-        serde_json::to_string_pretty(prefab).unwrap()
+        serde_json::to_string_pretty(&enemy_prefab.description()).unwrap()
     };
 
     std::fs::write("assets/prefab/mine_lander.json", mine_lander);
@@ -230,8 +346,7 @@ fn gen_wanderer() {
             animation: None,
         };
 
-        let prefab = &enemy_prefab as &dyn Prefab;
-        serde_json::to_string_pretty(prefab).unwrap()
+        serde_json::to_string_pretty(&enemy_prefab.description()).unwrap()
     };
 
     std::fs::write("assets/prefab/wanderer.json", prefab);
@@ -281,8 +396,7 @@ fn gen_base_enemy() {
             animation: None,
         };
 
-        let prefab = &enemy_prefab as &dyn Prefab;
-        serde_json::to_string_pretty(prefab).unwrap()
+        serde_json::to_string_pretty(&enemy_prefab.description()).unwrap()
     };
 
     std::fs::write("assets/prefab/base_enemy.json", base_enemy);
@@ -335,8 +449,7 @@ fn gen_carrier() {
             animation: None,
         };
 
-        let prefab = &enemy_prefab as &dyn Prefab;
-        serde_json::to_string_pretty(prefab).unwrap()
+        serde_json::to_string_pretty(&enemy_prefab.description()).unwrap()
     };
 
     std::fs::write("assets/prefab/carrier.json", base_enemy);
@@ -386,8 +499,7 @@ fn gen_kamikaze() {
             animation: None,
         };
 
-        let prefab = &enemy_prefab as &dyn Prefab;
-        serde_json::to_string_pretty(prefab).unwrap()
+        serde_json::to_string_pretty(&enemy_prefab.description()).unwrap()
     };
 
     std::fs::write("assets/prefab/kamikaze.json", base_enemy);
@@ -437,8 +549,7 @@ fn gen_base_enemy_2() {
             trail: Some(emitter),
         };
 
-        let prefab = &enemy_prefab as &dyn Prefab;
-        serde_json::to_string_pretty(prefab).unwrap()
+        serde_json::to_string_pretty(&extend_base_enemy(enemy_prefab.description())).unwrap()
     };
 
     std::fs::write("assets/prefab/base_enemy_2.json", base_enemy);
@@ -488,8 +599,7 @@ fn gen_base_enemy_3() {
             trail: Some(emitter),
         };
 
-        let prefab = &enemy_prefab as &dyn Prefab;
-        serde_json::to_string_pretty(prefab).unwrap()
+        serde_json::to_string_pretty(&extend_base_enemy(enemy_prefab.description())).unwrap()
     };
 
     std::fs::write("assets/prefab/base_enemy_3.json", base_enemy);
@@ -538,8 +648,7 @@ fn gen_spammer() {
             trail: None,
         };
 
-        let prefab = &enemy_prefab as &dyn Prefab;
-        serde_json::to_string_pretty(prefab).unwrap()
+        serde_json::to_string_pretty(&enemy_prefab.description()).unwrap()
     };
 
     std::fs::write("assets/prefab/spammer.json", spammer);
@@ -588,8 +697,7 @@ fn gen_last_boss() {
             trail: None,
         };
 
-        let prefab = &enemy_prefab as &dyn Prefab;
-        serde_json::to_string_pretty(prefab).unwrap()
+        serde_json::to_string_pretty(&enemy_prefab.description()).unwrap()
     };
 
     std::fs::write("assets/prefab/last_boss.json", boss);
@@ -647,8 +755,7 @@ fn main() {
             trail: None,
         };
 
-        let prefab = &enemy_prefab as &dyn Prefab;
-        serde_json::to_string_pretty(prefab).unwrap()
+        serde_json::to_string_pretty(&enemy_prefab.description()).unwrap()
     };
 
     std::fs::write("assets/prefab/satellite.json", satellite);
@@ -696,8 +803,7 @@ fn main() {
             trail: None,
         };
 
-        let prefab = &enemy_prefab as &dyn Prefab;
-        serde_json::to_string_pretty(prefab).unwrap()
+        serde_json::to_string_pretty(&enemy_prefab.description()).unwrap()
     };
 
     std::fs::write("assets/prefab/boss1.json", boss);
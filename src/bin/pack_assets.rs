@@ -2,12 +2,13 @@
 
 use downcast_rs::__std::ffi::{OsStr, OsString};
 use log::{debug, error, info};
+use spacegame::assets::audio::PackedAudio;
 use spacegame::assets::sprite::{
     load_texels, Packed, PackedSpriteAsset, SamplerDef, SpriteAssetMetadata,
 };
 use std::collections::HashMap;
 use std::fs::FileType;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 fn load_metadata(base_path: PathBuf, asset_name: &str) -> SpriteAssetMetadata {
     let metadata_path = base_path.join(asset_name).with_extension("json");
@@ -103,4 +104,50 @@ fn main() {
 
     let res = bincode::serialize(&packed).unwrap();
     std::fs::write("packed.bin", res).unwrap();
+
+    if let Err(e) = pack_audio() {
+        eprintln!("Cannot pack the audio assets = {:?}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Extensions of the audio files to pack. The sound banks and the adaptive music descriptions are
+/// JSON files that stay in the assets folder.
+const AUDIO_EXTENSIONS: [&str; 4] = ["wav", "ogg", "mp3", "flac"];
+
+fn is_audio(path: &Path) -> bool {
+    path.extension()
+        .and_then(OsStr::to_str)
+        .map(|ext| AUDIO_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Musics are packed as they are (not decoded) so that they can be streamed.
+fn pack_audio() -> Result<(), anyhow::Error> {
+    let dirs = vec!["./assets/music", "./assets/sounds"];
+
+    let mut content = HashMap::new();
+    for d in dirs {
+        let mut to_visit = vec![PathBuf::from(d)];
+        while let Some(dir) = to_visit.pop() {
+            for entry in std::fs::read_dir(&dir)
+                .map_err(|e| anyhow::anyhow!("Cannot read {}: {}", dir.display(), e))?
+            {
+                let path = entry?.path();
+                if path.is_dir() {
+                    to_visit.push(path);
+                } else if is_audio(&path) {
+                    let name = path.display().to_string()["./assets/".len()..].to_string();
+                    let data = std::fs::read(&path)
+                        .map_err(|e| anyhow::anyhow!("Cannot read {}: {}", path.display(), e))?;
+                    content.insert(name, data);
+                }
+            }
+        }
+    }
+
+    info!("Packed {} audio files", content.len());
+    let res = bincode::serialize(&PackedAudio { content })?;
+    std::fs::write("packed_audio.bin", res)?;
+    Ok(())
 }
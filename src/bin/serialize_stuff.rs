@@ -1,20 +1,73 @@
 #![allow(warnings)]
 use spacegame::config::InputConfig;
-use spacegame::core::input::ser::{BasicKey, BasicMouseButton, Input};
+use spacegame::core::input::ser::{
+    BasicAxisDirection, BasicGamepadAxis, BasicGamepadButton, BasicKey, BasicMouseButton, Input,
+};
 use spacegame::gameplay::Action;
 use std::collections::HashMap;
 
 fn main() {
     let mut input_map = HashMap::new();
-    input_map.insert(Action::Shoot, Input::Mouse(BasicMouseButton::Button1));
-    input_map.insert(Action::MoveUp, Input::Key(BasicKey::W));
-    input_map.insert(Action::Boost, Input::Key(BasicKey::Space));
-    input_map.insert(Action::Pickup, Input::Key(BasicKey::F));
-    input_map.insert(Action::MoveLeft, Input::Key(BasicKey::A));
-    input_map.insert(Action::MoveRight, Input::Key(BasicKey::D));
-    input_map.insert(Action::RotateLeft, Input::Key(BasicKey::Q));
-    input_map.insert(Action::RotateRight, Input::Key(BasicKey::E));
-    let config = InputConfig(input_map);
+    input_map.insert(
+        Action::Shoot,
+        vec![
+            Input::Mouse(BasicMouseButton::Button1),
+            Input::GamepadAxis(BasicGamepadAxis::RightTrigger, BasicAxisDirection::Positive),
+        ],
+    );
+    input_map.insert(
+        Action::MoveUp,
+        vec![
+            Input::Key(BasicKey::W),
+            Input::GamepadAxis(BasicGamepadAxis::LeftY, BasicAxisDirection::Negative),
+        ],
+    );
+    input_map.insert(
+        Action::Boost,
+        vec![
+            Input::Key(BasicKey::Space),
+            Input::GamepadButton(BasicGamepadButton::A),
+        ],
+    );
+    input_map.insert(
+        Action::Pickup,
+        vec![
+            Input::Key(BasicKey::F),
+            Input::GamepadButton(BasicGamepadButton::X),
+        ],
+    );
+    input_map.insert(
+        Action::MoveLeft,
+        vec![
+            Input::Key(BasicKey::A),
+            Input::GamepadAxis(BasicGamepadAxis::LeftX, BasicAxisDirection::Negative),
+        ],
+    );
+    input_map.insert(
+        Action::MoveRight,
+        vec![
+            Input::Key(BasicKey::D),
+            Input::GamepadAxis(BasicGamepadAxis::LeftX, BasicAxisDirection::Positive),
+        ],
+    );
+    input_map.insert(
+        Action::RotateLeft,
+        vec![
+            Input::Key(BasicKey::Q),
+            Input::GamepadButton(BasicGamepadButton::LeftBumper),
+        ],
+    );
+    input_map.insert(
+        Action::RotateRight,
+        vec![
+            Input::Key(BasicKey::E),
+            Input::GamepadButton(BasicGamepadButton::RightBumper),
+        ],
+    );
+    let config = InputConfig {
+        deadzone: 0.2,
+        bindings: input_map,
+    };
 
     let to_str = serde_json::to_string_pretty(&config).unwrap();
     std::fs::write("./assets/config/input.json", to_str.clone()).unwrap();
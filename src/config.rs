@@ -1,6 +1,10 @@
+use crate::core::audio;
+use crate::core::input::gamepad::DEFAULT_DEADZONE;
 use crate::core::input::ser::Input;
+use crate::core::input::{Binding, InputAction, InputMapping};
+use crate::core::window::{DisplayMode, ScaleMode};
 use crate::gameplay::Action;
-use glfw::{Key, MouseButton};
+use crate::{HEIGHT, WIDTH};
 use serde::de::DeserializeOwned;
 use serde_derive::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -15,6 +19,15 @@ where
     serde_json::from_str(&content).map_err(|e| e.into())
 }
 
+pub fn save_config<T, P: AsRef<Path>>(path: P, config: &T) -> Result<(), Box<dyn Error>>
+where
+    T: serde::Serialize,
+{
+    let content = serde_json::to_string_pretty(config)?;
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PlayerConfig {
     pub lateral_thrust: f32,
@@ -36,43 +49,285 @@ impl PlayerConfig {
         serde_json::from_str(&content).map_err(|e| e.into())
     }
 }
+/// Parameters of the camera controller.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraConfig {
+    /// How fast the camera catches up with the player. Higher is snappier.
+    pub follow_smoothing: f32,
+    /// Fraction of the distance to the mouse cursor the camera will look ahead.
+    pub look_ahead: f32,
+    /// Maximum distance of the look ahead in world units.
+    pub max_look_ahead: f32,
+    /// Default zoom of the camera.
+    pub zoom: f32,
+    /// How fast the camera reaches a new zoom level.
+    pub zoom_smoothing: f32,
+    /// Displacement of the camera in world units when trauma is 1.
+    pub max_shake_offset: f32,
+    /// Frequency of the shake noise.
+    pub shake_frequency: f32,
+    /// Amount of trauma removed per second.
+    pub trauma_decay: f32,
+    /// Trauma added when something explodes.
+    pub explosion_trauma: f32,
+    /// Trauma added when the player is hit.
+    pub hit_trauma: f32,
+}
+
+impl Default for CameraConfig {
+    fn default() -> Self {
+        Self {
+            follow_smoothing: 8.0,
+            look_ahead: 0.2,
+            max_look_ahead: 150.0,
+            zoom: 1.0,
+            zoom_smoothing: 3.0,
+            max_shake_offset: 20.0,
+            shake_frequency: 15.0,
+            trauma_decay: 1.5,
+            explosion_trauma: 0.4,
+            hit_trauma: 0.3,
+        }
+    }
+}
+
+/// Window and resolution settings.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GraphicsConfig {
+    /// Virtual resolution used by the gameplay and the UI.
+    pub width: u32,
+    pub height: u32,
+    /// Size of the window when not in fullscreen.
+    pub window_width: u32,
+    pub window_height: u32,
+    /// How the virtual resolution is scaled to the window.
+    pub scale_mode: ScaleMode,
+    pub display_mode: DisplayMode,
+}
+
+impl Default for GraphicsConfig {
+    fn default() -> Self {
+        Self {
+            width: WIDTH,
+            height: HEIGHT,
+            window_width: WIDTH,
+            window_height: HEIGHT,
+            scale_mode: ScaleMode::default(),
+            display_mode: DisplayMode::default(),
+        }
+    }
+}
+
 #[derive(Default, Debug, Serialize, Deserialize)]
 pub struct GameEngineConfig {
     pub show_gizmos: bool,
+    /// Allow opening the entity inspector with F9.
+    #[serde(default)]
+    pub inspector: bool,
+    /// Measure the frames. The profiler is opened with F8 and F7 records a trace.
+    #[serde(default)]
+    pub profiler: bool,
 }
 
-#[derive(Default, Debug, Serialize, Deserialize)]
-pub struct InputConfig(pub HashMap<Action, Input>);
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(from = "InputConfigFormat")]
+pub struct InputConfig {
+    /// Stick values under this are ignored.
+    pub deadzone: f32,
+    /// Several inputs can trigger the same action (e.g. key and gamepad button).
+    pub bindings: HashMap<Action, Vec<Input>>,
+}
+
+/// Formats of input.json that can be loaded.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum InputConfigFormat {
+    Current {
+        #[serde(default = "default_deadzone")]
+        deadzone: f32,
+        bindings: HashMap<Action, Vec<Input>>,
+    },
+    /// Before the gamepad support: one input per action.
+    Legacy(HashMap<Action, Input>),
+}
+
+impl From<InputConfigFormat> for InputConfig {
+    fn from(format: InputConfigFormat) -> Self {
+        match format {
+            InputConfigFormat::Current { deadzone, bindings } => Self { deadzone, bindings },
+            InputConfigFormat::Legacy(bindings) => {
+                log::warn!(
+                    "Input config uses the old format (one input per action). The default \
+                     gamepad bindings are added."
+                );
+                let mut bindings: HashMap<Action, Vec<Input>> = bindings
+                    .into_iter()
+                    .map(|(action, input)| (action, vec![input]))
+                    .collect();
+                for (input, action) in Action::get_default_gamepad_mapping() {
+                    if let Some(input) = Input::from_binding(Binding::Gamepad(input)) {
+                        bindings.entry(action).or_default().push(input);
+                    }
+                }
+                Self {
+                    deadzone: DEFAULT_DEADZONE,
+                    bindings,
+                }
+            }
+        }
+    }
+}
+
+fn default_deadzone() -> f32 {
+    DEFAULT_DEADZONE
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        Self {
+            deadzone: DEFAULT_DEADZONE,
+            bindings: HashMap::new(),
+        }
+    }
+}
 
 impl InputConfig {
-    pub fn input_maps(self) -> (HashMap<Key, Action>, HashMap<MouseButton, Action>) {
-        let mut btn_map = HashMap::new();
-        let mut key_map = HashMap::new();
-
-        for (action, input) in self.0 {
-            match input {
-                Input::Key(k) => key_map.insert(k.into(), action),
-                Input::Mouse(btn) => btn_map.insert(btn.into(), action),
-            };
+    pub fn input_maps(self) -> InputMapping<Action> {
+        let mut mapping = InputMapping::empty(self.deadzone);
+        for (action, inputs) in self.bindings {
+            for input in inputs {
+                mapping.bind(input.into(), action);
+            }
         }
 
-        (key_map, btn_map)
+        mapping
+    }
+
+    /// Config that can be saved from the bindings used in game.
+    pub fn from_mapping(mapping: &InputMapping<Action>) -> Self {
+        let bindings = Action::all()
+            .iter()
+            .map(|action| {
+                let inputs = mapping
+                    .bindings(action)
+                    .into_iter()
+                    .filter_map(Input::from_binding)
+                    .collect();
+                (*action, inputs)
+            })
+            .collect();
+
+        Self {
+            deadzone: mapping.deadzone,
+            bindings,
+        }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AudioConfig {
-    pub background_volume: u32,
-    pub effects_volume: u32,
+    #[serde(default)]
+    pub backend: AudioBackend,
+    /// Maximum number of sounds played at the same time.
     pub channel_nb: usize,
+    /// Settings for each bus (music, sfx, ui...).
+    #[serde(default = "default_buses")]
+    pub buses: HashMap<String, BusConfig>,
+    /// Duration in seconds of the crossfade between two musics.
+    #[serde(default = "default_crossfade")]
+    pub crossfade: f32,
+    /// Sounds closer than this to the center of the screen are played at full volume.
+    #[serde(default = "default_min_distance")]
+    pub min_distance: f32,
+    /// Sounds further than this from the center of the screen are not played.
+    #[serde(default = "default_max_distance")]
+    pub max_distance: f32,
+    /// Horizontal distance from the center of the screen at which a sound is only heard on one
+    /// side.
+    #[serde(default = "default_pan_distance")]
+    pub pan_distance: f32,
+}
+
+#[derive(Debug, Copy, Clone, Default, Serialize, Deserialize)]
+pub enum AudioBackend {
+    /// Default output device of the system.
+    #[default]
+    Default,
+    /// Do not play anything. Used when there is no output device.
+    Null,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BusConfig {
+    /// Between 0 and 100.
+    pub volume: u32,
+    #[serde(default)]
+    pub muted: bool,
+    /// Name of the buses that reduce the volume of this one while they are playing.
+    #[serde(default)]
+    pub ducked_by: Vec<String>,
+    /// Volume multiplier (between 0 and 100) when the bus is ducked.
+    #[serde(default = "default_duck_volume")]
+    pub duck_volume: u32,
+}
+
+impl BusConfig {
+    fn new(volume: u32) -> Self {
+        Self {
+            volume,
+            muted: false,
+            ducked_by: vec![],
+            duck_volume: default_duck_volume(),
+        }
+    }
+}
+
+fn default_buses() -> HashMap<String, BusConfig> {
+    let mut buses = HashMap::new();
+    buses.insert(
+        audio::MUSIC_BUS.to_string(),
+        BusConfig {
+            ducked_by: vec![audio::UI_BUS.to_string()],
+            ..BusConfig::new(100)
+        },
+    );
+    buses.insert(audio::SFX_BUS.to_string(), BusConfig::new(100));
+    buses.insert(audio::UI_BUS.to_string(), BusConfig::new(100));
+    buses
+}
+
+fn default_crossfade() -> f32 {
+    2.0
+}
+
+fn default_min_distance() -> f32 {
+    300.0
+}
+
+fn default_max_distance() -> f32 {
+    1500.0
+}
+
+fn default_pan_distance() -> f32 {
+    800.0
+}
+
+fn default_duck_volume() -> u32 {
+    40
 }
 
 impl Default for AudioConfig {
     fn default() -> Self {
         Self {
-            background_volume: 100,
-            effects_volume: 100,
+            backend: AudioBackend::default(),
             channel_nb: 15,
+            buses: default_buses(),
+            crossfade: default_crossfade(),
+            min_distance: default_min_distance(),
+            max_distance: default_max_distance(),
+            pan_distance: default_pan_distance(),
         }
     }
 }
@@ -1,10 +1,15 @@
-use crate::event::GameEvent;
+use crate::core::system::{Access, ParallelSystem, System};
 use crate::render::sprite::Sprite;
 use crate::resources::Resources;
+use hecs::World;
 use log::error;
 use serde_derive::{Deserialize, Serialize};
-use shrev::EventChannel;
 use std::collections::HashMap;
+use std::time::Duration;
+
+/// Duration of one animation frame. Animations follow the time of the scene so they are slowed
+/// down or sped up with it.
+pub const FRAME_DURATION: Duration = Duration::from_millis(16);
 
 /// One animation (in one spreadsheet).
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
@@ -17,6 +22,10 @@ pub struct Animation {
     pub current_index: usize,
     // in seconds
     pub elapsed_frame: usize,
+
+    /// Time that has not been turned into frames yet.
+    #[serde(skip)]
+    pub elapsed_time: Duration,
 }
 
 impl Animation {
@@ -25,12 +34,19 @@ impl Animation {
             keyframes,
             current_index: 0,
             elapsed_frame: 0,
+            elapsed_time: Duration::default(),
         }
     }
 
     pub fn last_frame(&self) -> bool {
         self.keyframes.len() == self.current_index + 1
     }
+
+    /// Time to play all the keyframes once.
+    pub fn duration(&self) -> Duration {
+        let frames: usize = self.keyframes.iter().map(|(_, n)| n + 1).sum();
+        FRAME_DURATION * frames as u32
+    }
 }
 
 /// All Animations for an entity
@@ -43,42 +59,68 @@ pub struct AnimationController {
     /// if set to something, will play the corresponding animation
     pub current_animation: Option<String>,
 
+    /// If true, the animation stays on its last keyframe instead of starting again. Add a
+    /// `TimeToLive` of `Animation::duration` to despawn the entity when it is finished.
     #[serde(default)]
-    pub delete_on_finished: bool,
+    pub play_once: bool,
 }
 
 pub struct AnimationSystem;
 
 impl AnimationSystem {
-    pub fn animate(&mut self, world: &mut hecs::World, resources: &Resources) {
-        let mut events = vec![];
-        for (e, (controller, sprite)) in world
+    pub fn animate(&mut self, world: &hecs::World, dt: Duration) {
+        for (_, (controller, sprite)) in world
             .query::<(&mut AnimationController, &mut Sprite)>()
             .iter()
         {
             if let Some(ref animation_name) = controller.current_animation {
                 if let Some(ref mut animation) = controller.animations.get_mut(animation_name) {
-                    sprite.id = animation.keyframes[animation.current_index].0.clone();
+                    animation.elapsed_time += dt;
+                    while animation.elapsed_time >= FRAME_DURATION {
+                        animation.elapsed_time -= FRAME_DURATION;
+                        sprite.id = animation.keyframes[animation.current_index].0.clone();
 
-                    animation.elapsed_frame += 1;
-                    if animation.elapsed_frame > animation.keyframes[animation.current_index].1 {
-                        animation.elapsed_frame = 0;
+                        animation.elapsed_frame += 1;
+                        if animation.elapsed_frame > animation.keyframes[animation.current_index].1
+                        {
+                            animation.elapsed_frame = 0;
 
-                        if animation.last_frame() && controller.delete_on_finished {
-                            events.push(GameEvent::Delete(e));
+                            if !(animation.last_frame() && controller.play_once) {
+                                animation.current_index =
+                                    (animation.current_index + 1) % animation.keyframes.len();
+                            }
                         }
-                        animation.current_index =
-                            (animation.current_index + 1) % animation.keyframes.len();
                     }
                 } else {
                     error!("Cannot find animation with name = {}", animation_name);
                 }
             }
         }
+    }
+}
 
-        {
-            let mut channel = resources.fetch_mut::<EventChannel<GameEvent>>().unwrap();
-            channel.drain_vec_write(&mut events);
-        }
+impl System for AnimationSystem {
+    fn name(&self) -> &'static str {
+        "animation"
+    }
+
+    fn access(&self) -> Access {
+        Access::new()
+            .write_component::<AnimationController>()
+            .write_component::<Sprite>()
+    }
+
+    fn run(&mut self, world: &mut World, _resources: &Resources, dt: Duration) {
+        self.animate(world, dt);
+    }
+
+    fn as_parallel(&mut self) -> Option<&mut dyn ParallelSystem> {
+        Some(self)
+    }
+}
+
+impl ParallelSystem for AnimationSystem {
+    fn run_parallel(&mut self, world: &World, dt: Duration) {
+        self.animate(world, dt);
     }
 }
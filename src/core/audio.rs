@@ -1,145 +1,675 @@
-use crate::assets::audio::Audio;
+//! Audio playback. Sounds are played on named buses (music, sfx, ui...) which have their own
+//! volume and can be muted or ducked when another bus is playing. Sounds that have a position
+//! are panned and attenuated depending on their distance to the center of the screen. Sounds
+//! are referenced by their name in the sound banks (see `bank`).
+//!
+//! If there is no output device (or if the null backend is selected in the configuration),
+//! events are still consumed but nothing is played.
+use crate::assets::audio::{Audio, AudioSource};
 use crate::assets::{AssetManager, Handle};
-use crate::config::AudioConfig;
-use crate::event::GameEvent;
+use crate::config::{AudioBackend, AudioConfig, BusConfig};
+use crate::core::audio::adaptive::AdaptiveMusic;
+use crate::core::audio::bank::{SoundBanks, SoundDefinition};
+use crate::core::camera::{get_camera_center, get_camera_zoom};
+use crate::core::events::{register_reader, send_event, EventReader, Events};
+use crate::core::window::WindowDim;
+use crate::event::AudioEvent;
 use crate::resources::Resources;
+use hecs::World;
 use luminance_glfw::GlfwSurface;
-use shrev::{EventChannel, ReaderId};
-use std::io::{BufReader, Cursor};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rodio::source::ChannelVolume;
+use rodio::Source;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
-pub struct AudioSystem {
+pub mod adaptive;
+pub mod bank;
+
+pub const MUSIC_BUS: &str = "music";
+pub const SFX_BUS: &str = "sfx";
+pub const UI_BUS: &str = "ui";
+
+/// How fast the ducking is applied/removed (volume multiplier change per second).
+const DUCK_SPEED: f32 = 4.0;
+
+/// A sound to play.
+#[derive(Debug, Clone)]
+pub struct Sound {
+    /// Name of the sound in the sound banks.
+    pub name: String,
+    /// Position in the world. If none, the sound is played at full volume in both ears.
+    pub position: Option<glam::Vec2>,
+}
+
+impl Sound {
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            position: None,
+        }
+    }
+
+    pub fn at(mut self, position: glam::Vec2) -> Self {
+        self.position = Some(position);
+        self
+    }
+}
+
+struct Bus {
+    config: BusConfig,
+    /// Current ducking multiplier, between duck_volume and 1.
+    duck: f32,
+}
+
+impl Bus {
+    fn gain(&self) -> f32 {
+        if self.config.muted {
+            0.0
+        } else {
+            self.config.volume as f32 / 100.0 * self.duck
+        }
+    }
+}
+
+/// Where the sounds are heard from: the center of the screen. The offset to a sound is scaled by
+/// the zoom so that the distances of the configuration are in screen pixels.
+#[derive(Debug, Copy, Clone)]
+struct Listener {
+    position: glam::Vec2,
+    zoom: f32,
+}
+
+/// A sink that plays one sound at a time.
+struct Voice {
+    sink: rodio::Sink,
+    /// Name of the sound that is played.
+    sound: String,
+    bus: String,
+    priority: u8,
+    /// Volume from the distance to the listener.
+    gain: f32,
+    started: Instant,
+}
+
+/// A music that is playing. Each music has its own sink so that two musics can be played during
+/// a crossfade.
+struct MusicTrack {
+    name: String,
+    sink: rodio::Sink,
+    /// Between 0 and 1.
+    fade: f32,
+}
+
+/// Adaptive music that is playing. Each layer has its own sink and all layers are started at the
+/// same time so that they stay synchronized.
+struct AdaptiveTrack {
+    music: AdaptiveMusic,
+    layers: Vec<MusicTrack>,
+    /// Whether each layer should be heard.
+    active: Vec<bool>,
+    /// Layers to activate on the next bar.
+    pending: Option<Vec<bool>>,
+    /// When the layers started to play. None until all the layers are loaded.
+    started: Option<Instant>,
+    /// Index of the current bar.
+    bar: u64,
+}
+
+/// Output device. None for the null backend.
+struct Output {
     _stream: rodio::OutputStream,
     handle: rodio::OutputStreamHandle,
+}
 
-    /// Sink for the background music.
-    background: rodio::Sink,
-    current_background: Option<String>,
+pub struct AudioSystem {
+    output: Option<Output>,
+
+    /// Music that is currently playing. It will loop until another music is requested.
+    music: Option<MusicTrack>,
+    /// Adaptive music that is currently playing. Only one of `music` and `adaptive` is set.
+    adaptive: Option<AdaptiveTrack>,
+    /// Last intensity sent by the game, between 0 and 1.
+    music_intensity: f32,
+    /// Previous musics that are fading out.
+    fading_out: Vec<MusicTrack>,
+
+    /// Voices for sounds
+    voices: Vec<Voice>,
+    /// Last time each sound was played, for the cooldowns.
+    last_played: HashMap<String, Instant>,
 
-    /// Sinks for sound
-    sound_sinks: Vec<rodio::Sink>,
+    buses: HashMap<String, Bus>,
 
-    rdr_id: ReaderId<GameEvent>,
+    rdr_id: EventReader<AudioEvent>,
 
     config: AudioConfig,
 }
 
 impl AudioSystem {
-    pub fn new(resources: &Resources, config: AudioConfig) -> Result<Self, anyhow::Error> {
+    pub fn new(resources: &Resources, config: AudioConfig) -> Self {
+        let (output, voices) = match config.backend {
+            AudioBackend::Default => match Self::open_output(config.channel_nb) {
+                Ok((output, voices)) => (Some(output), voices),
+                Err(e) => {
+                    error!("Cannot open audio device, will use null backend = {:?}", e);
+                    (None, vec![])
+                }
+            },
+            AudioBackend::Null => (None, vec![]),
+        };
+        let buses = config
+            .buses
+            .iter()
+            .map(|(name, bus_config)| {
+                (
+                    name.clone(),
+                    Bus {
+                        config: bus_config.clone(),
+                        duck: 1.0,
+                    },
+                )
+            })
+            .collect();
+        Self {
+            config,
+            output,
+            voices,
+            last_played: HashMap::new(),
+            buses,
+            music: None,
+            adaptive: None,
+            music_intensity: 0.0,
+            fading_out: vec![],
+            rdr_id: register_reader(resources, "audio"),
+        }
+    }
+
+    fn open_output(channel_nb: usize) -> Result<(Output, Vec<Voice>), anyhow::Error> {
         let (stream, handle) = rodio::OutputStream::try_default()?;
-        let background = rodio::Sink::try_new(&handle)?;
-        background.set_volume(config.background_volume as f32 / 100.0);
-        let mut sound_sinks = vec![];
-        for _ in 0..config.channel_nb {
-            sound_sinks.push({
-                let sink = rodio::Sink::try_new(&handle)?;
-                sink.set_volume(config.effects_volume as f32 / 100.0);
-                sink
+        let mut voices = vec![];
+        for _ in 0..channel_nb {
+            voices.push(Voice {
+                sink: rodio::Sink::try_new(&handle)?,
+                sound: String::new(),
+                bus: SFX_BUS.to_string(),
+                priority: 0,
+                gain: 1.0,
+                started: Instant::now(),
             });
         }
-        let mut channel = resources.fetch_mut::<EventChannel<GameEvent>>().unwrap();
+        Ok((
+            Output {
+                _stream: stream,
+                handle,
+            },
+            voices,
+        ))
+    }
+
+    fn new_sink(&self) -> Option<rodio::Sink> {
+        let output = self.output.as_ref()?;
+        match rodio::Sink::try_new(&output.handle) {
+            Ok(sink) => Some(sink),
+            Err(e) => {
+                error!("Cannot create sink = {:?}", e);
+                None
+            }
+        }
+    }
 
-        Ok(Self {
-            config,
-            _stream: stream,
-            handle,
-            sound_sinks,
-            background,
-            current_background: None,
-            rdr_id: channel.register_reader(),
-        })
-    }
-
-    pub fn process(&mut self, resources: &Resources) {
-        let channel = resources.fetch::<EventChannel<GameEvent>>().unwrap();
-        let audio_manager = resources
-            .fetch::<AssetManager<GlfwSurface, Audio>>()
+    pub fn process(&mut self, world: &World, resources: &Resources, dt: Duration) {
+        let channel = resources.fetch::<Events<AudioEvent>>().unwrap();
+        let mut audio_manager = resources
+            .fetch_mut::<AssetManager<GlfwSurface, Audio>>()
             .unwrap();
+        let banks = resources.fetch::<SoundBanks>().unwrap();
+        let listener =
+            get_camera_center(world, &resources.fetch::<WindowDim>().unwrap()).map(|position| {
+                Listener {
+                    position,
+                    zoom: get_camera_zoom(world).unwrap_or(1.0),
+                }
+            });
         for ev in channel.read(&mut self.rdr_id) {
             match ev {
-                GameEvent::PlayBackgroundMusic(name) => {
-                    if let Some(asset) = audio_manager.get(&Handle(name.to_string())) {
-                        self.current_background = Some(name.to_string());
-                        if !self.background.empty() {
-                            self.background.stop();
-                            self.background = rodio::Sink::try_new(&self.handle)
-                                .expect("SHould be able to create new sink");
-                            self.background
-                                .set_volume(self.config.background_volume as f32 / 100.0);
-                        }
+                AudioEvent::PlayBackgroundMusic(name) => self.play_music(name),
+                AudioEvent::PlayAdaptiveMusic(music) => {
+                    self.play_adaptive_music(music, &mut audio_manager)
+                }
+                AudioEvent::MusicIntensity(intensity) => self.set_music_intensity(*intensity),
+                AudioEvent::PlaySound(sound) => {
+                    if let Some(definition) = banks.get(&sound.name) {
+                        self.play_sound(sound, definition, &audio_manager, listener);
+                    } else {
+                        error!("No sound with name: {}", sound.name);
+                    }
+                }
+                AudioEvent::SetBusVolume(bus, volume) => {
+                    if let Some(bus) = self.buses.get_mut(bus) {
+                        bus.config.volume = *volume;
+                    }
+                }
+                AudioEvent::MuteBus(bus, muted) => {
+                    if let Some(bus) = self.buses.get_mut(bus) {
+                        bus.config.muted = *muted;
+                    }
+                }
+            }
+        }
 
-                        asset.execute(|audio| {
-                            info!("Could load asset");
+        self.update_ducking(dt);
+        self.update_music(dt, &audio_manager);
+        self.update_adaptive_music(dt, &audio_manager);
 
-                            if let Audio::File(content) = audio {
-                                self.background.append(
-                                    rodio::Decoder::new(BufReader::new(Cursor::new(
-                                        content.clone(),
-                                    )))
-                                    .unwrap(),
-                                );
+        for voice in &self.voices {
+            if !voice.sink.empty() {
+                voice
+                    .sink
+                    .set_volume(voice.gain * bus_gain(&self.buses, &voice.bus));
+            }
+        }
+    }
 
-                                self.background.play();
-                            }
-                        });
-                    } else {
-                        error!("No asset with name: {}", name);
-                    }
+    fn play_music(&mut self, name: &str) {
+        if let Some(ref music) = self.music {
+            if music.name == name {
+                return;
+            }
+        }
+
+        let sink = match self.new_sink() {
+            Some(sink) => sink,
+            None => return,
+        };
+        sink.set_volume(0.0);
+
+        // The previous music will fade out while the new one fades in. The music is appended to
+        // the sink in `update_music` as soon as the asset is loaded.
+        self.fade_out_music();
+        self.music = Some(MusicTrack {
+            name: name.to_string(),
+            sink,
+            fade: 0.0,
+        });
+    }
+
+    /// The layers that are not loaded yet are requested here. The music starts once all of them
+    /// are loaded.
+    fn play_adaptive_music(
+        &mut self,
+        music: &AdaptiveMusic,
+        audio_manager: &mut AssetManager<GlfwSurface, Audio>,
+    ) {
+        if let Some(ref track) = self.adaptive {
+            let same_layers = track.music.layers.len() == music.layers.len()
+                && track
+                    .music
+                    .layers
+                    .iter()
+                    .zip(music.layers.iter())
+                    .all(|(a, b)| a.name == b.name);
+            if same_layers {
+                return;
+            }
+        }
+
+        let mut layers = vec![];
+        for layer in &music.layers {
+            audio_manager.load(layer.name.clone());
+            let sink = match self.new_sink() {
+                Some(sink) => sink,
+                None => return,
+            };
+            sink.set_volume(0.0);
+            layers.push(MusicTrack {
+                name: layer.name.clone(),
+                sink,
+                fade: 0.0,
+            });
+        }
+
+        self.fade_out_music();
+        self.adaptive = Some(AdaptiveTrack {
+            active: music.active_layers(self.music_intensity),
+            music: music.clone(),
+            layers,
+            pending: None,
+            started: None,
+            bar: 0,
+        });
+    }
+
+    /// Move the current music to the musics that are fading out.
+    fn fade_out_music(&mut self) {
+        if let Some(previous) = self.music.take() {
+            self.fading_out.push(previous);
+        }
+        if let Some(previous) = self.adaptive.take() {
+            self.fading_out.extend(previous.layers);
+        }
+    }
+
+    fn set_music_intensity(&mut self, intensity: f32) {
+        self.music_intensity = intensity;
+        if let Some(ref mut track) = self.adaptive {
+            let active = track.music.active_layers(intensity);
+            track.pending = if active != track.active {
+                Some(active)
+            } else {
+                None
+            };
+        }
+    }
+
+    fn update_adaptive_music(
+        &mut self,
+        dt: Duration,
+        audio_manager: &AssetManager<GlfwSurface, Audio>,
+    ) {
+        let gain = bus_gain(&self.buses, MUSIC_BUS);
+        let track = match self.adaptive.as_mut() {
+            Some(track) => track,
+            None => return,
+        };
+
+        match track.started {
+            None => {
+                if let Some(layer) = track
+                    .music
+                    .layers
+                    .iter()
+                    .find(|layer| audio_manager.is_error(&Handle(layer.name.clone())))
+                {
+                    error!("Cannot load music layer {}, stop the music", layer.name);
+                    self.adaptive = None;
+                    return;
                 }
-                GameEvent::PlaySound(name) => {
-                    if let Some(asset) = audio_manager.get(&Handle(name.to_string())) {
-                        asset.execute(|audio| {
-                            if let Audio::File(content) = audio {
-                                // get the first available channel.
-                                let sink = self
-                                    .sound_sinks
-                                    .iter_mut()
-                                    .filter(|sink| sink.empty())
-                                    .next();
-                                if let Some(s) = sink {
-                                    s.append(
-                                        rodio::Decoder::new(BufReader::new(Cursor::new(
-                                            content.clone(),
-                                        )))
-                                        .unwrap(),
-                                    );
+
+                // Wait for all the layers to be loaded, then start them at the same time.
+                let sources: Option<Vec<AudioSource>> = track
+                    .music
+                    .layers
+                    .iter()
+                    .map(|layer| {
+                        audio_manager.get(&Handle(layer.name.clone()))?.execute(
+                            |audio| match audio.looped_source() {
+                                Ok(source) => Some(source),
+                                Err(e) => {
+                                    error!("Cannot play {} = {:?}", layer.name, e);
+                                    None
                                 }
-                            }
-                        });
-                    } else {
-                        error!("No asset with name: {}", name);
+                            },
+                        )?
+                    })
+                    .collect();
+                match sources {
+                    Some(sources) => {
+                        for (layer, source) in track.layers.iter().zip(sources) {
+                            layer.sink.append(source);
+                        }
+                        track.started = Some(Instant::now());
                     }
+                    None => return,
                 }
-                _ => (),
             }
+            Some(started) => {
+                // Apply the changes of intensity on the next bar.
+                let bar = (started.elapsed().as_secs_f32() / track.music.bar_duration()) as u64;
+                if bar != track.bar {
+                    track.bar = bar;
+                    if let Some(active) = track.pending.take() {
+                        track.active = active;
+                    }
+                }
+            }
+        }
+
+        let fade_duration = track.music.fade_beats * track.music.beat_duration();
+        let fade_delta = if fade_duration > 0.0 {
+            dt.as_secs_f32() / fade_duration
+        } else {
+            1.0
+        };
+        for (layer, active) in track.layers.iter_mut().zip(track.active.iter()) {
+            if *active {
+                layer.fade = (layer.fade + fade_delta).min(1.0);
+            } else {
+                layer.fade = (layer.fade - fade_delta).max(0.0);
+            }
+            layer.sink.set_volume(layer.fade * gain);
         }
+    }
 
-        // LOOP !
-        if let Some(ref bg) = self.current_background {
-            if self.background.empty() {
-                if let Some(asset) = audio_manager.get(&Handle(bg.clone())) {
-                    asset.execute(|audio| {
-                        info!("Could load asset");
-                        if let Audio::File(content) = audio {
-                            self.background.append(
-                                rodio::Decoder::new(BufReader::new(Cursor::new(content.clone())))
-                                    .unwrap(),
-                            );
+    fn update_music(&mut self, dt: Duration, audio_manager: &AssetManager<GlfwSurface, Audio>) {
+        let fade_delta = if self.config.crossfade > 0.0 {
+            dt.as_secs_f32() / self.config.crossfade
+        } else {
+            1.0
+        };
+        let gain = bus_gain(&self.buses, MUSIC_BUS);
 
-                            self.background.play();
+        if let Some(ref mut music) = self.music {
+            // LOOP !
+            if music.sink.empty() {
+                if let Some(asset) = audio_manager.get(&Handle(music.name.clone())) {
+                    asset.execute(|audio| match audio.source() {
+                        Ok(source) => {
+                            music.sink.append(source);
+                            music.sink.play();
                         }
+                        Err(e) => error!("Cannot play {} = {:?}", music.name, e),
                     });
+                } else {
+                    error!("No asset with name: {}", music.name);
                 }
             }
+
+            music.fade = (music.fade + fade_delta).min(1.0);
+            music.sink.set_volume(music.fade * gain);
+        }
+
+        for music in &mut self.fading_out {
+            music.fade = (music.fade - fade_delta).max(0.0);
+            music.sink.set_volume(music.fade * gain);
+            if music.fade == 0.0 {
+                music.sink.stop();
+            }
+        }
+        self.fading_out.retain(|music| music.fade > 0.0);
+    }
+
+    fn update_ducking(&mut self, dt: Duration) {
+        let mut playing_buses = vec![];
+        for voice in &self.voices {
+            if !voice.sink.empty() {
+                playing_buses.push(voice.bus.clone());
+            }
+        }
+
+        let step = DUCK_SPEED * dt.as_secs_f32();
+        for bus in self.buses.values_mut() {
+            let is_ducked = bus
+                .config
+                .ducked_by
+                .iter()
+                .any(|other| playing_buses.contains(other));
+            let target = if is_ducked {
+                bus.config.duck_volume as f32 / 100.0
+            } else {
+                1.0
+            };
+            if bus.duck < target {
+                bus.duck = (bus.duck + step).min(target);
+            } else {
+                bus.duck = (bus.duck - step).max(target);
+            }
+        }
+    }
+
+    fn play_sound(
+        &mut self,
+        sound: &Sound,
+        definition: &SoundDefinition,
+        audio_manager: &AssetManager<GlfwSurface, Audio>,
+        listener: Option<Listener>,
+    ) {
+        if let Some(last_played) = self.last_played.get(&sound.name) {
+            if last_played.elapsed().as_secs_f32() < definition.cooldown {
+                return;
+            }
+        }
+
+        let (gain, left, right) = match (sound.position, listener) {
+            (Some(position), Some(listener)) => {
+                self.spatialize((position - listener.position) * listener.zoom)
+            }
+            _ => (1.0, 1.0, 1.0),
+        };
+        if gain <= 0.0 {
+            // too far away to be heard.
+            return;
+        }
+
+        let mut rng = rand::thread_rng();
+        let clip = match definition.clips.choose(&mut rng) {
+            Some(clip) => clip,
+            None => return,
+        };
+        let source = match audio_manager.get(&Handle(clip.clone())) {
+            Some(asset) => asset.execute(|audio| audio.source()),
+            None => {
+                error!("No asset with name: {}", clip);
+                return;
+            }
+        };
+        let source = match source {
+            Some(Ok(source)) => source,
+            Some(Err(e)) => {
+                error!("Cannot play {} = {:?}", clip, e);
+                return;
+            }
+            // not loaded yet.
+            None => return,
+        };
+
+        let idx = match self.find_voice(&sound.name, definition) {
+            Some(idx) => idx,
+            None => {
+                debug!("No voice available for {}", sound.name);
+                return;
+            }
+        };
+
+        if !self.voices[idx].sink.empty() {
+            trace!("Stealing voice for {}", sound.name);
+            self.voices[idx].sink.stop();
+            match self.new_sink() {
+                Some(sink) => self.voices[idx].sink = sink,
+                None => return,
+            }
+        }
+
+        let pitch = random_in_range(&mut rng, definition.pitch);
+        let volume = random_in_range(&mut rng, definition.volume);
+
+        let now = Instant::now();
+        self.last_played.insert(sound.name.clone(), now);
+        let voice = &mut self.voices[idx];
+
+        voice.sound = sound.name.clone();
+        voice.bus = definition.bus.clone();
+        voice.priority = definition.priority;
+        voice.gain = gain;
+        voice.started = now;
+        voice
+            .sink
+            .set_volume(gain * bus_gain(&self.buses, &definition.bus));
+
+        voice.sink.append(ChannelVolume::new(
+            source.speed(pitch.max(0.1)).amplify(volume.max(0.0)),
+            vec![left, right],
+        ));
+    }
+
+    /// Find a voice for the sound. If the sound is already playing too many times, its oldest
+    /// instance is replaced. Otherwise, a free voice is used. If all are busy, the voice with the
+    /// lowest priority will be reused (the oldest one if several have the same priority) as long
+    /// as its priority is not higher than the new sound.
+    fn find_voice(&self, name: &str, definition: &SoundDefinition) -> Option<usize> {
+        if definition.max_instances > 0 {
+            let instances = self
+                .voices
+                .iter()
+                .enumerate()
+                .filter(|(_, v)| !v.sink.empty() && v.sound == name);
+            if instances.clone().count() >= definition.max_instances {
+                return instances.min_by_key(|(_, v)| v.started).map(|(idx, _)| idx);
+            }
+        }
+
+        if let Some(idx) = self.voices.iter().position(|v| v.sink.empty()) {
+            return Some(idx);
         }
+
+        self.voices
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| v.priority <= definition.priority)
+            .min_by_key(|(_, v)| (v.priority, v.started))
+            .map(|(idx, _)| idx)
+    }
+
+    /// Attenuation and volume of the left and right channels for a sound at the given offset
+    /// from the listener.
+    fn spatialize(&self, offset: glam::Vec2) -> (f32, f32, f32) {
+        let distance = offset.length();
+        let range = (self.config.max_distance - self.config.min_distance).max(1.0);
+        let gain = 1.0 - ((distance - self.config.min_distance) / range).clamp(0.0, 1.0);
+
+        let pan = (offset.x() / self.config.pan_distance.max(1.0)).clamp(-1.0, 1.0);
+        let left = (1.0 - pan).min(1.0);
+        let right = (1.0 + pan).min(1.0);
+        (gain * gain, left, right)
     }
 }
 
+fn random_in_range<R: Rng>(rng: &mut R, (min, max): (f32, f32)) -> f32 {
+    if max > min {
+        rng.gen_range(min, max)
+    } else {
+        min
+    }
+}
+
+fn bus_gain(buses: &HashMap<String, Bus>, name: &str) -> f32 {
+    buses.get(name).map(|bus| bus.gain()).unwrap_or(1.0)
+}
+
 pub fn play_background_music(resources: &Resources, name: &str) {
-    let mut channel = resources.fetch_mut::<EventChannel<GameEvent>>().unwrap();
-    channel.single_write(GameEvent::PlayBackgroundMusic(name.to_string()));
+    send_event(resources, AudioEvent::PlayBackgroundMusic(name.to_string()));
+}
+
+/// Play a music made of several layers. The layers are selected with `set_music_intensity`.
+pub fn play_adaptive_music(resources: &Resources, music: AdaptiveMusic) {
+    send_event(resources, AudioEvent::PlayAdaptiveMusic(music));
+}
+
+/// Intensity between 0 (calm) and 1 (boss fight) for the adaptive music.
+pub fn set_music_intensity(resources: &Resources, intensity: f32) {
+    send_event(resources, AudioEvent::MusicIntensity(intensity));
+}
+
+pub fn play_sound(resources: &Resources, sound: Sound) {
+    send_event(resources, AudioEvent::PlaySound(sound));
+}
+
+/// Change the volume (between 0 and 100) of a bus.
+pub fn set_bus_volume(resources: &Resources, bus: &str, volume: u32) {
+    send_event(resources, AudioEvent::SetBusVolume(bus.to_string(), volume));
 }
 
-pub fn play_sound(resources: &Resources, name: &str) {
-    let mut channel = resources.fetch_mut::<EventChannel<GameEvent>>().unwrap();
-    channel.single_write(GameEvent::PlaySound(name.to_string()));
+pub fn mute_bus(resources: &Resources, bus: &str, muted: bool) {
+    send_event(resources, AudioEvent::MuteBus(bus.to_string(), muted));
 }
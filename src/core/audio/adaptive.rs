@@ -0,0 +1,59 @@
+//! Music made of several layers (stems) played at the same time. Layers are faded in or out
+//! depending on the intensity sent by the game. Changes are applied on the next bar so that they
+//! stay in rhythm with the music.
+use serde_derive::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdaptiveMusic {
+    /// Beats per minute. All the layers should have the same tempo and length.
+    pub bpm: f32,
+    #[serde(default = "default_beats_per_bar")]
+    pub beats_per_bar: u32,
+    /// Number of beats it takes to fade a layer in or out.
+    #[serde(default = "default_fade_beats")]
+    pub fade_beats: f32,
+    pub layers: Vec<MusicLayer>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MusicLayer {
+    /// Name of the audio asset.
+    pub name: String,
+    /// The layer is played when the intensity is at least this value (between 0 and 1).
+    #[serde(default)]
+    pub min_intensity: f32,
+}
+
+fn default_beats_per_bar() -> u32 {
+    4
+}
+
+fn default_fade_beats() -> f32 {
+    2.0
+}
+
+impl AdaptiveMusic {
+    pub fn load_from_path<P: AsRef<Path>>(p: P) -> Result<Self, anyhow::Error> {
+        let content = std::fs::read_to_string(p)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Duration of a beat in seconds.
+    pub fn beat_duration(&self) -> f32 {
+        60.0 / self.bpm.max(1.0)
+    }
+
+    /// Duration of a bar in seconds.
+    pub fn bar_duration(&self) -> f32 {
+        self.beat_duration() * self.beats_per_bar.max(1) as f32
+    }
+
+    /// Whether each layer should be played for the given intensity.
+    pub fn active_layers(&self, intensity: f32) -> Vec<bool> {
+        self.layers
+            .iter()
+            .map(|layer| intensity >= layer.min_intensity)
+            .collect()
+    }
+}
@@ -0,0 +1,94 @@
+//! Sound banks map the name of a sound event (e.g. "player_shoot") to the clips to play and how
+//! to play them. Banks are JSON files in `sounds/banks/` and are loaded with their clips before the
+//! scene that needs them (see `SceneAssets`).
+use crate::core::audio::SFX_BUS;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SoundDefinition {
+    /// Audio assets. One is picked randomly each time the sound is played.
+    pub clips: Vec<String>,
+    /// Min and max volume multiplier. A random value in this range is used for each play.
+    #[serde(default = "default_range")]
+    pub volume: (f32, f32),
+    /// Min and max pitch multiplier. A random value in this range is used for each play.
+    #[serde(default = "default_range")]
+    pub pitch: (f32, f32),
+    /// Minimum time in seconds between two plays of this sound.
+    #[serde(default)]
+    pub cooldown: f32,
+    /// Maximum number of instances of this sound playing at the same time. When reached, the
+    /// oldest instance is stopped. 0 means no limit.
+    #[serde(default)]
+    pub max_instances: usize,
+    /// When all voices are busy, a sound can only replace a sound with lower or same priority.
+    #[serde(default)]
+    pub priority: u8,
+    #[serde(default = "default_bus")]
+    pub bus: String,
+}
+
+fn default_range() -> (f32, f32) {
+    (1.0, 1.0)
+}
+
+fn default_bus() -> String {
+    SFX_BUS.to_string()
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SoundBank {
+    pub sounds: HashMap<String, SoundDefinition>,
+}
+
+impl SoundBank {
+    pub fn load_from_path<P: AsRef<Path>>(p: P) -> Result<Self, anyhow::Error> {
+        let content = std::fs::read_to_string(p)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Path of the bank with the given name.
+    pub fn path(name: &str) -> PathBuf {
+        let base_path = std::env::var("ASSET_PATH").unwrap_or("assets/".to_string());
+        PathBuf::from(base_path)
+            .join("sounds")
+            .join("banks")
+            .join(name)
+            .with_extension("json")
+    }
+
+    /// All the audio assets used by the bank.
+    pub fn clips(&self) -> Vec<String> {
+        let mut clips: Vec<String> = self
+            .sounds
+            .values()
+            .flat_map(|sound| sound.clips.iter().cloned())
+            .collect();
+        clips.sort();
+        clips.dedup();
+        clips
+    }
+}
+
+/// Banks that are loaded, by name.
+#[derive(Debug, Default)]
+pub struct SoundBanks {
+    banks: HashMap<String, SoundBank>,
+}
+
+impl SoundBanks {
+    pub fn insert(&mut self, name: String, bank: SoundBank) {
+        self.banks.insert(name, bank);
+    }
+
+    pub fn is_loaded(&self, name: &str) -> bool {
+        self.banks.contains_key(name)
+    }
+
+    /// Find the definition of a sound in the loaded banks.
+    pub fn get(&self, sound: &str) -> Option<&SoundDefinition> {
+        self.banks.values().find_map(|bank| bank.sounds.get(sound))
+    }
+}
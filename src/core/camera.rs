@@ -1,3 +1,4 @@
+use crate::core::window::WindowDim;
 use glam::Vec2;
 use hecs::World;
 
@@ -7,6 +8,13 @@ use hecs::World;
 pub struct Camera {
     pub main: bool,
     pub position: glam::Vec2,
+
+    /// Extra displacement added to the position when rendering. Used for screen shake so that
+    /// the followed position is not modified.
+    pub offset: glam::Vec2,
+
+    /// Zoom factor. 1.0 is the default, more than 1.0 will zoom in.
+    pub zoom: f32,
 }
 
 impl Camera {
@@ -14,15 +22,18 @@ impl Camera {
         Self {
             main: true,
             position: Vec2::zero(),
+            offset: Vec2::zero(),
+            zoom: 1.0,
         }
     }
 
     pub fn to_view(&self) -> glam::Mat4 {
-        glam::Mat4::look_at_rh(self.eye(), self.position.extend(0.0), glam::Vec3::unit_y())
+        let target = self.position + self.offset;
+        glam::Mat4::look_at_rh(target.extend(1.0), target.extend(0.0), glam::Vec3::unit_y())
     }
 
     pub fn eye(&self) -> glam::Vec3 {
-        self.position.extend(1.0)
+        (self.position + self.offset).extend(1.0)
     }
 }
 
@@ -34,6 +45,27 @@ pub fn get_view_matrix(world: &World) -> Option<glam::Mat4> {
         .next()
 }
 
+/// Zoom of the main camera.
+pub fn get_camera_zoom(world: &World) -> Option<f32> {
+    world.query::<&Camera>().iter().map(|(_, c)| c.zoom).next()
+}
+
+/// Position of the main camera, without screen shake.
+pub fn get_camera_position(world: &World) -> Option<glam::Vec2> {
+    world
+        .query::<&Camera>()
+        .iter()
+        .find(|(_, c)| c.main)
+        .map(|(_, c)| c.position)
+}
+
+/// Point of the world at the center of the screen for the main camera, without screen shake.
+/// `position` is the bottom-left corner of the screen. The zoom is applied around the center of
+/// the screen so it does not move this point.
+pub fn get_camera_center(world: &World, window_dim: &WindowDim) -> Option<glam::Vec2> {
+    get_camera_position(world).map(|position| position + window_dim.to_vec2() / 2.0)
+}
+
 pub fn screen_to_world(
     screen_coords: glam::Vec2,
     projection_matrix: glam::Mat4,
@@ -46,15 +78,57 @@ pub fn screen_to_world(
     glam::vec2(mouse_pos_world.x(), mouse_pos_world.y())
 }
 
+/// Orthographic projection. The zoom is applied around the center of the screen so that a zoom
+/// of 1.0 gives the usual (0, 0) -> (width, height) projection.
 #[derive(Copy, Clone, Debug)]
-pub struct ProjectionMatrix(pub(crate) glam::Mat4);
+pub struct ProjectionMatrix {
+    pub(crate) matrix: glam::Mat4,
+    width: f32,
+    height: f32,
+    zoom: f32,
+}
 
 impl ProjectionMatrix {
     pub fn new(w: f32, h: f32) -> Self {
-        Self(glam::Mat4::orthographic_rh_gl(0.0, w, 0.0, h, -1.0, 10.0))
+        Self {
+            matrix: Self::orthographic(w, h, 1.0),
+            width: w,
+            height: h,
+            zoom: 1.0,
+        }
     }
 
     pub fn resize(&mut self, w: f32, h: f32) {
-        self.0 = glam::Mat4::orthographic_rh_gl(0.0, w, 0.0, h, -1.0, 10.0);
+        self.width = w;
+        self.height = h;
+        self.matrix = Self::orthographic(w, h, self.zoom);
+    }
+
+    pub fn set_zoom(&mut self, zoom: f32) {
+        if (self.zoom - zoom).abs() > f32::EPSILON {
+            self.zoom = zoom;
+            self.matrix = Self::orthographic(self.width, self.height, zoom);
+        }
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    pub fn matrix(&self) -> glam::Mat4 {
+        self.matrix
+    }
+
+    fn orthographic(w: f32, h: f32, zoom: f32) -> glam::Mat4 {
+        let half_w = w / (2.0 * zoom);
+        let half_h = h / (2.0 * zoom);
+        glam::Mat4::orthographic_rh_gl(
+            w / 2.0 - half_w,
+            w / 2.0 + half_w,
+            h / 2.0 - half_h,
+            h / 2.0 + half_h,
+            -1.0,
+            10.0,
+        )
     }
 }
@@ -0,0 +1,273 @@
+//! Commands and console variables (cvars) used by the developer console. The `Console` is stored
+//! in the resources and the console scene gives it the lines typed by the user.
+//!
+//! A line is split on whitespace. The first word is either:
+//! - `help`, to list the commands and variables,
+//! - the name of a command, that receives the other words as arguments,
+//! - the name of a variable. Without argument, its value is displayed. Otherwise the argument is
+//!   parsed and the variable is set, then all the variables are saved to disk.
+//!
+//! ```ignore
+//! let console = Console::new()
+//!     .with_command("god", "god", "Toggle invulnerability", |_args, world, resources| {
+//!         // ...
+//!         Ok("god mode on".to_string())
+//!     })
+//!     .with_cvar(
+//!         "physics.damping",
+//!         "Velocity multiplier when no force is applied",
+//!         |resources| resources.fetch::<PhysicConfig>().map(|c| c.damping),
+//!         |resources, v| match resources.fetch_mut::<PhysicConfig>() {
+//!             Some(mut config) => {
+//!                 config.damping = v;
+//!                 true
+//!             }
+//!             None => false,
+//!         },
+//!     )
+//!     .with_save_path("assets/config/cvars.json");
+//! ```
+use crate::config::{load_config, save_config};
+use crate::resources::Resources;
+use hecs::World;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ConsoleError {
+    #[error("Unknown command {0}. Type help to see the commands")]
+    UnknownCommand(String),
+
+    #[error("Usage: {0}")]
+    Usage(&'static str),
+
+    #[error("Invalid value {value} for {name}")]
+    InvalidValue { name: String, value: String },
+
+    #[error("{0} is not available here")]
+    Unavailable(String),
+
+    #[error("Cannot save the console variables: {0}")]
+    Save(String),
+
+    #[error("{0}")]
+    Failed(String),
+}
+
+/// Text to display when the command succeeds.
+pub type CommandResult = Result<String, ConsoleError>;
+
+type CommandFn = Box<dyn Fn(&[&str], &mut World, &Resources) -> CommandResult>;
+
+struct Command {
+    usage: &'static str,
+    help: &'static str,
+    run: CommandFn,
+}
+
+/// Value of a console variable. It is parsed from the console and saved as JSON.
+pub trait CVarValue: FromStr + Display + Serialize + DeserializeOwned + 'static {}
+
+impl<T> CVarValue for T where T: FromStr + Display + Serialize + DeserializeOwned + 'static {}
+
+/// Current value of a variable, as text and as JSON. None if the resource that holds the value is
+/// missing.
+type CVarGetter = Box<dyn Fn(&Resources) -> Option<(String, serde_json::Value)>>;
+type CVarParser = Box<dyn Fn(&str) -> Option<serde_json::Value>>;
+/// Returns false if the JSON value does not have the type of the variable, or if the value cannot
+/// be set (e.g. missing resource).
+type CVarSetter = Box<dyn Fn(&Resources, serde_json::Value) -> bool>;
+
+struct CVar {
+    help: &'static str,
+    get: CVarGetter,
+    parse: CVarParser,
+    set: CVarSetter,
+}
+
+#[derive(Default)]
+pub struct Console {
+    commands: BTreeMap<String, Command>,
+    cvars: BTreeMap<String, CVar>,
+    /// Where the variables are saved. They are not saved if None.
+    save_path: Option<PathBuf>,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `usage` is displayed when the command returns `ConsoleError::Usage`.
+    pub fn with_command<F>(
+        mut self,
+        name: &str,
+        usage: &'static str,
+        help: &'static str,
+        run: F,
+    ) -> Self
+    where
+        F: Fn(&[&str], &mut World, &Resources) -> CommandResult + 'static,
+    {
+        self.commands.insert(
+            name.to_string(),
+            Command {
+                usage,
+                help,
+                run: Box::new(run),
+            },
+        );
+        self
+    }
+
+    /// Typed variable. `get` returns None and `set` returns false when the value is not available
+    /// (e.g. missing resource). Nothing is saved when a value cannot be set.
+    pub fn with_cvar<T, G, S>(mut self, name: &str, help: &'static str, get: G, set: S) -> Self
+    where
+        T: CVarValue,
+        G: Fn(&Resources) -> Option<T> + 'static,
+        S: Fn(&Resources, T) -> bool + 'static,
+    {
+        self.cvars.insert(
+            name.to_string(),
+            CVar {
+                help,
+                get: Box::new(move |resources| {
+                    get(resources).and_then(|value| {
+                        let text = value.to_string();
+                        serde_json::to_value(value).ok().map(|json| (text, json))
+                    })
+                }),
+                parse: Box::new(|text| {
+                    text.parse::<T>()
+                        .ok()
+                        .and_then(|value| serde_json::to_value(value).ok())
+                }),
+                set: Box::new(
+                    move |resources, json| match serde_json::from_value::<T>(json) {
+                        Ok(value) => set(resources, value),
+                        Err(_) => false,
+                    },
+                ),
+            },
+        );
+        self
+    }
+
+    /// The variables are saved to this file when one of them is changed.
+    pub fn with_save_path<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.save_path = Some(path.into());
+        self
+    }
+
+    /// Name of the commands and variables that start with the prefix.
+    pub fn completions(&self, prefix: &str) -> Vec<&str> {
+        std::iter::once("help")
+            .chain(self.commands.keys().map(String::as_str))
+            .chain(self.cvars.keys().map(String::as_str))
+            .filter(|name| name.starts_with(prefix))
+            .collect()
+    }
+
+    /// Run a line typed in the console.
+    pub fn execute(&self, line: &str, world: &mut World, resources: &Resources) -> CommandResult {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (name, args) = match words.split_first() {
+            Some((name, args)) => (*name, args),
+            None => return Ok(String::new()),
+        };
+
+        if name == "help" {
+            return Ok(self.help(resources));
+        }
+
+        if let Some(command) = self.commands.get(name) {
+            return match (command.run)(args, world, resources) {
+                Err(ConsoleError::Usage(_)) => Err(ConsoleError::Usage(command.usage)),
+                res => res,
+            };
+        }
+
+        if let Some(cvar) = self.cvars.get(name) {
+            if let Some(value) = args.first() {
+                let json = (cvar.parse)(value).ok_or_else(|| ConsoleError::InvalidValue {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                })?;
+                if !(cvar.set)(resources, json) {
+                    return Err(ConsoleError::Unavailable(name.to_string()));
+                }
+                self.save_cvars(resources)?;
+            }
+            return match (cvar.get)(resources) {
+                Some((text, _)) => Ok(format!("{} = {}", name, text)),
+                None => Err(ConsoleError::Unavailable(name.to_string())),
+            };
+        }
+
+        Err(ConsoleError::UnknownCommand(name.to_string()))
+    }
+
+    fn help(&self, resources: &Resources) -> String {
+        let mut lines = vec!["Commands:".to_string()];
+        for command in self.commands.values() {
+            lines.push(format!("  {} - {}", command.usage, command.help));
+        }
+        lines
+            .push("Variables (type the name to see the value, add a value to set it):".to_string());
+        for (name, cvar) in &self.cvars {
+            let value = (cvar.get)(resources)
+                .map(|(text, _)| text)
+                .unwrap_or_else(|| "-".to_string());
+            lines.push(format!("  {} = {} - {}", name, value, cvar.help));
+        }
+        lines.join("\n")
+    }
+
+    /// Save the current value of all the variables.
+    fn save_cvars(&self, resources: &Resources) -> Result<(), ConsoleError> {
+        let path = match &self.save_path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let values: BTreeMap<&str, serde_json::Value> = self
+            .cvars
+            .iter()
+            .filter_map(|(name, cvar)| (cvar.get)(resources).map(|(_, json)| (name.as_str(), json)))
+            .collect();
+        save_config(path, &values).map_err(|e| ConsoleError::Save(e.to_string()))
+    }
+
+    /// Apply the values saved by a previous session. Unknown or invalid values are ignored.
+    pub fn load_cvars(&self, resources: &Resources) {
+        let path = match &self.save_path {
+            Some(path) => path,
+            None => return,
+        };
+
+        let values: BTreeMap<String, serde_json::Value> = match load_config(path) {
+            Ok(values) => values,
+            Err(e) => {
+                info!("No console variables loaded from {}: {}", path.display(), e);
+                return;
+            }
+        };
+
+        for (name, json) in values {
+            match self.cvars.get(&name) {
+                Some(cvar) => {
+                    if !(cvar.set)(resources, json) {
+                        warn!("Cannot apply console variable {}", name);
+                    }
+                }
+                None => warn!("Unknown console variable {}", name),
+            }
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! One-shot particle effects (explosions, pickups...) spawned by name with `spawn_effect`. The
+//! effects are the particle emitters in assets/particle/. Burst effects get a `TimeToLive` and are
+//! despawned once their particles are dead.
+use crate::assets::particle::ParticleEffectManager;
+use crate::core::events::{register_reader, send_event, EventReader, Events};
+use crate::core::lifetime::TimeToLive;
+use crate::core::transform::{HasChildren, HasParent, LocalTransform, Transform};
+use crate::render::particle::ParticleEmitter;
+use crate::resources::Resources;
+use hecs::{Entity, World};
+use luminance_glfw::GlfwSurface;
+
+#[derive(Debug, Clone)]
+pub struct ParticleEffect {
+    /// Name of the particle asset.
+    pub name: String,
+    /// Position in the world, or offset from the parent if the effect is attached to an entity.
+    pub position: glam::Vec2,
+    /// If set, the effect follows this entity.
+    pub parent: Option<Entity>,
+}
+
+impl ParticleEffect {
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            position: glam::Vec2::zero(),
+            parent: None,
+        }
+    }
+
+    pub fn at(mut self, position: glam::Vec2) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub fn attached_to(mut self, parent: Entity) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+}
+
+pub fn spawn_effect(resources: &Resources, effect: ParticleEffect) {
+    send_event(resources, effect);
+}
+
+pub struct EffectSystem {
+    rdr_id: EventReader<ParticleEffect>,
+    /// Effects that are waiting for their asset to be loaded.
+    pending: Vec<ParticleEffect>,
+}
+
+impl EffectSystem {
+    pub fn new(resources: &mut Resources) -> Self {
+        let rdr_id = register_reader(resources, "effects");
+        Self {
+            rdr_id,
+            pending: vec![],
+        }
+    }
+
+    pub fn process(&mut self, world: &mut World, resources: &Resources) {
+        let mut effects = std::mem::take(&mut self.pending);
+        {
+            let chan = resources.fetch::<Events<ParticleEffect>>().unwrap();
+            effects.extend(chan.read(&mut self.rdr_id).cloned());
+        }
+
+        if effects.is_empty() {
+            return;
+        }
+
+        let mut particle_manager = resources
+            .fetch_mut::<ParticleEffectManager<GlfwSurface>>()
+            .unwrap();
+        for effect in effects {
+            let handle = particle_manager.load(effect.name.clone());
+            if particle_manager.is_error(&handle) {
+                error!("Cannot spawn particle effect {}", effect.name);
+                continue;
+            }
+
+            let emitter = particle_manager
+                .get(&handle)
+                .and_then(|asset| asset.execute(|emitter| emitter.clone()));
+            if let Some(emitter) = emitter {
+                spawn_emitter(world, &effect, emitter);
+            } else {
+                self.pending.push(effect);
+            }
+        }
+    }
+}
+
+fn spawn_emitter(world: &mut World, effect: &ParticleEffect, emitter: ParticleEmitter) {
+    let lifetime = emitter.lifetime();
+    let parent = effect.parent.filter(|parent| world.contains(*parent));
+    let entity = if let Some(parent) = parent {
+        let parent_position = world
+            .get::<Transform>(parent)
+            .map(|t| t.translation)
+            .unwrap_or_default();
+        let entity = world.spawn((
+            Transform {
+                translation: parent_position + effect.position,
+                rotation: 0.0,
+                scale: glam::Vec2::one(),
+                dirty: true,
+            },
+            LocalTransform::new(effect.position, 0.0, glam::Vec2::one()),
+            HasParent { entity: parent },
+            emitter,
+        ));
+
+        let has_children = if let Ok(mut children) = world.get_mut::<HasChildren>(parent) {
+            children.children.push(entity);
+            true
+        } else {
+            false
+        };
+        if !has_children {
+            world
+                .insert_one(
+                    parent,
+                    HasChildren {
+                        children: vec![entity],
+                    },
+                )
+                .expect("Parent should exist");
+        }
+        entity
+    } else {
+        world.spawn((
+            Transform {
+                translation: effect.position,
+                rotation: 0.0,
+                scale: glam::Vec2::one(),
+                dirty: false,
+            },
+            emitter,
+        ))
+    };
+
+    if let Some(lifetime) = lifetime {
+        world
+            .insert_one(entity, TimeToLive::of_seconds(lifetime))
+            .expect("Effect was just spawned");
+    }
+}
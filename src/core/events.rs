@@ -0,0 +1,256 @@
+//! Events sent between systems. Each event type has its own channel, `Events<T>`, stored in the
+//! resources (see `register_event`), and each subscriber has its own `EventReader`. Events of a
+//! type are read in the order they were sent.
+//!
+//! An event is either sent right away with `Events::send` (systems that run later in the frame
+//! will read it) or deferred with `Events::defer`. Deferred events are delivered when
+//! `flush_events` is called, once per frame after the scene update and before the garbage
+//! collector. The gameplay systems defer `Delete` and `GameEvent` so that the entities stay
+//! alive, and the scene reacts to the game events, only once all the systems have run.
+//!
+//! When flushing, readers that have events waiting but did not read anything for `STALE_FRAMES`
+//! frames are reported in the logs. They are also listed by `lagging_readers` (displayed in the
+//! profiler overlay).
+//!
+//! ```ignore
+//! register_event::<Hit>(resources);
+//! let mut reader = register_reader::<Hit>(resources, "health");
+//!
+//! send_event(resources, Hit(entity, details));
+//!
+//! let events = resources.fetch::<Events<Hit>>().unwrap();
+//! for Hit(entity, details) in events.read(&mut reader) {
+//!     // ...
+//! }
+//! ```
+use crate::resources::Resources;
+use log::warn;
+pub use shrev::Event;
+use shrev::{EventChannel, EventIterator, ReaderId};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+
+/// Number of frames without reading (while events are waiting) before a reader is reported.
+const STALE_FRAMES: u32 = 120;
+
+/// Subscriber to the events of type T. Each reader receives all the events sent after it was
+/// registered.
+pub struct EventReader<T: 'static> {
+    rdr_id: ReaderId<T>,
+    /// Number of events sent to the channel when this reader last read. Shared with the channel
+    /// for the diagnostics.
+    read: Arc<AtomicU64>,
+}
+
+struct ReaderState {
+    name: &'static str,
+    /// Gone when the reader is dropped.
+    read: Weak<AtomicU64>,
+    /// Value of `read` at the previous flush.
+    last_read: u64,
+    /// Number of flushes without reading while events were waiting.
+    stale_frames: u32,
+}
+
+/// Reader that has not read its events for a while.
+#[derive(Debug, Clone)]
+pub struct LaggingReader {
+    pub name: &'static str,
+    /// Type of the events.
+    pub event: &'static str,
+    /// Number of events waiting.
+    pub pending: u64,
+    /// Number of frames since the last read.
+    pub frames: u32,
+}
+
+/// Channel for one type of events.
+pub struct Events<T: Event> {
+    channel: EventChannel<T>,
+    deferred: Vec<T>,
+    /// Number of events sent since the creation of the channel.
+    written: u64,
+    readers: Vec<ReaderState>,
+}
+
+impl<T: Event> Default for Events<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Event> Events<T> {
+    pub fn new() -> Self {
+        Self {
+            channel: EventChannel::new(),
+            deferred: vec![],
+            written: 0,
+            readers: vec![],
+        }
+    }
+
+    /// Create a new subscriber. The name is used in the diagnostics.
+    pub fn register_reader(&mut self, name: &'static str) -> EventReader<T> {
+        let read = Arc::new(AtomicU64::new(self.written));
+        self.readers.push(ReaderState {
+            name,
+            read: Arc::downgrade(&read),
+            last_read: self.written,
+            stale_frames: 0,
+        });
+        EventReader {
+            rdr_id: self.channel.register_reader(),
+            read,
+        }
+    }
+
+    /// Send the event now.
+    pub fn send(&mut self, event: T) {
+        self.written += 1;
+        self.channel.single_write(event);
+    }
+
+    pub fn send_all<I: IntoIterator<Item = T>>(&mut self, events: I) {
+        for event in events {
+            self.send(event);
+        }
+    }
+
+    /// Send the event at the next flush.
+    pub fn defer(&mut self, event: T) {
+        self.deferred.push(event);
+    }
+
+    pub fn defer_all<I: IntoIterator<Item = T>>(&mut self, events: I) {
+        self.deferred.extend(events);
+    }
+
+    /// Events sent since the last read, in the order they were sent.
+    pub fn read(&self, reader: &mut EventReader<T>) -> EventIterator<'_, T> {
+        reader.read.store(self.written, Ordering::Relaxed);
+        self.channel.read(&mut reader.rdr_id)
+    }
+
+    /// Readers that did not read their events for at least `STALE_FRAMES` frames.
+    pub fn lagging_readers(&self) -> Vec<LaggingReader> {
+        self.readers
+            .iter()
+            .filter(|reader| reader.stale_frames >= STALE_FRAMES)
+            .map(|reader| LaggingReader {
+                name: reader.name,
+                event: std::any::type_name::<T>(),
+                pending: self.written - reader.last_read,
+                frames: reader.stale_frames,
+            })
+            .collect()
+    }
+
+    /// Send the deferred events and update the diagnostics of the readers.
+    pub fn flush(&mut self) {
+        self.written += self.deferred.len() as u64;
+        self.channel.drain_vec_write(&mut self.deferred);
+
+        self.readers.retain(|reader| reader.read.strong_count() > 0);
+        let written = self.written;
+        for reader in &mut self.readers {
+            let read = match reader.read.upgrade() {
+                Some(read) => read.load(Ordering::Relaxed),
+                None => continue,
+            };
+
+            if read == reader.last_read && read < written {
+                reader.stale_frames += 1;
+                if reader.stale_frames == STALE_FRAMES {
+                    warn!(
+                        "Reader {} of {} is falling behind ({} events waiting)",
+                        reader.name,
+                        std::any::type_name::<T>(),
+                        written - read
+                    );
+                }
+            } else {
+                reader.stale_frames = 0;
+            }
+            reader.last_read = read;
+        }
+    }
+}
+
+/// Event types stored in the resources. Used to flush all the channels at once.
+#[derive(Default)]
+pub struct EventRegistry {
+    flushes: Vec<fn(&Resources)>,
+    lagging_readers: Vec<fn(&Resources) -> Vec<LaggingReader>>,
+}
+
+fn flush<T: Event>(resources: &Resources) {
+    if let Some(mut events) = resources.fetch_mut::<Events<T>>() {
+        events.flush();
+    }
+}
+
+fn lagging_readers_of<T: Event>(resources: &Resources) -> Vec<LaggingReader> {
+    resources
+        .fetch::<Events<T>>()
+        .map(|events| events.lagging_readers())
+        .unwrap_or_default()
+}
+
+/// Add the channel for the events of type T to the resources. Does nothing if it already exists.
+pub fn register_event<T: Event>(resources: &mut Resources) {
+    if resources.fetch::<Events<T>>().is_some() {
+        return;
+    }
+    if resources.fetch::<EventRegistry>().is_none() {
+        resources.insert(EventRegistry::default());
+    }
+
+    resources.insert(Events::<T>::new());
+    let mut registry = resources.fetch_mut::<EventRegistry>().unwrap();
+    registry.flushes.push(flush::<T>);
+    registry.lagging_readers.push(lagging_readers_of::<T>);
+}
+
+/// Send the deferred events of all the registered channels.
+pub fn flush_events(resources: &Resources) {
+    if let Some(registry) = resources.fetch::<EventRegistry>() {
+        for flush in &registry.flushes {
+            flush(resources);
+        }
+    }
+}
+
+/// Lagging readers of all the registered channels.
+pub fn lagging_readers(resources: &Resources) -> Vec<LaggingReader> {
+    match resources.fetch::<EventRegistry>() {
+        Some(registry) => registry
+            .lagging_readers
+            .iter()
+            .flat_map(|lagging_readers| lagging_readers(resources))
+            .collect(),
+        None => vec![],
+    }
+}
+
+pub fn register_reader<T: Event>(resources: &Resources, name: &'static str) -> EventReader<T> {
+    resources
+        .fetch_mut::<Events<T>>()
+        .expect("Event should be registered")
+        .register_reader(name)
+}
+
+/// Send an event now.
+pub fn send_event<T: Event>(resources: &Resources, event: T) {
+    resources
+        .fetch_mut::<Events<T>>()
+        .expect("Event should be registered")
+        .send(event);
+}
+
+/// Send an event at the next flush.
+pub fn defer_event<T: Event>(resources: &Resources, event: T) {
+    resources
+        .fetch_mut::<Events<T>>()
+        .expect("Event should be registered")
+        .defer(event);
+}
@@ -1,13 +1,130 @@
-use crate::{HEIGHT, WIDTH};
-use glfw::{Key, MouseButton, WindowEvent};
+use crate::core::input::gamepad::{find_gamepad, GamepadInput, DEFAULT_DEADZONE};
+use crate::core::window::WindowDim;
+use glfw::{GamepadAxis, GamepadState, JoystickId, Key, Modifiers, MouseButton, WindowEvent};
 use serde::de::DeserializeOwned;
 use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
+use std::time::{Duration, Instant};
 
+pub mod gamepad;
 pub mod ser;
 pub trait InputAction: Hash + Eq + PartialEq + Clone + DeserializeOwned {
     fn get_default_key_mapping() -> HashMap<Key, Self>;
     fn get_default_mouse_mapping() -> HashMap<MouseButton, Self>;
+    fn get_default_gamepad_mapping() -> HashMap<GamepadInput, Self>;
+}
+
+/// Anything that can be bound to an action.
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum Binding {
+    Key(Key),
+    Mouse(MouseButton),
+    Gamepad(GamepadInput),
+}
+
+impl std::fmt::Display for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Binding::Key(k) => write!(f, "{:?}", k),
+            Binding::Mouse(btn) => write!(f, "Mouse {:?}", btn),
+            Binding::Gamepad(GamepadInput::Button(btn)) => write!(f, "Pad {:?}", btn),
+            Binding::Gamepad(GamepadInput::Axis(axis, dir)) => {
+                write!(f, "Pad {:?} {:?}", axis, dir)
+            }
+        }
+    }
+}
+
+/// Inputs that trigger each action. An input triggers at most one action but an action can be
+/// triggered by several inputs.
+#[derive(Clone)]
+pub struct InputMapping<A> {
+    pub keys: HashMap<Key, A>,
+    pub mouse: HashMap<MouseButton, A>,
+    pub gamepad: HashMap<GamepadInput, A>,
+    /// Stick values under this are ignored.
+    pub deadzone: f32,
+}
+
+impl<A> InputMapping<A>
+where
+    A: InputAction,
+{
+    /// Mapping without any binding.
+    pub fn empty(deadzone: f32) -> Self {
+        Self {
+            keys: HashMap::new(),
+            mouse: HashMap::new(),
+            gamepad: HashMap::new(),
+            deadzone,
+        }
+    }
+
+    /// All the inputs that trigger the action, sorted by name.
+    pub fn bindings(&self, action: &A) -> Vec<Binding> {
+        let keys = self
+            .keys
+            .iter()
+            .filter(|(_, a)| *a == action)
+            .map(|(k, _)| Binding::Key(*k));
+        let mouse = self
+            .mouse
+            .iter()
+            .filter(|(_, a)| *a == action)
+            .map(|(btn, _)| Binding::Mouse(*btn));
+        let gamepad = self
+            .gamepad
+            .iter()
+            .filter(|(_, a)| *a == action)
+            .map(|(input, _)| Binding::Gamepad(*input));
+
+        let mut bindings: Vec<Binding> = keys.chain(mouse).chain(gamepad).collect();
+        bindings.sort_by_key(|b| b.to_string());
+        bindings
+    }
+
+    /// Action triggered by the input.
+    pub fn action(&self, binding: Binding) -> Option<&A> {
+        match binding {
+            Binding::Key(k) => self.keys.get(&k),
+            Binding::Mouse(btn) => self.mouse.get(&btn),
+            Binding::Gamepad(input) => self.gamepad.get(&input),
+        }
+    }
+
+    /// Bind the input to the action. If the input was already bound to another action, that
+    /// action is returned.
+    pub fn bind(&mut self, binding: Binding, action: A) -> Option<A> {
+        let previous = match binding {
+            Binding::Key(k) => self.keys.insert(k, action.clone()),
+            Binding::Mouse(btn) => self.mouse.insert(btn, action.clone()),
+            Binding::Gamepad(input) => self.gamepad.insert(input, action.clone()),
+        };
+        previous.filter(|a| *a != action)
+    }
+
+    /// Remove the input. Returns the action that was triggered by it.
+    pub fn unbind(&mut self, binding: Binding) -> Option<A> {
+        match binding {
+            Binding::Key(k) => self.keys.remove(&k),
+            Binding::Mouse(btn) => self.mouse.remove(&btn),
+            Binding::Gamepad(input) => self.gamepad.remove(&input),
+        }
+    }
+}
+
+impl<A> Default for InputMapping<A>
+where
+    A: InputAction,
+{
+    fn default() -> Self {
+        Self {
+            keys: A::get_default_key_mapping(),
+            mouse: A::get_default_mouse_mapping(),
+            gamepad: A::get_default_gamepad_mapping(),
+            deadzone: DEFAULT_DEADZONE,
+        }
+    }
 }
 
 pub struct Axis<A>
@@ -18,87 +135,354 @@ where
     pub right: A,
 }
 
-#[derive(Debug, Default)]
+/// Maximum delay between two presses to count as a double-tap.
+pub const DOUBLE_TAP_DELAY: Duration = Duration::from_millis(300);
+/// How long a press is remembered by `consume_buffered`.
+pub const INPUT_BUFFER: Duration = Duration::from_millis(150);
+
+#[derive(Debug)]
 pub struct Input<A>
 where
     A: InputAction,
 {
-    /// true for pressed
-    action_state: HashMap<A, bool>,
+    /// Keys and mouse buttons that are held.
+    held: HashSet<Binding>,
     just_pressed: HashSet<A>,
+    just_released: HashSet<A>,
+    double_tapped: HashSet<A>,
+    /// When the actions that are held were pressed.
+    pressed_since: HashMap<A, Instant>,
+    /// Last press for each action, used to detect double-taps.
+    last_tap: HashMap<A, Instant>,
+    /// Presses that were not consumed yet.
+    buffered: HashMap<A, Instant>,
+    /// Modifiers held during the last key or mouse event.
+    modifiers: Modifiers,
+    /// Vertical scroll during this frame.
+    scroll: f32,
 
+    /// Position of the mouse on the virtual screen.
     mouse_pos: glam::Vec2,
+    window_dim: Option<WindowDim>,
 
     key_mapping: HashMap<Key, A>,
     mouse_mapping: HashMap<MouseButton, A>,
+    gamepad_mapping: HashMap<GamepadInput, A>,
+
+    /// Gamepad currently used.
+    gamepad: Option<JoystickId>,
+    /// Value between 0 and 1 for each action triggered by the gamepad.
+    gamepad_state: HashMap<A, f32>,
+    deadzone: f32,
+    /// Direction of the right stick. Kept until the mouse moves.
+    aim: Option<glam::Vec2>,
+    /// Gamepad inputs that are held, bound or not.
+    gamepad_held: HashSet<GamepadInput>,
+    /// Gamepad inputs that were pressed during this frame, bound or not.
+    gamepad_just_pressed: Vec<GamepadInput>,
 }
 
 impl<A> Input<A>
 where
     A: InputAction,
 {
-    pub fn new(key_mapping: HashMap<Key, A>, mouse_mapping: HashMap<MouseButton, A>) -> Self {
+    pub fn new(mapping: InputMapping<A>) -> Self {
         Self {
-            action_state: HashMap::default(),
+            held: HashSet::default(),
             just_pressed: HashSet::default(),
+            just_released: HashSet::default(),
+            double_tapped: HashSet::default(),
+            pressed_since: HashMap::default(),
+            last_tap: HashMap::default(),
+            buffered: HashMap::default(),
+            modifiers: Modifiers::empty(),
+            scroll: 0.0,
             mouse_pos: glam::Vec2::zero(),
-            key_mapping,
-            mouse_mapping,
+            window_dim: None,
+            key_mapping: mapping.keys,
+            mouse_mapping: mapping.mouse,
+            gamepad_mapping: mapping.gamepad,
+            gamepad: None,
+            gamepad_state: HashMap::default(),
+            deadzone: mapping.deadzone,
+            aim: None,
+            gamepad_held: HashSet::default(),
+            gamepad_just_pressed: vec![],
+        }
+    }
+
+    /// Current bindings.
+    pub fn mapping(&self) -> InputMapping<A> {
+        InputMapping {
+            keys: self.key_mapping.clone(),
+            mouse: self.mouse_mapping.clone(),
+            gamepad: self.gamepad_mapping.clone(),
+            deadzone: self.deadzone,
         }
     }
 
+    /// Change the bindings. The state of all actions is reset.
+    pub fn set_mapping(&mut self, mapping: InputMapping<A>) {
+        self.key_mapping = mapping.keys;
+        self.mouse_mapping = mapping.mouse;
+        self.gamepad_mapping = mapping.gamepad;
+        self.deadzone = mapping.deadzone;
+        self.held.clear();
+        self.just_pressed.clear();
+        self.just_released.clear();
+        self.double_tapped.clear();
+        self.pressed_since.clear();
+        self.buffered.clear();
+        self.gamepad_state.clear();
+    }
+
+    /// Gamepad inputs pressed during this frame, even if they are not bound to an action.
+    pub fn gamepad_just_pressed(&self) -> &[GamepadInput] {
+        &self.gamepad_just_pressed
+    }
+
+    /// Needs to be called whenever the window is resized so that mouse position is correct.
+    pub fn set_window_dim(&mut self, window_dim: WindowDim) {
+        self.window_dim = Some(window_dim);
+    }
+
     pub fn prepare(&mut self) {
         self.just_pressed.clear();
+        self.just_released.clear();
+        self.double_tapped.clear();
+        self.scroll = 0.0;
+        self.buffered
+            .retain(|_, pressed_at| pressed_at.elapsed() <= INPUT_BUFFER);
     }
+
     pub fn process_event(&mut self, ev: WindowEvent) {
         match ev {
-            WindowEvent::Key(key, _, glfw::Action::Press, _) => {
-                if let Some(action) = self.key_mapping.get(&key).cloned() {
-                    self.action_state.insert(action.clone(), true);
-                    self.just_pressed.insert(action);
-                }
+            WindowEvent::Key(key, _, glfw::Action::Press, mods) => {
+                self.modifiers = mods;
+                self.press(Binding::Key(key));
+            }
+            WindowEvent::Key(key, _, glfw::Action::Release, mods) => {
+                self.modifiers = mods;
+                self.release(Binding::Key(key));
             }
-            WindowEvent::Key(key, _, glfw::Action::Release, _) => {
-                if let Some(action) = self.key_mapping.get(&key).cloned() {
-                    self.action_state.insert(action, false);
-                }
+            WindowEvent::MouseButton(btn, glfw::Action::Press, mods) => {
+                self.modifiers = mods;
+                self.press(Binding::Mouse(btn));
             }
-            WindowEvent::MouseButton(btn, glfw::Action::Press, _) => {
-                if let Some(action) = self.mouse_mapping.get(&btn).cloned() {
-                    self.action_state.insert(action.clone(), true);
-                    self.just_pressed.insert(action);
-                }
+            WindowEvent::MouseButton(btn, glfw::Action::Release, mods) => {
+                self.modifiers = mods;
+                self.release(Binding::Mouse(btn));
             }
-            WindowEvent::MouseButton(btn, glfw::Action::Release, _) => {
-                if let Some(action) = self.mouse_mapping.get(&btn).cloned() {
-                    self.action_state.insert(action, false);
-                }
+            WindowEvent::CursorPos(x, y) => {
+                self.mouse_pos = glam::vec2(x as f32, y as f32);
+                // Moving the mouse will switch back to mouse aiming.
+                self.aim = None;
             }
-            WindowEvent::CursorPos(x, y) => self.mouse_pos = glam::vec2(x as f32, y as f32),
+            WindowEvent::Scroll(_, y) => self.scroll += y as f32,
             _ => {}
         }
     }
 
-    pub fn get_axis(&self, axis: Axis<A>) -> f32 {
-        match (
-            self.action_state.get(&axis.left).unwrap_or(&false),
-            self.action_state.get(&axis.right).unwrap_or(&false),
-        ) {
-            (&true, &true) => 1.0,
-            (&true, &false) => -1.0,
-            (&false, &true) => 1.0,
-            (&false, &false) => 0.0,
+    fn bound_action(&self, binding: &Binding) -> Option<&A> {
+        match binding {
+            Binding::Key(k) => self.key_mapping.get(k),
+            Binding::Mouse(btn) => self.mouse_mapping.get(btn),
+            Binding::Gamepad(input) => self.gamepad_mapping.get(input),
+        }
+    }
+
+    /// True if a key or mouse button bound to the action is held.
+    fn is_held(&self, action: &A) -> bool {
+        self.held
+            .iter()
+            .any(|b| self.bound_action(b) == Some(action))
+    }
+
+    fn press(&mut self, binding: Binding) {
+        let action = self.bound_action(&binding).cloned();
+        let was_pressed = action.as_ref().map(|a| self.is_pressed(a.clone()));
+        self.held.insert(binding);
+        if let (Some(action), Some(false)) = (action, was_pressed) {
+            self.on_pressed(action);
+        }
+    }
+
+    fn release(&mut self, binding: Binding) {
+        if !self.held.remove(&binding) {
+            return;
+        }
+        if let Some(action) = self.bound_action(&binding).cloned() {
+            if !self.is_pressed(action.clone()) {
+                self.on_released(action);
+            }
         }
     }
 
+    fn on_pressed(&mut self, action: A) {
+        let now = Instant::now();
+        self.just_pressed.insert(action.clone());
+        self.pressed_since.insert(action.clone(), now);
+        self.buffered.insert(action.clone(), now);
+
+        let is_double_tap = self
+            .last_tap
+            .get(&action)
+            .map(|t| now.duration_since(*t) <= DOUBLE_TAP_DELAY)
+            .unwrap_or(false);
+        if is_double_tap {
+            // A third tap will not count as another double-tap.
+            self.last_tap.remove(&action);
+            self.double_tapped.insert(action);
+        } else {
+            self.last_tap.insert(action, now);
+        }
+    }
+
+    fn on_released(&mut self, action: A) {
+        self.pressed_since.remove(&action);
+        self.just_released.insert(action);
+    }
+
+    /// Poll the gamepad. Needs to be called once per frame after the window events. A gamepad
+    /// connected while the game is running will be picked up automatically.
+    ///
+    /// Returns the gamepad state so that it can be used for something else (e.g. GUI).
+    pub fn process_gamepad(&mut self, glfw: &glfw::Glfw) -> Option<GamepadState> {
+        let joystick = find_gamepad(glfw, self.gamepad);
+        let id = joystick.as_ref().map(|j| j.id);
+        if id != self.gamepad {
+            match joystick.as_ref() {
+                Some(j) => info!("Gamepad connected: {:?}", j.get_gamepad_name()),
+                None => info!("Gamepad disconnected"),
+            }
+            self.gamepad = id;
+        }
+
+        let state = joystick.and_then(|j| j.get_gamepad_state());
+        let mut gamepad_state: HashMap<A, f32> = HashMap::new();
+        let mut gamepad_held = HashSet::new();
+        if let Some(ref state) = state {
+            gamepad_held = gamepad::all_inputs()
+                .filter(|input| input.value(state, self.deadzone) > 0.5)
+                .collect();
+
+            for (input, action) in &self.gamepad_mapping {
+                let value = input.value(state, self.deadzone);
+                let entry = gamepad_state.entry(action.clone()).or_insert(0.0);
+                *entry = entry.max(value);
+            }
+
+            // Y axis is pointing down for the gamepad.
+            let aim = gamepad::stick(
+                state,
+                GamepadAxis::AxisRightX,
+                GamepadAxis::AxisRightY,
+                self.deadzone,
+            ) * glam::vec2(1.0, -1.0);
+            if aim.length_squared() > 0.0 {
+                self.aim = Some(aim.normalize());
+            }
+        }
+
+        let actions: HashSet<A> = gamepad_state
+            .keys()
+            .chain(self.gamepad_state.keys())
+            .cloned()
+            .collect();
+        let previous_state = std::mem::replace(&mut self.gamepad_state, gamepad_state);
+        for action in actions {
+            let was_pressed =
+                self.is_held(&action) || previous_state.get(&action).copied().unwrap_or(0.0) > 0.5;
+            let is_pressed = self.is_pressed(action.clone());
+            if is_pressed && !was_pressed {
+                self.on_pressed(action);
+            } else if was_pressed && !is_pressed {
+                self.on_released(action);
+            }
+        }
+        self.gamepad_just_pressed = gamepad_held
+            .difference(&self.gamepad_held)
+            .copied()
+            .collect();
+        self.gamepad_held = gamepad_held;
+
+        state
+    }
+
+    /// Value between 0 and 1 for the action.
+    fn value(&self, action: &A) -> f32 {
+        if self.is_held(action) {
+            1.0
+        } else {
+            self.gamepad_state.get(action).copied().unwrap_or(0.0)
+        }
+    }
+
+    /// Value between -1 and 1. Analog when using the gamepad sticks.
+    pub fn get_axis(&self, axis: Axis<A>) -> f32 {
+        self.value(&axis.right) - self.value(&axis.left)
+    }
+
     pub fn is_just_pressed(&self, action: A) -> bool {
         self.just_pressed.contains(&action)
     }
 
+    /// True if the action has been pressed during this frame while the modifiers were held
+    /// (e.g. Shift+F).
+    pub fn is_just_pressed_with(&self, action: A, modifiers: Modifiers) -> bool {
+        self.is_just_pressed(action) && self.modifiers.contains(modifiers)
+    }
+
+    /// True as long as the action is held.
+    pub fn is_pressed(&self, action: A) -> bool {
+        self.value(&action) > 0.5
+    }
+
+    pub fn is_just_released(&self, action: A) -> bool {
+        self.just_released.contains(&action)
+    }
+
+    /// True if the action has been pressed twice in a short time, the second press being during
+    /// this frame.
+    pub fn is_double_tapped(&self, action: A) -> bool {
+        self.double_tapped.contains(&action)
+    }
+
+    /// How long the action has been held.
+    pub fn held_duration(&self, action: A) -> Option<Duration> {
+        self.pressed_since.get(&action).map(|t| t.elapsed())
+    }
+
+    /// True if the action has been pressed recently and this press was not consumed yet. This
+    /// is useful when an action cannot be performed right away (e.g. cooldown) to avoid
+    /// dropping the player input.
+    pub fn consume_buffered(&mut self, action: A) -> bool {
+        match self.buffered.remove(&action) {
+            Some(pressed_at) => pressed_at.elapsed() <= INPUT_BUFFER,
+            None => false,
+        }
+    }
+
+    /// Modifiers held during the last key or mouse event.
+    pub fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+
+    /// Mouse wheel movement during this frame. Positive when scrolling up.
+    pub fn scroll_axis(&self) -> f32 {
+        self.scroll
+    }
+
+    /// Direction of the right stick (Y pointing up) if the gamepad is used to aim. Moving the
+    /// mouse will reset it.
+    pub fn aim_direction(&self) -> Option<glam::Vec2> {
+        self.aim
+    }
+
+    /// Mouse position in normalized device coordinates.
     pub fn mouse_position(&self) -> glam::Vec2 {
-        glam::vec2(
-            (self.mouse_pos.x() / WIDTH as f32) * 2.0 - 1.0,
-            ((HEIGHT as f32 - self.mouse_pos.y()) / HEIGHT as f32) * 2.0 - 1.0,
-        )
+        self.window_dim
+            .map(|dim| dim.screen_to_ndc(self.mouse_pos))
+            .unwrap_or_default()
     }
 }
@@ -0,0 +1,146 @@
+//! Gamepad support through the GLFW gamepad API. Only one gamepad is used at a time: the first
+//! one that is connected. If it is disconnected, another one will be picked on the next poll.
+use glfw::{GamepadAxis, GamepadButton, GamepadState, Glfw, Joystick, JoystickId};
+
+/// Stick values under this are ignored by default.
+pub const DEFAULT_DEADZONE: f32 = 0.2;
+
+const JOYSTICKS: [JoystickId; 16] = [
+    JoystickId::Joystick1,
+    JoystickId::Joystick2,
+    JoystickId::Joystick3,
+    JoystickId::Joystick4,
+    JoystickId::Joystick5,
+    JoystickId::Joystick6,
+    JoystickId::Joystick7,
+    JoystickId::Joystick8,
+    JoystickId::Joystick9,
+    JoystickId::Joystick10,
+    JoystickId::Joystick11,
+    JoystickId::Joystick12,
+    JoystickId::Joystick13,
+    JoystickId::Joystick14,
+    JoystickId::Joystick15,
+    JoystickId::Joystick16,
+];
+
+const BUTTONS: [GamepadButton; 14] = [
+    GamepadButton::ButtonA,
+    GamepadButton::ButtonB,
+    GamepadButton::ButtonX,
+    GamepadButton::ButtonY,
+    GamepadButton::ButtonLeftBumper,
+    GamepadButton::ButtonRightBumper,
+    GamepadButton::ButtonBack,
+    GamepadButton::ButtonStart,
+    GamepadButton::ButtonLeftThumb,
+    GamepadButton::ButtonRightThumb,
+    GamepadButton::ButtonDpadUp,
+    GamepadButton::ButtonDpadRight,
+    GamepadButton::ButtonDpadDown,
+    GamepadButton::ButtonDpadLeft,
+];
+
+const AXES: [GamepadAxis; 6] = [
+    GamepadAxis::AxisLeftX,
+    GamepadAxis::AxisLeftY,
+    GamepadAxis::AxisRightX,
+    GamepadAxis::AxisRightY,
+    GamepadAxis::AxisLeftTrigger,
+    GamepadAxis::AxisRightTrigger,
+];
+
+/// Direction of an axis that triggers an action.
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum AxisDirection {
+    Positive,
+    Negative,
+}
+
+/// Gamepad input that can be mapped to an action.
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum GamepadInput {
+    Button(GamepadButton),
+    Axis(GamepadAxis, AxisDirection),
+}
+
+impl GamepadInput {
+    /// Value between 0 and 1 of the input.
+    pub fn value(&self, state: &GamepadState, deadzone: f32) -> f32 {
+        match *self {
+            GamepadInput::Button(btn) => {
+                if state.get_button_state(btn) == glfw::Action::Press {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            GamepadInput::Axis(axis, direction) => {
+                let value = axis_value(state, axis, deadzone);
+                match direction {
+                    AxisDirection::Positive => value.max(0.0),
+                    AxisDirection::Negative => (-value).max(0.0),
+                }
+            }
+        }
+    }
+}
+
+/// Every gamepad input that can be bound.
+pub fn all_inputs() -> impl Iterator<Item = GamepadInput> {
+    let buttons = BUTTONS.iter().map(|btn| GamepadInput::Button(*btn));
+    let axes = AXES.iter().flat_map(|axis| {
+        let positive = GamepadInput::Axis(*axis, AxisDirection::Positive);
+        let negative = GamepadInput::Axis(*axis, AxisDirection::Negative);
+        vec![positive, negative]
+    });
+    buttons.chain(axes)
+}
+
+/// Value of the stick after the deadzone is applied. The deadzone is radial so that diagonals
+/// are not harder to reach and the value is rescaled so that it starts at 0 right after the
+/// deadzone.
+pub fn stick(state: &GamepadState, x: GamepadAxis, y: GamepadAxis, deadzone: f32) -> glam::Vec2 {
+    let raw = glam::vec2(state.get_axis(x), state.get_axis(y));
+    let length = raw.length();
+    if length <= deadzone {
+        return glam::Vec2::zero();
+    }
+    let scaled = ((length - deadzone) / (1.0 - deadzone)).min(1.0);
+    raw / length * scaled
+}
+
+/// Value of an axis between -1 and 1 (0 and 1 for the triggers) with the deadzone applied.
+fn axis_value(state: &GamepadState, axis: GamepadAxis, deadzone: f32) -> f32 {
+    use GamepadAxis::*;
+    match axis {
+        AxisLeftX => stick(state, AxisLeftX, AxisLeftY, deadzone).x(),
+        AxisLeftY => stick(state, AxisLeftX, AxisLeftY, deadzone).y(),
+        AxisRightX => stick(state, AxisRightX, AxisRightY, deadzone).x(),
+        AxisRightY => stick(state, AxisRightX, AxisRightY, deadzone).y(),
+        AxisLeftTrigger | AxisRightTrigger => {
+            // triggers are between -1 (released) and 1 (fully pressed).
+            let value = (state.get_axis(axis) + 1.0) / 2.0;
+            if value <= deadzone {
+                0.0
+            } else {
+                (value - deadzone) / (1.0 - deadzone)
+            }
+        }
+    }
+}
+
+/// Find the gamepad to use. The current one is kept as long as it is connected.
+pub fn find_gamepad(glfw: &Glfw, current: Option<JoystickId>) -> Option<Joystick> {
+    if let Some(id) = current {
+        let joystick = glfw.get_joystick(id);
+        if joystick.is_gamepad() {
+            return Some(joystick);
+        }
+    }
+
+    JOYSTICKS
+        .iter()
+        .map(|id| glfw.get_joystick(*id))
+        .find(|joystick| joystick.is_gamepad())
+}
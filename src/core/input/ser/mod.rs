@@ -1,130 +1,252 @@
-use glfw::Key;
+use crate::core::input::gamepad::{AxisDirection, GamepadInput};
+use crate::core::input::Binding;
+use glfw::{GamepadAxis, GamepadButton, Key, MouseButton};
 use serde_derive::{Deserialize, Serialize};
 
-/// Input keys. Copy of glfw just for serialization.
-#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
-pub enum BasicKey {
-    Space,
-
-    Num0,
-    Num1,
-    Num2,
-    Num3,
-    Num4,
-    Num5,
-    Num6,
-    Num7,
-    Num8,
-    Num9,
+/// Generate a serializable copy of a glfw enum with the conversions in both directions.
+macro_rules! serializable_copy {
+    (
+        $(#[$meta:meta])*
+        $name:ident => $target:ident {
+            $($variant:ident => $glfw:ident,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+        pub enum $name {
+            $($variant,)*
+        }
 
-    A,
-    B,
-    C,
-    D,
-    E,
-    F,
-    G,
-    H,
-    I,
-    J,
-    K,
-    L,
-    M,
-    N,
-    O,
-    P,
-    Q,
-    R,
-    S,
-    T,
-    U,
-    V,
-    W,
-    X,
-    Y,
-    Z,
+        impl From<$name> for $target {
+            fn from(v: $name) -> Self {
+                match v {
+                    $($name::$variant => $target::$glfw,)*
+                }
+            }
+        }
 
-    Enter,
+        impl $name {
+            /// None if there is no serializable copy of this value.
+            #[allow(unreachable_patterns)]
+            pub fn from_glfw(v: $target) -> Option<Self> {
+                match v {
+                    $($target::$glfw => Some($name::$variant),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
 
-    Right,
-    Left,
-    Down,
-    Up,
+serializable_copy! {
+    /// Input keys. Copy of glfw just for serialization.
+    BasicKey => Key {
+        Space => Space,
+        Apostrophe => Apostrophe,
+        Comma => Comma,
+        Minus => Minus,
+        Period => Period,
+        Slash => Slash,
+        Num0 => Num0,
+        Num1 => Num1,
+        Num2 => Num2,
+        Num3 => Num3,
+        Num4 => Num4,
+        Num5 => Num5,
+        Num6 => Num6,
+        Num7 => Num7,
+        Num8 => Num8,
+        Num9 => Num9,
+        Semicolon => Semicolon,
+        Equal => Equal,
+        A => A,
+        B => B,
+        C => C,
+        D => D,
+        E => E,
+        F => F,
+        G => G,
+        H => H,
+        I => I,
+        J => J,
+        K => K,
+        L => L,
+        M => M,
+        N => N,
+        O => O,
+        P => P,
+        Q => Q,
+        R => R,
+        S => S,
+        T => T,
+        U => U,
+        V => V,
+        W => W,
+        X => X,
+        Y => Y,
+        Z => Z,
+        LeftBracket => LeftBracket,
+        Backslash => Backslash,
+        RightBracket => RightBracket,
+        GraveAccent => GraveAccent,
+        Escape => Escape,
+        Enter => Enter,
+        Tab => Tab,
+        Backspace => Backspace,
+        Insert => Insert,
+        Delete => Delete,
+        Right => Right,
+        Left => Left,
+        Down => Down,
+        Up => Up,
+        PageUp => PageUp,
+        PageDown => PageDown,
+        Home => Home,
+        End => End,
+        CapsLock => CapsLock,
+        ScrollLock => ScrollLock,
+        NumLock => NumLock,
+        PrintScreen => PrintScreen,
+        Pause => Pause,
+        F1 => F1,
+        F2 => F2,
+        F3 => F3,
+        F4 => F4,
+        F5 => F5,
+        F6 => F6,
+        F7 => F7,
+        F8 => F8,
+        F9 => F9,
+        F10 => F10,
+        F11 => F11,
+        F12 => F12,
+        Kp0 => Kp0,
+        Kp1 => Kp1,
+        Kp2 => Kp2,
+        Kp3 => Kp3,
+        Kp4 => Kp4,
+        Kp5 => Kp5,
+        Kp6 => Kp6,
+        Kp7 => Kp7,
+        Kp8 => Kp8,
+        Kp9 => Kp9,
+        KpDecimal => KpDecimal,
+        KpDivide => KpDivide,
+        KpMultiply => KpMultiply,
+        KpSubtract => KpSubtract,
+        KpAdd => KpAdd,
+        KpEnter => KpEnter,
+        KpEqual => KpEqual,
+        LeftShift => LeftShift,
+        LeftControl => LeftControl,
+        LeftAlt => LeftAlt,
+        LeftSuper => LeftSuper,
+        RightShift => RightShift,
+        RightControl => RightControl,
+        RightAlt => RightAlt,
+        RightSuper => RightSuper,
+        Menu => Menu,
+    }
 }
 
-impl Into<Key> for BasicKey {
-    fn into(self) -> Key {
-        use BasicKey::*;
-        match self {
-            Space => Key::Space,
+serializable_copy! {
+    BasicMouseButton => MouseButton {
+        Button1 => Button1,
+        Button2 => Button2,
+        Button3 => Button3,
+        Button4 => Button4,
+        Button5 => Button5,
+    }
+}
 
-            Num0 => Key::Num0,
-            Num1 => Key::Num1,
-            Num2 => Key::Num2,
-            Num3 => Key::Num4,
-            Num4 => Key::Num4,
-            Num5 => Key::Num5,
-            Num6 => Key::Num6,
-            Num7 => Key::Num7,
-            Num8 => Key::Num8,
-            Num9 => Key::Num9,
+serializable_copy! {
+    BasicGamepadButton => GamepadButton {
+        A => ButtonA,
+        B => ButtonB,
+        X => ButtonX,
+        Y => ButtonY,
+        LeftBumper => ButtonLeftBumper,
+        RightBumper => ButtonRightBumper,
+        Back => ButtonBack,
+        Start => ButtonStart,
+        LeftThumb => ButtonLeftThumb,
+        RightThumb => ButtonRightThumb,
+        DpadUp => ButtonDpadUp,
+        DpadRight => ButtonDpadRight,
+        DpadDown => ButtonDpadDown,
+        DpadLeft => ButtonDpadLeft,
+    }
+}
 
-            A => Key::A,
-            B => Key::B,
-            C => Key::C,
-            D => Key::D,
-            E => Key::E,
-            F => Key::F,
-            G => Key::G,
-            H => Key::H,
-            I => Key::I,
-            J => Key::J,
-            K => Key::K,
-            L => Key::L,
-            M => Key::M,
-            N => Key::N,
-            O => Key::O,
-            P => Key::P,
-            Q => Key::Q,
-            R => Key::R,
-            S => Key::S,
-            T => Key::T,
-            U => Key::U,
-            V => Key::V,
-            W => Key::W,
-            X => Key::X,
-            Y => Key::Y,
-            Z => Key::Z,
+serializable_copy! {
+    BasicGamepadAxis => GamepadAxis {
+        LeftX => AxisLeftX,
+        LeftY => AxisLeftY,
+        RightX => AxisRightX,
+        RightY => AxisRightY,
+        LeftTrigger => AxisLeftTrigger,
+        RightTrigger => AxisRightTrigger,
+    }
+}
 
-            Enter => Key::Enter,
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum BasicAxisDirection {
+    Positive,
+    Negative,
+}
 
-            Right => Key::Right,
-            Left => Key::Left,
-            Down => Key::Down,
-            Up => Key::Up,
+impl From<BasicAxisDirection> for AxisDirection {
+    fn from(direction: BasicAxisDirection) -> Self {
+        match direction {
+            BasicAxisDirection::Positive => AxisDirection::Positive,
+            BasicAxisDirection::Negative => AxisDirection::Negative,
         }
     }
 }
 
-#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
-pub enum BasicMouseButton {
-    Button1,
-    Button2,
-    Button3,
-}
-
-impl Into<glfw::MouseButton> for BasicMouseButton {
-    fn into(self) -> glfw::MouseButton {
-        match self {
-            Self::Button1 => glfw::MouseButton::Button1,
-            Self::Button2 => glfw::MouseButton::Button2,
-            Self::Button3 => glfw::MouseButton::Button3,
+impl From<AxisDirection> for BasicAxisDirection {
+    fn from(direction: AxisDirection) -> Self {
+        match direction {
+            AxisDirection::Positive => BasicAxisDirection::Positive,
+            AxisDirection::Negative => BasicAxisDirection::Negative,
         }
     }
 }
 
-#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub enum Input {
     Key(BasicKey),
     Mouse(BasicMouseButton),
+    GamepadButton(BasicGamepadButton),
+    /// Axis and the direction that triggers the action. Y axis is pointing down.
+    GamepadAxis(BasicGamepadAxis, BasicAxisDirection),
+}
+
+impl From<Input> for Binding {
+    fn from(input: Input) -> Self {
+        match input {
+            Input::Key(k) => Binding::Key(k.into()),
+            Input::Mouse(btn) => Binding::Mouse(btn.into()),
+            Input::GamepadButton(btn) => Binding::Gamepad(GamepadInput::Button(btn.into())),
+            Input::GamepadAxis(axis, dir) => {
+                Binding::Gamepad(GamepadInput::Axis(axis.into(), dir.into()))
+            }
+        }
+    }
+}
+
+impl Input {
+    /// None if the binding cannot be serialized.
+    pub fn from_binding(binding: Binding) -> Option<Self> {
+        match binding {
+            Binding::Key(k) => BasicKey::from_glfw(k).map(Input::Key),
+            Binding::Mouse(btn) => BasicMouseButton::from_glfw(btn).map(Input::Mouse),
+            Binding::Gamepad(GamepadInput::Button(btn)) => {
+                BasicGamepadButton::from_glfw(btn).map(Input::GamepadButton)
+            }
+            Binding::Gamepad(GamepadInput::Axis(axis, dir)) => {
+                BasicGamepadAxis::from_glfw(axis).map(|axis| Input::GamepadAxis(axis, dir.into()))
+            }
+        }
+    }
 }
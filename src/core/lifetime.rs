@@ -0,0 +1,81 @@
+//! Components that despawn an entity when it is not needed anymore. The entities are deleted by
+//! sending `Delete`, so they are removed by the `GarbageCollector` at the end of the frame.
+//!
+//! - `TimeToLive`: after a duration.
+//! - `DespawnOutOfBounds`: when the entity leaves the `StageBounds`.
+//! - `DespawnWithParent`: when the parent is deleted (see `GarbageCollector`).
+//! - `DespawnOnExit`: when another scene is pushed above the scene that owns the entity. Overlays
+//!   (pause, console...) do not count (see `SceneStack::push`).
+use crate::core::events::Events;
+use crate::core::timer::Timer;
+use crate::core::transform::Transform;
+use crate::event::Delete;
+use crate::resources::Resources;
+use hecs::World;
+use serde_derive::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeToLive {
+    pub timer: Timer,
+}
+
+impl TimeToLive {
+    pub fn of_seconds(seconds: f32) -> Self {
+        Self {
+            timer: Timer::of_seconds(seconds),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct DespawnOutOfBounds;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct DespawnWithParent;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct DespawnOnExit;
+
+/// Area where the entities with `DespawnOutOfBounds` can be. Nothing is despawned when the
+/// resource is missing.
+#[derive(Debug, Clone, Copy)]
+pub struct StageBounds {
+    pub min: glam::Vec2,
+    pub max: glam::Vec2,
+}
+
+impl StageBounds {
+    pub fn contains(&self, position: glam::Vec2) -> bool {
+        position.x() >= self.min.x()
+            && position.x() <= self.max.x()
+            && position.y() >= self.min.y()
+            && position.y() <= self.max.y()
+    }
+}
+
+/// Delete the entities that lived long enough or left the stage.
+pub fn update_lifetimes(world: &World, resources: &Resources, dt: Duration) {
+    let mut to_delete = vec![];
+    for (e, ttl) in world.query::<&mut TimeToLive>().iter() {
+        ttl.timer.tick(dt);
+        if ttl.timer.finished() {
+            to_delete.push(Delete(e));
+        }
+    }
+
+    if let Some(bounds) = resources.fetch::<StageBounds>() {
+        to_delete.extend(
+            world
+                .query::<(&Transform, &DespawnOutOfBounds)>()
+                .iter()
+                .filter(|(_, (t, _))| !bounds.contains(t.translation))
+                .map(|(e, _)| Delete(e)),
+        );
+    }
+
+    if !to_delete.is_empty() {
+        let mut channel = resources.fetch_mut::<Events<Delete>>().unwrap();
+        channel.defer_all(to_delete);
+    }
+}
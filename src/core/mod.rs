@@ -2,11 +2,17 @@ pub mod animation;
 pub mod audio;
 pub mod camera;
 pub mod colors;
+pub mod console;
 pub mod curve;
+pub mod effect;
+pub mod events;
 pub mod input;
+pub mod lifetime;
 pub mod noise;
+pub mod profiler;
 pub mod random;
 pub mod scene;
+pub mod system;
 pub mod timer;
 pub mod transform;
 pub mod window;
@@ -0,0 +1,417 @@
+//! Lightweight frame profiler. Phases of the frame and systems are measured with `scope`, which
+//! records the time spent until the returned guard is dropped. Nothing is recorded when there is no
+//! `Profiler` in the resources.
+//!
+//! ```ignore
+//! {
+//!     let _scope = profiler::scope(resources, "render");
+//!     // ...
+//! }
+//! ```
+//!
+//! The last frames are kept to draw the overlay (see `Profiler::prepare_gui`). The spans can also
+//! be recorded and exported to the Chrome tracing format (open chrome://tracing or
+//! https://ui.perfetto.dev and load the file).
+use crate::core::colors::RgbaColor;
+use crate::core::events::LaggingReader;
+use crate::render::ui::Gui;
+use crate::resources::Resources;
+use serde_derive::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Number of frames displayed in the graph.
+const HISTORY_LEN: usize = 120;
+/// Recording stops after this number of spans.
+const MAX_RECORDED_SPANS: usize = 1_000_000;
+/// Duration of a frame at 60 FPS. Half the height of the graph.
+const TARGET_FRAME: Duration = Duration::from_millis(16);
+
+const WIDTH: f32 = 360.0;
+const GRAPH_HEIGHT: f32 = 80.0;
+const MARGIN: f32 = 10.0;
+const FONT_SIZE: f32 = 14.0;
+const LINE_HEIGHT: f32 = 18.0;
+
+/// Time spent in a phase or a system.
+#[derive(Debug, Clone)]
+pub struct Span {
+    pub name: &'static str,
+    /// Since the creation of the profiler.
+    pub start: Duration,
+    pub duration: Duration,
+    /// Number of scopes opened when this one was opened. 0 for the phases of the frame.
+    pub depth: usize,
+    /// 0 for the main thread. Systems that run in parallel have their own thread number.
+    pub thread: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FrameStats {
+    /// Since the creation of the profiler.
+    pub start: Duration,
+    /// Time spent working during the frame (without the sleep at the end).
+    pub duration: Duration,
+    pub spans: Vec<Span>,
+    pub entities: usize,
+    pub draw_calls: usize,
+}
+
+pub struct Profiler {
+    pub visible: bool,
+    epoch: Instant,
+    frame_start: Instant,
+    /// Spans of the current frame.
+    spans: Vec<Span>,
+    depth: usize,
+    history: VecDeque<FrameStats>,
+    /// Frames kept for the Chrome trace while recording.
+    recording: Option<Vec<FrameStats>>,
+    recorded_spans: usize,
+    /// Event readers that are falling behind, updated every frame.
+    lagging_readers: Vec<LaggingReader>,
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            visible: false,
+            epoch: now,
+            frame_start: now,
+            spans: vec![],
+            depth: 0,
+            history: VecDeque::with_capacity(HISTORY_LEN),
+            recording: None,
+            recorded_spans: 0,
+            lagging_readers: vec![],
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// Start recording, or stop and save the trace in the working directory.
+    pub fn toggle_recording(&mut self) {
+        if self.is_recording() {
+            let secs = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default();
+            if let Err(e) = self.stop_recording(format!("trace_{}.json", secs)) {
+                error!("Cannot save the trace = {:?}", e);
+            }
+        } else {
+            self.start_recording();
+        }
+    }
+
+    /// Keep the frames until `stop_recording` is called.
+    pub fn start_recording(&mut self) {
+        info!("Start recording the frames");
+        self.recording = Some(vec![]);
+        self.recorded_spans = 0;
+    }
+
+    /// Write the frames recorded since `start_recording` as a Chrome trace.
+    pub fn stop_recording<P: AsRef<Path>>(&mut self, path: P) -> Result<(), std::io::Error> {
+        let frames = match self.recording.take() {
+            Some(frames) => frames,
+            None => return Ok(()),
+        };
+        let trace = ChromeTrace::new(&frames);
+        let file = std::io::BufWriter::new(std::fs::File::create(path.as_ref())?);
+        serde_json::to_writer(file, &trace)?;
+        info!(
+            "Saved {} frames of trace to {}",
+            frames.len(),
+            path.as_ref().display()
+        );
+        Ok(())
+    }
+
+    pub fn begin_frame(&mut self) {
+        self.frame_start = Instant::now();
+        self.spans.clear();
+        self.depth = 0;
+    }
+
+    pub fn end_frame(&mut self, entities: usize, draw_calls: usize) {
+        let frame = FrameStats {
+            start: self.frame_start.duration_since(self.epoch),
+            duration: self.frame_start.elapsed(),
+            spans: std::mem::take(&mut self.spans),
+            entities,
+            draw_calls,
+        };
+
+        if let Some(recording) = self.recording.as_mut() {
+            if self.recorded_spans < MAX_RECORDED_SPANS {
+                self.recorded_spans += frame.spans.len();
+                recording.push(frame.clone());
+                if self.recorded_spans >= MAX_RECORDED_SPANS {
+                    warn!("Too many spans recorded, the next frames are not kept in the trace");
+                }
+            }
+        }
+
+        if self.history.len() == HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back(frame);
+    }
+
+    pub fn set_lagging_readers(&mut self, lagging_readers: Vec<LaggingReader>) {
+        self.lagging_readers = lagging_readers;
+    }
+
+    /// Add a span measured elsewhere (e.g. on another thread). It is nested in the scopes that are
+    /// currently opened.
+    pub fn record(
+        &mut self,
+        name: &'static str,
+        start: Instant,
+        duration: Duration,
+        thread: usize,
+    ) {
+        self.spans.push(Span {
+            name,
+            start: start.duration_since(self.epoch),
+            duration,
+            depth: self.depth,
+            thread,
+        });
+    }
+
+    /// Last frames, oldest first.
+    pub fn history(&self) -> impl Iterator<Item = &FrameStats> {
+        self.history.iter()
+    }
+
+    /// Average time spent in each span over the last frames, by depth then name.
+    fn averages(&self) -> BTreeMap<(usize, &'static str), Duration> {
+        let mut totals: BTreeMap<(usize, &'static str), Duration> = BTreeMap::new();
+        for frame in &self.history {
+            for span in &frame.spans {
+                *totals.entry((span.depth, span.name)).or_default() += span.duration;
+            }
+        }
+        let nb = self.history.len().max(1) as u32;
+        totals.values_mut().for_each(|total| *total /= nb);
+        totals
+    }
+
+    /// Graph of the frame times, counters, lagging event readers and average time of the phases
+    /// and systems.
+    pub fn prepare_gui(&self, gui: &mut Gui) {
+        let last = match self.history.back() {
+            Some(last) => last,
+            None => return,
+        };
+        let averages = self.averages();
+        let nb_lines = 2 + self.lagging_readers.len() + averages.len();
+        let height = GRAPH_HEIGHT + 4.0 * MARGIN + LINE_HEIGHT * nb_lines as f32;
+        gui.panel(
+            glam::Vec2::zero(),
+            glam::vec2(WIDTH, height),
+            RgbaColor::new(0, 0, 0, 200),
+        );
+
+        // Frame times. The line is the time available at 60 FPS.
+        let graph_pos = glam::vec2(MARGIN, MARGIN);
+        let bar_width = (WIDTH - 2.0 * MARGIN) / HISTORY_LEN as f32;
+        let scale = GRAPH_HEIGHT / (2.0 * TARGET_FRAME.as_secs_f32());
+        for (i, frame) in self.history.iter().enumerate() {
+            let bar_height = (frame.duration.as_secs_f32() * scale).min(GRAPH_HEIGHT);
+            let color = if frame.duration > TARGET_FRAME {
+                RgbaColor::new(255, 80, 80, 255)
+            } else {
+                RgbaColor::new(80, 255, 80, 255)
+            };
+            gui.panel(
+                graph_pos + glam::vec2(i as f32 * bar_width, GRAPH_HEIGHT - bar_height),
+                glam::vec2(bar_width.max(1.0), bar_height),
+                color,
+            );
+        }
+        gui.panel(
+            graph_pos + glam::vec2(0.0, GRAPH_HEIGHT / 2.0),
+            glam::vec2(WIDTH - 2.0 * MARGIN, 1.0),
+            RgbaColor::new(255, 255, 255, 150),
+        );
+
+        let mut y = graph_pos.y() + GRAPH_HEIGHT + MARGIN;
+        let text_color = gui.style.text_color;
+        let avg_frame = self
+            .history
+            .iter()
+            .map(|frame| frame.duration)
+            .sum::<Duration>()
+            / self.history.len() as u32;
+        gui.sized_label(
+            glam::vec2(MARGIN, y),
+            format!(
+                "frame {:.2} ms (avg {:.2} ms){}",
+                ms(last.duration),
+                ms(avg_frame),
+                if self.is_recording() { " REC" } else { "" }
+            ),
+            FONT_SIZE,
+            text_color,
+        );
+        y += LINE_HEIGHT;
+        gui.sized_label(
+            glam::vec2(MARGIN, y),
+            format!(
+                "entities {}   draw calls {}",
+                last.entities, last.draw_calls
+            ),
+            FONT_SIZE,
+            text_color,
+        );
+        y += LINE_HEIGHT;
+        for reader in &self.lagging_readers {
+            // Only the name of the type, without the module path.
+            let event = reader.event.rsplit("::").next().unwrap_or(reader.event);
+            gui.sized_label(
+                glam::vec2(MARGIN, y),
+                format!(
+                    "reader {} ({}) lagging: {} events, {} frames",
+                    reader.name, event, reader.pending, reader.frames
+                ),
+                FONT_SIZE,
+                RgbaColor::new(255, 80, 80, 255),
+            );
+            y += LINE_HEIGHT;
+        }
+        y += MARGIN;
+
+        // Phases first, then the systems, sorted by name.
+        for ((depth, name), duration) in averages {
+            gui.sized_label(
+                glam::vec2(MARGIN + depth as f32 * MARGIN * 2.0, y),
+                format!("{} {:.3} ms", name, ms(duration)),
+                FONT_SIZE,
+                if depth == 0 {
+                    text_color
+                } else {
+                    gui.style.disabled_text_color
+                },
+            );
+            y += LINE_HEIGHT;
+        }
+    }
+}
+
+fn ms(d: Duration) -> f32 {
+    d.as_secs_f32() * 1000.0
+}
+
+/// Measure the time until the guard is dropped.
+pub struct Scope<'a> {
+    resources: &'a Resources,
+    name: &'static str,
+    start: Instant,
+}
+
+impl<'a> Drop for Scope<'a> {
+    fn drop(&mut self) {
+        let duration = self.start.elapsed();
+        if let Some(mut profiler) = self.resources.fetch_mut::<Profiler>() {
+            profiler.depth = profiler.depth.saturating_sub(1);
+            profiler.record(self.name, self.start, duration, 0);
+        }
+    }
+}
+
+/// Measure the time until the guard is dropped. Scopes can be nested.
+pub fn scope<'a>(resources: &'a Resources, name: &'static str) -> Scope<'a> {
+    if let Some(mut profiler) = resources.fetch_mut::<Profiler>() {
+        profiler.depth += 1;
+    }
+    Scope {
+        resources,
+        name,
+        start: Instant::now(),
+    }
+}
+
+/// https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ChromeTrace {
+    trace_events: Vec<TraceEvent>,
+    display_time_unit: &'static str,
+}
+
+#[derive(Serialize)]
+struct TraceEvent {
+    name: &'static str,
+    /// "X" for a span, "C" for counters.
+    ph: &'static str,
+    /// Microseconds.
+    ts: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dur: Option<u128>,
+    pid: u32,
+    tid: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    args: Option<BTreeMap<&'static str, usize>>,
+}
+
+impl ChromeTrace {
+    fn new(frames: &[FrameStats]) -> Self {
+        let mut trace_events = vec![];
+        for frame in frames {
+            let frame_start = frame.start;
+            trace_events.push(TraceEvent {
+                name: "frame",
+                ph: "X",
+                ts: frame_start.as_micros(),
+                dur: Some(frame.duration.as_micros()),
+                pid: 1,
+                tid: 0,
+                args: None,
+            });
+            trace_events.extend(frame.spans.iter().map(|span| TraceEvent {
+                name: span.name,
+                ph: "X",
+                ts: span.start.as_micros(),
+                dur: Some(span.duration.as_micros()),
+                pid: 1,
+                tid: span.thread,
+                args: None,
+            }));
+
+            let mut counters = BTreeMap::new();
+            counters.insert("entities", frame.entities);
+            counters.insert("draw_calls", frame.draw_calls);
+            trace_events.push(TraceEvent {
+                name: "counters",
+                ph: "C",
+                ts: frame_start.as_micros(),
+                dur: None,
+                pid: 1,
+                tid: 0,
+                args: Some(counters),
+            });
+        }
+
+        Self {
+            trace_events,
+            display_time_unit: "ms",
+        }
+    }
+}
@@ -4,6 +4,15 @@ use rand::SeedableRng;
 #[derive(Debug, Copy, Clone)]
 pub struct Seed(pub(crate) [u8; 32]);
 
+impl Seed {
+    /// Seed that can be typed (e.g. in the console).
+    pub fn from_u64(seed: u64) -> Self {
+        let mut bytes = [0u8; 32];
+        bytes[..8].copy_from_slice(&seed.to_le_bytes());
+        Self(bytes)
+    }
+}
+
 pub struct RandomGenerator {
     rand: StdRng,
 }
@@ -1,21 +1,55 @@
-use crate::event::GameEvent;
+use crate::assets::preload::{Preload, PreloadStatus, SceneAssets};
+use crate::core::colors::RgbaColor;
+use crate::core::events::Events;
+use crate::core::lifetime::DespawnOnExit;
+use crate::event::{Delete, GameEvent};
 use crate::render::ui::gui::GuiContext;
 use crate::render::ui::Gui;
 use crate::resources::Resources;
-use hecs::World;
+use hecs::{Without, World};
 use std::time::Duration;
 
+/// Identifies a scene on the stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SceneId(u32);
+
+/// Scene that owns the entity. The entity is despawned when the scene is removed from the stack.
+///
+/// New entities are tagged at the end of the frame and after `on_create` (see
+/// `SceneStack::tag_entities`), so scenes do not have to add it themselves.
+#[derive(Debug, Clone, Copy)]
+pub struct SceneTag(pub SceneId);
+
+/// Entity that does not belong to any scene and survives scene changes (e.g. the camera).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Persistent;
+
+struct SceneEntry<I> {
+    id: SceneId,
+    scene: Box<dyn Scene<I>>,
+}
+
 /// The stack will keep track of the states in the game.
 /// The top of the stack will be used for the update loop. The states below
 /// are still kept in memory so to go back to a previous state, you just have
 /// to pop the stack.
+///
+/// All the scenes share the world. Entities are owned by the scene that spawned them (see
+/// `SceneTag`) and are despawned with it, so the world below an overlay scene is still rendered.
 pub struct SceneStack<I> {
-    states: Vec<Box<dyn Scene<I>>>,
+    states: Vec<SceneEntry<I>>,
+    next_id: u32,
+    /// Transition that is currently playing. The result is applied when the screen is covered.
+    transition: Option<ActiveTransition<I>>,
 }
 
 impl<I> Default for SceneStack<I> {
     fn default() -> Self {
-        Self { states: vec![] }
+        Self {
+            states: vec![],
+            next_id: 0,
+            transition: None,
+        }
     }
 }
 
@@ -25,9 +59,131 @@ pub enum SceneResult<I> {
     Pop,
     /// Remove all existing scenes and create the new one.
     ReplaceAll(Box<dyn Scene<I>>),
+    /// Play a transition and apply the result when the screen is covered. The assets of the
+    /// incoming scene are loaded during the transition. If they cannot be loaded, the result is
+    /// given back to the current scene (see `Scene::on_transition_cancelled`) and that scene is
+    /// revealed again.
+    WithTransition(Box<SceneResult<I>>, Transition),
     Noop,
 }
 
+impl<I> SceneResult<I> {
+    pub fn with_transition(self, transition: Transition) -> Self {
+        SceneResult::WithTransition(Box::new(self), transition)
+    }
+
+    /// The scene that will be created when the result is applied.
+    fn incoming_scene(&self) -> Option<&dyn Scene<I>> {
+        match self {
+            SceneResult::ReplaceScene(scene)
+            | SceneResult::Push(scene)
+            | SceneResult::ReplaceAll(scene) => Some(scene.as_ref()),
+            SceneResult::WithTransition(res, _) => res.incoming_scene(),
+            SceneResult::Pop | SceneResult::Noop => None,
+        }
+    }
+
+    /// Take back the scene that would have been created when the result is applied.
+    pub fn into_incoming_scene(self) -> Option<Box<dyn Scene<I>>> {
+        match self {
+            SceneResult::ReplaceScene(scene)
+            | SceneResult::Push(scene)
+            | SceneResult::ReplaceAll(scene) => Some(scene),
+            SceneResult::WithTransition(res, _) => res.into_incoming_scene(),
+            SceneResult::Pop | SceneResult::Noop => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransitionKind {
+    /// The screen fades to the color.
+    Fade,
+    /// The color sweeps the screen from left to right.
+    Wipe,
+    /// The color appears by blocks in random order.
+    Dissolve,
+}
+
+/// Effect played when switching scenes. The first half of the duration covers the outgoing scene
+/// and the second half reveals the incoming one.
+#[derive(Debug, Clone, Copy)]
+pub struct Transition {
+    pub kind: TransitionKind,
+    pub duration: Duration,
+    pub color: RgbaColor,
+}
+
+impl Transition {
+    pub fn new(kind: TransitionKind, duration: Duration) -> Self {
+        Self {
+            kind,
+            duration,
+            color: RgbaColor::new(0, 0, 0, 255),
+        }
+    }
+
+    pub fn fade(duration: Duration) -> Self {
+        Self::new(TransitionKind::Fade, duration)
+    }
+
+    pub fn wipe(duration: Duration) -> Self {
+        Self::new(TransitionKind::Wipe, duration)
+    }
+
+    pub fn dissolve(duration: Duration) -> Self {
+        Self::new(TransitionKind::Dissolve, duration)
+    }
+
+    pub fn with_color(mut self, color: RgbaColor) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+/// What the renderer needs to draw the transition above the scene.
+#[derive(Debug, Clone, Copy)]
+pub struct TransitionOverlay {
+    pub kind: TransitionKind,
+    pub color: RgbaColor,
+    /// 0 when the scene is fully visible, 1 when it is fully covered.
+    pub coverage: f32,
+    /// True when the incoming scene is being revealed.
+    pub revealing: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TransitionPhase {
+    Covering,
+    /// The screen is covered until the assets of the incoming scene are loaded.
+    Loading,
+    Revealing,
+}
+
+struct ActiveTransition<I> {
+    transition: Transition,
+    phase: TransitionPhase,
+    elapsed: Duration,
+    /// Applied at the end of the loading phase.
+    pending: Option<SceneResult<I>>,
+    preload: Preload,
+}
+
+impl<I> ActiveTransition<I> {
+    fn half(&self) -> Duration {
+        self.transition.duration / 2
+    }
+
+    fn progress(&self) -> f32 {
+        let half = self.half().as_secs_f32();
+        if half > 0.0 {
+            (self.elapsed.as_secs_f32() / half).min(1.0)
+        } else {
+            1.0
+        }
+    }
+}
+
 impl<I> SceneStack<I> {
     pub fn apply_result(
         &mut self,
@@ -35,25 +191,129 @@ impl<I> SceneStack<I> {
         world: &mut hecs::World,
         resources: &mut Resources,
     ) {
+        if self.transition.is_some() {
+            if !matches!(res, SceneResult::Noop) {
+                warn!("A transition is playing, scene result is ignored");
+            }
+            return;
+        }
+
         match res {
             SceneResult::ReplaceScene(state) => self.replace(state, world, resources),
             SceneResult::Push(state) => self.push(state, world, resources),
             SceneResult::Pop => {
-                self.pop(world);
+                self.pop(world, resources);
             }
             SceneResult::ReplaceAll(state) => {
                 while !self.states.is_empty() {
-                    self.pop(world);
+                    self.pop(world, resources);
                 }
                 self.push(state, world, resources);
             }
+            SceneResult::WithTransition(res, transition) => {
+                let assets = res
+                    .incoming_scene()
+                    .map(|scene| scene.assets())
+                    .unwrap_or_default();
+                // Nothing to cover when there is no scene yet.
+                let phase = if self.states.is_empty() {
+                    TransitionPhase::Loading
+                } else {
+                    TransitionPhase::Covering
+                };
+                self.transition = Some(ActiveTransition {
+                    transition,
+                    phase,
+                    elapsed: Duration::default(),
+                    pending: Some(*res),
+                    preload: Preload::new(assets),
+                });
+            }
             SceneResult::Noop => (),
         }
     }
 
+    /// Advance the current transition. The scene result is applied once the screen is covered and
+    /// the assets of the incoming scene are loaded.
+    pub fn update_transition(
+        &mut self,
+        dt: Duration,
+        world: &mut hecs::World,
+        resources: &mut Resources,
+    ) {
+        let transition = match self.transition.as_mut() {
+            Some(transition) => transition,
+            None => return,
+        };
+
+        let status = transition.preload.poll(resources);
+        match transition.phase {
+            TransitionPhase::Covering => {
+                transition.elapsed += dt;
+                if transition.elapsed >= transition.half() {
+                    transition.phase = TransitionPhase::Loading;
+                }
+            }
+            TransitionPhase::Loading => {
+                if let PreloadStatus::Loading(_) = status {
+                    return;
+                }
+                transition.phase = TransitionPhase::Revealing;
+                transition.elapsed = Duration::default();
+                let pending = transition.pending.take();
+                // Go back to the outgoing scene when the next one cannot be loaded. The first
+                // scene is created anyway as there is nothing to go back to.
+                if let PreloadStatus::Failed = status {
+                    if let Some(current) = self.states.last_mut() {
+                        error!("Cannot load the assets of the next scene, stay in the current one");
+                        if let Some(res) = pending {
+                            current.scene.on_transition_cancelled(res, world, resources);
+                        }
+                        return;
+                    }
+                    error!("Some assets of the first scene could not be loaded");
+                }
+                if let Some(res) = pending {
+                    let transition = self.transition.take();
+                    self.apply_result(res, world, resources);
+                    self.transition = transition;
+                }
+            }
+            TransitionPhase::Revealing => {
+                transition.elapsed += dt;
+                if transition.elapsed >= transition.half() {
+                    self.transition = None;
+                }
+            }
+        }
+    }
+
+    /// True while a transition is playing. The current scene is not updated during that time.
+    pub fn is_transitioning(&self) -> bool {
+        self.transition.is_some()
+    }
+
+    /// Overlay to draw above the current scene, if a transition is playing.
+    pub fn transition_overlay(&self) -> Option<TransitionOverlay> {
+        self.transition.as_ref().map(|transition| {
+            let coverage = match transition.phase {
+                TransitionPhase::Covering => transition.progress(),
+                TransitionPhase::Loading => 1.0,
+                TransitionPhase::Revealing => 1.0 - transition.progress(),
+            };
+            TransitionOverlay {
+                kind: transition.transition.kind,
+                color: transition.transition.color,
+                coverage,
+                revealing: transition.phase == TransitionPhase::Revealing,
+            }
+        })
+    }
+
     /// Add a state to the game. Will be used for updating.
     ///
-    /// The callback on_enter will be executed for the new state.
+    /// The callback on_exit will be executed for the previous state. Its entities with
+    /// `DespawnOnExit` are deleted, unless the new state is an overlay (e.g. pause).
     pub fn push(
         &mut self,
         state: Box<dyn Scene<I>>,
@@ -61,21 +321,25 @@ impl<I> SceneStack<I> {
         resources: &mut Resources,
     ) {
         if let Some(current) = self.states.last_mut() {
-            current.on_exit();
+            current.scene.on_exit(world, resources);
         }
-
-        self.states.push(state);
-        if let Some(current) = self.states.last_mut() {
-            current.on_create(world, resources);
+        if !state.is_overlay() {
+            self.despawn_on_exit(world, resources);
         }
+
+        self.create(state, world, resources);
     }
 
-    /// Remove the current state and execute its exit callback.
-    pub fn pop(&mut self, world: &mut hecs::World) -> Option<Box<dyn Scene<I>>> {
-        if let Some(mut s) = self.states.pop() {
-            s.on_destroy(world);
-            if let Some(current) = self.states.last() {
-                current.on_enter();
+    /// Remove the current state and execute its exit callback. The entities of the state are
+    /// despawned.
+    pub fn pop(
+        &mut self,
+        world: &mut hecs::World,
+        resources: &mut Resources,
+    ) -> Option<Box<dyn Scene<I>>> {
+        if let Some(s) = self.destroy(world) {
+            if let Some(current) = self.states.last_mut() {
+                current.scene.on_enter(world, resources);
             }
             Some(s)
         } else {
@@ -90,44 +354,147 @@ impl<I> SceneStack<I> {
         world: &mut hecs::World,
         resources: &mut Resources,
     ) {
-        if let Some(mut s) = self.states.pop() {
-            s.on_destroy(world);
-        }
-        self.states.push(state);
+        self.destroy(world);
+        self.create(state, world, resources);
+    }
+
+    fn create(
+        &mut self,
+        scene: Box<dyn Scene<I>>,
+        world: &mut hecs::World,
+        resources: &mut Resources,
+    ) {
+        let id = SceneId(self.next_id);
+        self.next_id += 1;
+        self.states.push(SceneEntry { id, scene });
         if let Some(current) = self.states.last_mut() {
-            current.on_create(world, resources);
+            current.scene.on_create(world, resources);
+        }
+        self.tag_entities(world);
+    }
+
+    fn destroy(&mut self, world: &mut hecs::World) -> Option<Box<dyn Scene<I>>> {
+        let mut entry = self.states.pop()?;
+        entry.scene.on_destroy(world);
+
+        let owned: Vec<_> = world
+            .query::<&SceneTag>()
+            .iter()
+            .filter(|(_, tag)| tag.0 == entry.id)
+            .map(|(e, _)| e)
+            .collect();
+        debug!("Despawn {} entities of scene {:?}", owned.len(), entry.id);
+        for e in owned {
+            // Children might have been despawned by the scene already.
+            let _ = world.despawn(e);
+        }
+        Some(entry.scene)
+    }
+
+    /// Delete the entities with `DespawnOnExit` that belong to the current scene.
+    fn despawn_on_exit(&self, world: &mut hecs::World, resources: &Resources) {
+        let current = match self.states.last() {
+            Some(current) => current.id,
+            None => return,
+        };
+        self.tag_entities(world);
+        let to_delete: Vec<_> = world
+            .query::<(&SceneTag, &DespawnOnExit)>()
+            .iter()
+            .filter(|(_, (tag, _))| tag.0 == current)
+            .map(|(e, _)| Delete(e))
+            .collect();
+        if let Some(mut chan) = resources.fetch_mut::<Events<Delete>>() {
+            chan.send_all(to_delete);
+        }
+    }
+
+    /// Scene that owns the entities spawned now: the top scene, or the first scene below the
+    /// overlays.
+    fn owner(&self) -> Option<SceneId> {
+        self.states
+            .iter()
+            .rev()
+            .find(|entry| !entry.scene.is_overlay())
+            .or_else(|| self.states.last())
+            .map(|entry| entry.id)
+    }
+
+    /// Tag the new entities with the scene that owns them. Entities that are `Persistent` or
+    /// already tagged are left alone.
+    pub fn tag_entities(&self, world: &mut hecs::World) {
+        let owner = match self.owner() {
+            Some(owner) => owner,
+            None => return,
+        };
+        let untagged: Vec<_> = world
+            .query::<Without<SceneTag, Without<Persistent, ()>>>()
+            .iter()
+            .map(|(e, _)| e)
+            .collect();
+        for e in untagged {
+            if let Err(e) = world.insert_one(e, SceneTag(owner)) {
+                error!("Cannot tag entity with its scene = {:?}", e);
+            }
         }
     }
 
     /// Get the current state as a mut reference.
     #[allow(clippy::borrowed_box)]
     pub fn current_mut(&mut self) -> Option<&mut Box<dyn Scene<I>>> {
-        self.states.last_mut()
+        self.states.last_mut().map(|entry| &mut entry.scene)
     }
 }
 
 pub trait Scene<I> {
+    /// Assets to load before the scene is created. They are loaded during the transition to the
+    /// scene (see `SceneResult::WithTransition`).
+    fn assets(&self) -> SceneAssets {
+        SceneAssets::default()
+    }
+
     /// WIll be called when the state is added to the state stack.
     fn on_create(&mut self, _world: &mut hecs::World, _resources: &mut Resources) {
         info!("Create state");
     }
 
-    /// Will be called when the state is removed from the state stack.
+    /// Overlay scenes (e.g. pause menu) are drawn above the scene below and do not own the
+    /// entities spawned while they are on top. These belong to the scene below.
+    fn is_overlay(&self) -> bool {
+        false
+    }
+
+    /// Will be called when the state is removed from the state stack. The entities owned by the
+    /// scene are despawned afterwards.
     fn on_destroy(&mut self, _world: &mut hecs::World) {
         info!("Destroy state");
     }
 
     /// Will be called when the state becomes active. This is called
-    /// on stack.pop
+    /// on stack.pop, and by default when the transition to another scene is cancelled (reset the
+    /// request to leave the scene here).
     ///
     /// Careful, this is not call on stack.push. Use the on_create callback instead.
-    fn on_enter(&self) {
+    fn on_enter(&mut self, _world: &mut hecs::World, _resources: &mut Resources) {
         info!("Enter state");
     }
 
+    /// Will be called when the transition requested by this scene is cancelled because the assets
+    /// of the next scene cannot be loaded. `result` is the result that was not applied, so that
+    /// the scene can take back what it gave away. The scene should not request the same
+    /// transition again right away, as it would most likely fail again.
+    fn on_transition_cancelled(
+        &mut self,
+        _result: SceneResult<I>,
+        world: &mut hecs::World,
+        resources: &mut Resources,
+    ) {
+        self.on_enter(world, resources);
+    }
+
     /// Will be called when the state becomes inactive. This is called on
     /// stack.push
-    fn on_exit(&self) {
+    fn on_exit(&mut self, _world: &mut hecs::World, _resources: &mut Resources) {
         info!("Exit state");
     }
 
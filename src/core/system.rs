@@ -0,0 +1,682 @@
+//! Systems update the world every frame. Each system declares the resources and components it
+//! reads and writes. When the scheduler is built, it checks that the systems can run together
+//! (resources exist, no system borrows the same resource twice, ordering is possible...) so that
+//! problems are found when the scene starts instead of in the middle of a frame.
+//!
+//! Systems are grouped in named stages that run in the order they were added. Inside a stage,
+//! systems run in the order they were added unless an ordering constraint says otherwise.
+//!
+//! Systems that only work on components (see `ParallelSystem`) can run on several threads at the
+//! same time when the scheduler is parallel. The others run on the main thread as they use the
+//! resources, which are not thread-safe.
+//!
+//! ```ignore
+//! let scheduler = SchedulerBuilder::new()
+//!     .with_stage("logic")
+//!     .with_stage("physics")
+//!     .with_system("logic", PlayerSystem)
+//!     .with_system("physics", PhysicSystem::default())
+//!     .with_system("logic", CameraSystem::new(resources, bounds))
+//!     .with_ordering("player", "camera")
+//!     .build(resources)?;
+//! scheduler.run(world, resources, dt);
+//! ```
+use crate::core::profiler::{self, Profiler};
+use crate::resources::Resources;
+use downcast_rs::{impl_downcast, Downcast};
+use hecs::World;
+use std::any::TypeId;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy)]
+struct TypeInfo {
+    id: TypeId,
+    name: &'static str,
+}
+
+impl TypeInfo {
+    fn of<T: 'static>() -> Self {
+        Self {
+            id: TypeId::of::<T>(),
+            name: std::any::type_name::<T>(),
+        }
+    }
+}
+
+impl PartialEq for TypeInfo {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+/// Resources and components used by a system.
+#[derive(Debug, Clone, Default)]
+pub struct Access {
+    resource_reads: Vec<TypeInfo>,
+    resource_writes: Vec<TypeInfo>,
+    component_reads: Vec<TypeInfo>,
+    component_writes: Vec<TypeInfo>,
+    /// Spawn or despawn entities, or add and remove components.
+    structural: bool,
+}
+
+impl Access {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The system fetches this resource with `fetch`.
+    pub fn read<T: 'static>(mut self) -> Self {
+        self.resource_reads.push(TypeInfo::of::<T>());
+        self
+    }
+
+    /// The system fetches this resource with `fetch_mut`.
+    pub fn write<T: 'static>(mut self) -> Self {
+        self.resource_writes.push(TypeInfo::of::<T>());
+        self
+    }
+
+    pub fn read_component<T: 'static>(mut self) -> Self {
+        self.component_reads.push(TypeInfo::of::<T>());
+        self
+    }
+
+    pub fn write_component<T: 'static>(mut self) -> Self {
+        self.component_writes.push(TypeInfo::of::<T>());
+        self
+    }
+
+    /// The system spawns or despawns entities, or adds and removes components.
+    pub fn structural(mut self) -> Self {
+        self.structural = true;
+        self
+    }
+
+    fn uses_resources(&self) -> bool {
+        !self.resource_reads.is_empty() || !self.resource_writes.is_empty()
+    }
+
+    /// Name of the resources and components that are used by both and written by at least one.
+    fn conflicts_with(&self, other: &Access) -> Vec<&'static str> {
+        let mut conflicts = overlap(
+            (&self.resource_reads, &self.resource_writes),
+            (&other.resource_reads, &other.resource_writes),
+        );
+        conflicts.extend(overlap(
+            (&self.component_reads, &self.component_writes),
+            (&other.component_reads, &other.component_writes),
+        ));
+        if self.structural || other.structural {
+            conflicts.push("World");
+        }
+        conflicts
+    }
+}
+
+fn overlap(
+    (reads, writes): (&[TypeInfo], &[TypeInfo]),
+    (other_reads, other_writes): (&[TypeInfo], &[TypeInfo]),
+) -> Vec<&'static str> {
+    let mut conflicts: Vec<&'static str> = writes
+        .iter()
+        .filter(|ty| other_reads.contains(ty) || other_writes.contains(ty))
+        .map(|ty| ty.name)
+        .collect();
+    conflicts.extend(
+        other_writes
+            .iter()
+            .filter(|ty| reads.contains(ty) && !writes.contains(ty))
+            .map(|ty| ty.name),
+    );
+    conflicts
+}
+
+pub trait System: Downcast {
+    /// Unique name of the system. Used by the ordering constraints and in errors.
+    fn name(&self) -> &'static str;
+
+    /// Resources and components that are used by `run`.
+    fn access(&self) -> Access;
+
+    fn run(&mut self, world: &mut World, resources: &Resources, dt: Duration);
+
+    /// Return Some if the system can run on another thread. Then `run` is not called when the
+    /// scheduler is parallel.
+    fn as_parallel(&mut self) -> Option<&mut dyn ParallelSystem> {
+        None
+    }
+}
+impl_downcast!(System);
+
+/// System that does not use the resources nor change the structure of the world. Components are
+/// borrowed as declared in `System::access`.
+pub trait ParallelSystem: Send {
+    fn run_parallel(&mut self, world: &World, dt: Duration);
+}
+
+#[derive(Debug, Error)]
+pub enum SchedulerError {
+    #[error("Stage {stage} of system {system} does not exist")]
+    UnknownStage { stage: String, system: String },
+
+    #[error("System {0} is added twice")]
+    DuplicateSystem(String),
+
+    #[error("System {0} in ordering constraint does not exist")]
+    UnknownSystem(String),
+
+    #[error("{first} should run before {second} but its stage runs after")]
+    InvalidOrdering { first: String, second: String },
+
+    #[error("Ordering constraints have a cycle between {0:?}")]
+    Cycle(Vec<String>),
+
+    #[error("System {system} fetches {resource} mutably and immutably")]
+    ReadWrite {
+        system: String,
+        resource: &'static str,
+    },
+
+    #[error("Resource {resource} used by {system} does not exist")]
+    MissingResource {
+        system: String,
+        resource: &'static str,
+    },
+
+    #[error("System {0} uses resources or changes the world so it cannot run in parallel")]
+    NotParallel(String),
+}
+
+struct SystemEntry {
+    stage: usize,
+    system: Box<dyn System>,
+}
+
+/// Create a `Scheduler`.
+pub struct SchedulerBuilder {
+    stages: Vec<String>,
+    systems: Vec<(String, Box<dyn System>)>,
+    /// (first, second): first runs before second.
+    orderings: Vec<(String, String)>,
+    parallel: bool,
+}
+
+impl Default for SchedulerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SchedulerBuilder {
+    pub fn new() -> Self {
+        Self {
+            stages: vec![],
+            systems: vec![],
+            orderings: vec![],
+            parallel: false,
+        }
+    }
+
+    /// Stages run in the order they are added.
+    pub fn with_stage<S: Into<String>>(mut self, name: S) -> Self {
+        self.stages.push(name.into());
+        self
+    }
+
+    pub fn with_system<S: System>(mut self, stage: &str, system: S) -> Self {
+        self.systems.push((stage.to_string(), Box::new(system)));
+        self
+    }
+
+    /// The system `first` will run before the system `second`.
+    pub fn with_ordering(mut self, first: &str, second: &str) -> Self {
+        self.orderings.push((first.to_string(), second.to_string()));
+        self
+    }
+
+    /// Run the parallel systems on several threads.
+    pub fn with_parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    pub fn build(self, resources: &Resources) -> Result<Scheduler, SchedulerError> {
+        let mut entries: Vec<SystemEntry> = vec![];
+        for (stage, mut system) in self.systems {
+            let name = system.name();
+            let stage = self
+                .stages
+                .iter()
+                .position(|s| *s == stage)
+                .ok_or_else(|| SchedulerError::UnknownStage {
+                    stage,
+                    system: name.to_string(),
+                })?;
+            if entries.iter().any(|e| e.system.name() == name) {
+                return Err(SchedulerError::DuplicateSystem(name.to_string()));
+            }
+
+            let access = system.access();
+            if let Some(resource) = access
+                .resource_writes
+                .iter()
+                .find(|ty| access.resource_reads.contains(ty))
+            {
+                return Err(SchedulerError::ReadWrite {
+                    system: name.to_string(),
+                    resource: resource.name,
+                });
+            }
+            if let Some(resource) = access
+                .resource_reads
+                .iter()
+                .chain(access.resource_writes.iter())
+                .find(|ty| !resources.contains_type_id(ty.id))
+            {
+                return Err(SchedulerError::MissingResource {
+                    system: name.to_string(),
+                    resource: resource.name,
+                });
+            }
+            if system.as_parallel().is_some() && (access.uses_resources() || access.structural) {
+                return Err(SchedulerError::NotParallel(name.to_string()));
+            }
+
+            entries.push(SystemEntry { stage, system });
+        }
+
+        // (first, second) indices in entries.
+        let mut orderings = vec![];
+        for (first, second) in &self.orderings {
+            let find = |name: &str| {
+                entries
+                    .iter()
+                    .position(|e| e.system.name() == name)
+                    .ok_or_else(|| SchedulerError::UnknownSystem(name.to_string()))
+            };
+            let (first_idx, second_idx) = (find(first)?, find(second)?);
+            if entries[first_idx].stage > entries[second_idx].stage {
+                return Err(SchedulerError::InvalidOrdering {
+                    first: first.clone(),
+                    second: second.clone(),
+                });
+            }
+            orderings.push((first_idx, second_idx));
+        }
+
+        let order = sort_systems(&entries, &orderings)?;
+
+        // Group the systems by stage, in execution order. When parallel, consecutive parallel
+        // systems that do not conflict share a batch.
+        let accesses: Vec<Access> = entries.iter().map(|e| e.system.access()).collect();
+        let parallel = self.parallel;
+        let is_parallel: Vec<bool> = entries
+            .iter_mut()
+            .map(|e| parallel && e.system.as_parallel().is_some())
+            .collect();
+        let mut batches: Vec<Vec<Vec<usize>>> = vec![vec![]; self.stages.len()];
+        for idx in order {
+            let stage_batches = &mut batches[entries[idx].stage];
+            let can_join_batch = is_parallel[idx]
+                && stage_batches.last().is_some_and(|batch| {
+                    batch.iter().all(|&other| {
+                        is_parallel[other]
+                            && accesses[idx].conflicts_with(&accesses[other]).is_empty()
+                            && !orderings.contains(&(idx, other))
+                            && !orderings.contains(&(other, idx))
+                    })
+                });
+
+            if can_join_batch {
+                stage_batches.last_mut().unwrap().push(idx);
+            } else {
+                stage_batches.push(vec![idx]);
+            }
+        }
+
+        let mut systems: Vec<Option<Box<dyn System>>> =
+            entries.into_iter().map(|e| Some(e.system)).collect();
+        let stages = self
+            .stages
+            .into_iter()
+            .zip(batches)
+            .map(|(name, batches)| Stage {
+                name,
+                batches: batches
+                    .into_iter()
+                    .map(|batch| {
+                        batch
+                            .into_iter()
+                            .map(|idx| systems[idx].take().expect("System is only scheduled once"))
+                            .collect()
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Ok(Scheduler {
+            stages,
+            parallel: self.parallel,
+        })
+    }
+}
+
+/// Sort the systems by stage, then by ordering constraints. Systems without constraints keep the
+/// order in which they were added.
+fn sort_systems(
+    entries: &[SystemEntry],
+    orderings: &[(usize, usize)],
+) -> Result<Vec<usize>, SchedulerError> {
+    let mut remaining: Vec<usize> = (0..entries.len()).collect();
+    remaining.sort_by_key(|&idx| entries[idx].stage);
+
+    let mut order = Vec::with_capacity(entries.len());
+    while !remaining.is_empty() {
+        // first system whose predecessors have all been scheduled.
+        let next = remaining.iter().position(|&idx| {
+            orderings
+                .iter()
+                .all(|&(first, second)| second != idx || order.contains(&first))
+        });
+        match next {
+            Some(pos) => order.push(remaining.remove(pos)),
+            None => {
+                return Err(SchedulerError::Cycle(
+                    remaining
+                        .iter()
+                        .map(|&idx| entries[idx].system.name().to_string())
+                        .collect(),
+                ))
+            }
+        }
+    }
+
+    Ok(order)
+}
+
+struct Stage {
+    name: String,
+    /// Systems in a batch can run at the same time.
+    batches: Vec<Vec<Box<dyn System>>>,
+}
+
+/// Run the systems every frame. Create it with `SchedulerBuilder`.
+pub struct Scheduler {
+    stages: Vec<Stage>,
+    parallel: bool,
+}
+
+impl Scheduler {
+    pub fn run(&mut self, world: &mut World, resources: &Resources, dt: Duration) {
+        for stage in &mut self.stages {
+            log::trace!("Run stage {}", stage.name);
+            for batch in &mut stage.batches {
+                if self.parallel && batch.len() > 1 {
+                    let world = &*world;
+                    let systems: Vec<(&'static str, &mut dyn ParallelSystem)> = batch
+                        .iter_mut()
+                        .filter_map(|system| {
+                            let name = system.name();
+                            system.as_parallel().map(|system| (name, system))
+                        })
+                        .collect();
+                    // Each system is timed on its thread, then recorded on the main thread.
+                    let timings: Vec<(&'static str, Instant, Duration)> =
+                        std::thread::scope(|scope| {
+                            let handles: Vec<_> = systems
+                                .into_iter()
+                                .map(|(name, system)| {
+                                    scope.spawn(move || {
+                                        let start = Instant::now();
+                                        system.run_parallel(world, dt);
+                                        (name, start, start.elapsed())
+                                    })
+                                })
+                                .collect();
+                            handles
+                                .into_iter()
+                                .map(|handle| handle.join().expect("System should not panic"))
+                                .collect()
+                        });
+                    if let Some(mut profiler) = resources.fetch_mut::<Profiler>() {
+                        for (thread, (name, start, duration)) in timings.into_iter().enumerate() {
+                            profiler.record(name, start, duration, thread + 1);
+                        }
+                    }
+                } else {
+                    for system in batch.iter_mut() {
+                        let _scope = profiler::scope(resources, system.name());
+                        match system.as_parallel() {
+                            Some(system) if self.parallel => system.run_parallel(world, dt),
+                            _ => system.run(world, resources, dt),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Get a system to change its state (e.g. camera bounds when the stage changes).
+    pub fn system_mut<T: System>(&mut self) -> Option<&mut T> {
+        self.stages
+            .iter_mut()
+            .flat_map(|stage| stage.batches.iter_mut())
+            .flat_map(|batch| batch.iter_mut())
+            .find_map(|system| system.downcast_mut::<T>())
+    }
+
+    /// Name of the systems of each stage, in execution order.
+    pub fn stages(&self) -> Vec<(&str, Vec<&'static str>)> {
+        self.stages
+            .iter()
+            .map(|stage| {
+                (
+                    stage.name.as_str(),
+                    stage
+                        .batches
+                        .iter()
+                        .flat_map(|batch| batch.iter().map(|system| system.name()))
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Position(u32);
+    struct Velocity(u32);
+    struct Config;
+
+    /// Adds one to a component of every entity.
+    struct Increment<T> {
+        name: &'static str,
+        parallel: bool,
+        get: fn(&mut T) -> &mut u32,
+    }
+
+    impl<T: Send + Sync + 'static> Increment<T> {
+        fn new(name: &'static str, get: fn(&mut T) -> &mut u32) -> Self {
+            Self {
+                name,
+                parallel: true,
+                get,
+            }
+        }
+
+        fn sequential(mut self) -> Self {
+            self.parallel = false;
+            self
+        }
+
+        fn increment(&self, world: &World) {
+            for (_, value) in world.query::<&mut T>().iter() {
+                *(self.get)(value) += 1;
+            }
+        }
+    }
+
+    impl<T: Send + Sync + 'static> System for Increment<T> {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn access(&self) -> Access {
+            Access::new().write_component::<T>()
+        }
+
+        fn run(&mut self, world: &mut World, _resources: &Resources, _dt: Duration) {
+            self.increment(world);
+        }
+
+        fn as_parallel(&mut self) -> Option<&mut dyn ParallelSystem> {
+            if self.parallel {
+                Some(self)
+            } else {
+                None
+            }
+        }
+    }
+
+    impl<T: Send + Sync + 'static> ParallelSystem for Increment<T> {
+        fn run_parallel(&mut self, world: &World, _dt: Duration) {
+            self.increment(world);
+        }
+    }
+
+    /// Declares the `Config` resource as read and written.
+    struct ReadWriteConfig;
+
+    impl System for ReadWriteConfig {
+        fn name(&self) -> &'static str {
+            "read_write"
+        }
+
+        fn access(&self) -> Access {
+            Access::new().read::<Config>().write::<Config>()
+        }
+
+        fn run(&mut self, _world: &mut World, _resources: &Resources, _dt: Duration) {}
+    }
+
+    fn position() -> Increment<Position> {
+        Increment::new("position", |p: &mut Position| &mut p.0)
+    }
+
+    fn velocity() -> Increment<Velocity> {
+        Increment::new("velocity", |v: &mut Velocity| &mut v.0)
+    }
+
+    fn batch_sizes(scheduler: &Scheduler) -> Vec<Vec<usize>> {
+        scheduler
+            .stages
+            .iter()
+            .map(|stage| stage.batches.iter().map(Vec::len).collect())
+            .collect()
+    }
+
+    #[test]
+    fn parallel_systems_share_a_batch() {
+        let resources = Resources::new();
+        let mut scheduler = SchedulerBuilder::new()
+            .with_stage("logic")
+            .with_system("logic", position())
+            .with_system("logic", velocity())
+            .with_parallel(true)
+            .build(&resources)
+            .unwrap();
+        assert_eq!(batch_sizes(&scheduler), vec![vec![2]]);
+
+        let mut world = World::new();
+        let e = world.spawn((Position(0), Velocity(10)));
+        scheduler.run(&mut world, &resources, Duration::from_millis(16));
+        assert_eq!(world.get::<Position>(e).unwrap().0, 1);
+        assert_eq!(world.get::<Velocity>(e).unwrap().0, 11);
+    }
+
+    #[test]
+    fn conflicting_systems_do_not_share_a_batch() {
+        let resources = Resources::new();
+        let other_position = Increment::new("other_position", |p: &mut Position| &mut p.0);
+        let scheduler = SchedulerBuilder::new()
+            .with_stage("logic")
+            .with_system("logic", position())
+            .with_system("logic", other_position)
+            .with_system("logic", velocity().sequential())
+            .with_parallel(true)
+            .build(&resources)
+            .unwrap();
+        assert_eq!(batch_sizes(&scheduler), vec![vec![1, 1, 1]]);
+    }
+
+    #[test]
+    fn ordered_systems_do_not_share_a_batch() {
+        let resources = Resources::new();
+        let scheduler = SchedulerBuilder::new()
+            .with_stage("logic")
+            .with_system("logic", position())
+            .with_system("logic", velocity())
+            .with_ordering("velocity", "position")
+            .with_parallel(true)
+            .build(&resources)
+            .unwrap();
+        assert_eq!(batch_sizes(&scheduler), vec![vec![1, 1]]);
+        assert_eq!(
+            scheduler.stages(),
+            vec![("logic", vec!["velocity", "position"])]
+        );
+    }
+
+    #[test]
+    fn read_write_resource_is_an_error() {
+        let mut resources = Resources::new();
+        resources.insert(Config);
+        let res = SchedulerBuilder::new()
+            .with_stage("logic")
+            .with_system("logic", ReadWriteConfig)
+            .build(&resources);
+        assert!(matches!(
+            res,
+            Err(SchedulerError::ReadWrite { system, .. }) if system == "read_write"
+        ));
+    }
+
+    #[test]
+    fn ordering_cycle_is_an_error() {
+        let res = SchedulerBuilder::new()
+            .with_stage("logic")
+            .with_system("logic", position())
+            .with_system("logic", velocity())
+            .with_ordering("position", "velocity")
+            .with_ordering("velocity", "position")
+            .build(&Resources::new());
+        match res {
+            Err(SchedulerError::Cycle(mut systems)) => {
+                systems.sort();
+                assert_eq!(systems, vec!["position", "velocity"]);
+            }
+            _ => panic!("Expected a cycle"),
+        }
+    }
+
+    #[test]
+    fn ordering_against_stages_is_an_error() {
+        let res = SchedulerBuilder::new()
+            .with_stage("logic")
+            .with_stage("physics")
+            .with_system("logic", position())
+            .with_system("physics", velocity())
+            .with_ordering("velocity", "position")
+            .build(&Resources::new());
+        assert!(matches!(
+            res,
+            Err(SchedulerError::InvalidOrdering { first, second })
+                if first == "velocity" && second == "position"
+        ));
+    }
+}
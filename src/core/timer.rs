@@ -39,10 +39,19 @@ impl Timer {
         self.elapsed = 0.0;
     }
 
+    pub fn deadline(&self) -> f32 {
+        self.deadline
+    }
+
     pub fn set_deadline(&mut self, deadline: f32) {
         self.deadline = deadline;
     }
 
+    /// Reach the deadline now.
+    pub fn finish(&mut self) {
+        self.elapsed = self.deadline;
+    }
+
     /// Update the timer
     pub fn tick(&mut self, dt: Duration) {
         self.elapsed += dt.as_secs_f32();
@@ -53,3 +62,19 @@ impl Timer {
         self.elapsed >= self.deadline
     }
 }
+
+/// Multiplier applied to the frame duration given to the scenes. 1 is normal speed.
+#[derive(Debug, Copy, Clone)]
+pub struct TimeScale(pub f32);
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+impl TimeScale {
+    pub fn scale(&self, dt: Duration) -> Duration {
+        dt.mul_f32(self.0.max(0.0))
+    }
+}
@@ -1,20 +1,210 @@
+//! Screen dimensions. The gameplay and the UI work with a virtual resolution that is mapped to
+//! the real framebuffer according to a `ScaleMode`. All conversions between window coordinates
+//! (what GLFW gives for the cursor) and the virtual screen should go through `WindowDim`.
+
+use serde_derive::{Deserialize, Serialize};
+
+/// How the virtual resolution is mapped to the framebuffer.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ScaleMode {
+    /// Keep the aspect ratio of the virtual resolution. Black bars fill the remaining space.
+    #[default]
+    Letterbox,
+    /// Stretch the virtual resolution to the whole framebuffer.
+    Stretch,
+    /// The virtual resolution follows the window size. A bigger window will show more of the
+    /// world.
+    Expand,
+}
+
+/// How the window is displayed on the monitor.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    /// Exclusive fullscreen on the primary monitor.
+    Fullscreen,
+    /// Window without decorations that covers the primary monitor.
+    Borderless,
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct WindowDim {
+    /// Size of the virtual screen. This is what the gameplay and the UI see.
     pub width: u32,
     pub height: u32,
+
+    /// Virtual resolution requested by the game.
+    pub design_width: u32,
+    pub design_height: u32,
+
+    /// Size of the framebuffer in pixels.
+    pub framebuffer_width: u32,
+    pub framebuffer_height: u32,
+
+    /// Size of the window in screen coordinates. On HiDPI screens, this is smaller than the
+    /// framebuffer.
+    pub window_width: u32,
+    pub window_height: u32,
+
+    pub scale_mode: ScaleMode,
 }
 
 impl WindowDim {
     pub fn new(width: u32, height: u32) -> Self {
-        Self { width, height }
+        Self {
+            width,
+            height,
+            design_width: width,
+            design_height: height,
+            framebuffer_width: width,
+            framebuffer_height: height,
+            window_width: width,
+            window_height: height,
+            scale_mode: ScaleMode::default(),
+        }
     }
 
-    pub fn resize(&mut self, width: u32, height: u32) {
-        self.width = width;
-        self.height = height;
+    pub fn with_scale_mode(mut self, scale_mode: ScaleMode) -> Self {
+        self.scale_mode = scale_mode;
+        self.update_virtual_size();
+        self
+    }
+
+    /// Update the dimensions when the window has been resized.
+    ///
+    /// - framebuffer: size in pixels
+    /// - window: size in screen coordinates.
+    pub fn resize(&mut self, framebuffer: (u32, u32), window: (u32, u32)) {
+        self.framebuffer_width = framebuffer.0.max(1);
+        self.framebuffer_height = framebuffer.1.max(1);
+        self.window_width = window.0.max(1);
+        self.window_height = window.1.max(1);
+        self.update_virtual_size();
+    }
+
+    fn update_virtual_size(&mut self) {
+        match self.scale_mode {
+            ScaleMode::Letterbox | ScaleMode::Stretch => {
+                self.width = self.design_width;
+                self.height = self.design_height;
+            }
+            ScaleMode::Expand => {
+                self.width = self.window_width;
+                self.height = self.window_height;
+            }
+        }
     }
 
     pub fn to_vec2(&self) -> glam::Vec2 {
         glam::vec2(self.width as f32, self.height as f32)
     }
+
+    /// Number of framebuffer pixels per window screen coordinate.
+    pub fn content_scale(&self) -> glam::Vec2 {
+        glam::vec2(
+            self.framebuffer_width as f32 / self.window_width as f32,
+            self.framebuffer_height as f32 / self.window_height as f32,
+        )
+    }
+
+    /// Area of the framebuffer where the virtual screen is drawn, in pixels, as
+    /// (x, y, width, height). The origin is the bottom-left corner.
+    pub fn viewport(&self) -> (u32, u32, u32, u32) {
+        match self.scale_mode {
+            ScaleMode::Letterbox => {
+                let scale = (self.framebuffer_width as f32 / self.design_width as f32)
+                    .min(self.framebuffer_height as f32 / self.design_height as f32);
+                let w = (self.design_width as f32 * scale).round() as u32;
+                let h = (self.design_height as f32 * scale).round() as u32;
+                (
+                    (self.framebuffer_width - w.min(self.framebuffer_width)) / 2,
+                    (self.framebuffer_height - h.min(self.framebuffer_height)) / 2,
+                    w,
+                    h,
+                )
+            }
+            ScaleMode::Stretch | ScaleMode::Expand => {
+                (0, 0, self.framebuffer_width, self.framebuffer_height)
+            }
+        }
+    }
+
+    /// Convert a position in window coordinates (e.g. the mouse cursor) to the virtual screen.
+    /// Origin is the top-left corner for both.
+    pub fn window_to_screen(&self, pos: glam::Vec2) -> glam::Vec2 {
+        let pixels = pos * self.content_scale();
+        let (x, y, w, h) = self.viewport();
+        // viewport origin is bottom-left, window origin is top-left.
+        let top = self.framebuffer_height as f32 - (y + h) as f32;
+        glam::vec2(
+            (pixels.x() - x as f32) / w as f32 * self.width as f32,
+            (pixels.y() - top) / h as f32 * self.height as f32,
+        )
+    }
+
+    /// Convert a position on the virtual screen to normalized device coordinates (between -1
+    /// and 1, origin at the center, y going up).
+    pub fn screen_to_ndc(&self, pos: glam::Vec2) -> glam::Vec2 {
+        glam::vec2(
+            (pos.x() / self.width as f32) * 2.0 - 1.0,
+            ((self.height as f32 - pos.y()) / self.height as f32) * 2.0 - 1.0,
+        )
+    }
+}
+
+/// Switch the window to the given display mode. `windowed_pos` and `windowed_size` are the
+/// position and size of the window in windowed mode.
+pub fn set_display_mode(
+    window: &mut glfw::Window,
+    mode: DisplayMode,
+    windowed_pos: (i32, i32),
+    windowed_size: (u32, u32),
+) {
+    let mut glfw = window.glfw.clone();
+    match mode {
+        DisplayMode::Windowed => {
+            window.set_decorated(true);
+            window.set_monitor(
+                glfw::WindowMode::Windowed,
+                windowed_pos.0,
+                windowed_pos.1,
+                windowed_size.0,
+                windowed_size.1,
+                None,
+            );
+        }
+        DisplayMode::Fullscreen => glfw.with_primary_monitor_mut(|_, monitor| {
+            if let Some((monitor, vmode)) = monitor.and_then(|m| m.get_video_mode().map(|v| (m, v)))
+            {
+                window.set_monitor(
+                    glfw::WindowMode::FullScreen(monitor),
+                    0,
+                    0,
+                    vmode.width,
+                    vmode.height,
+                    Some(vmode.refresh_rate),
+                );
+            } else {
+                error!("Cannot go fullscreen, no primary monitor");
+            }
+        }),
+        DisplayMode::Borderless => glfw.with_primary_monitor_mut(|_, monitor| {
+            if let Some((monitor, vmode)) = monitor.and_then(|m| m.get_video_mode().map(|v| (m, v)))
+            {
+                let (x, y) = monitor.get_pos();
+                window.set_decorated(false);
+                window.set_monitor(
+                    glfw::WindowMode::Windowed,
+                    x,
+                    y,
+                    vmode.width,
+                    vmode.height,
+                    None,
+                );
+            } else {
+                error!("Cannot go borderless, no primary monitor");
+            }
+        }),
+    }
 }
@@ -1,10 +1,49 @@
+//! Events sent between the systems. Each type has its own channel (see `core::events`).
+use crate::core::audio::adaptive::AdaptiveMusic;
+use crate::core::audio::Sound;
+use crate::core::effect::ParticleEffect;
+use crate::core::events::register_event;
 use crate::gameplay::explosion::ExplosionDetails;
 use crate::gameplay::health::HitDetails;
+use crate::resources::Resources;
 
+/// Entity to remove from the world at the end of the frame.
+#[derive(Debug, Clone, Copy)]
+pub struct Delete(pub hecs::Entity);
+
+/// Entity that has been hit by a bullet or an explosion.
+#[derive(Debug, Clone)]
+pub struct Hit(pub hecs::Entity, pub HitDetails);
+
+/// Something exploded :D
+#[derive(Debug, Clone)]
+pub struct Explode(pub hecs::Entity, pub ExplosionDetails, pub glam::Vec2);
+
+/// Music and sounds. One channel so that the changes are applied in order.
+#[derive(Debug, Clone)]
+pub enum AudioEvent {
+    /// Play the background music.
+    PlayBackgroundMusic(String),
+
+    /// Play a music made of layers that follow the intensity of the game.
+    PlayAdaptiveMusic(AdaptiveMusic),
+
+    /// Intensity (between 0 and 1) of the game, used by the adaptive music.
+    MusicIntensity(f32),
+
+    /// Play some sound
+    PlaySound(Sound),
+
+    /// Change the volume (0-100) of an audio bus.
+    SetBusVolume(String, u32),
+
+    /// Mute or unmute an audio bus.
+    MuteBus(String, bool),
+}
+
+/// Progress of the game. These events are given to the current scene.
 #[derive(Debug, Clone)]
 pub enum GameEvent {
-    Delete(hecs::Entity),
-    Hit(hecs::Entity, HitDetails),
     GameOver,
     TextUpdated,
 
@@ -14,18 +53,29 @@ pub enum GameEvent {
     /// Some text to display for the player. E.g. Pickup.
     InfoText(String),
 
-    /// Play the background music.
-    PlayBackgroundMusic(String),
-
-    /// Play some sound
-    PlaySound(String),
-
     /// Start the next stage.
     NextStage(String),
 
-    /// Something exploded :D
-    Explosion(hecs::Entity, ExplosionDetails, glam::Vec2),
-
     /// No more stages, you are the boss !
     YouWin,
 }
+
+/// Requests from the developer console to the main scene.
+#[derive(Debug, Clone)]
+pub enum StageCommand {
+    /// Finish the current wave (or the wait before the next one).
+    NextWave,
+    /// Replace the stage by the one in this file (in assets/stages).
+    Load(String),
+}
+
+/// Add the channels of all the game events to the resources.
+pub fn register_game_events(resources: &mut Resources) {
+    register_event::<Delete>(resources);
+    register_event::<Hit>(resources);
+    register_event::<Explode>(resources);
+    register_event::<AudioEvent>(resources);
+    register_event::<ParticleEffect>(resources);
+    register_event::<GameEvent>(resources);
+    register_event::<StageCommand>(resources);
+}
@@ -1,31 +1,40 @@
 #[cfg(feature = "hot-reload")]
 use crate::assets::HotReloader;
-use crate::config::AudioConfig;
+use crate::config::{AudioConfig, CameraConfig, GameEngineConfig, GraphicsConfig};
+use crate::core::audio::bank::SoundBanks;
 use crate::core::audio::AudioSystem;
-use crate::core::camera::{Camera, ProjectionMatrix};
-use crate::core::input::{Input, InputAction};
+use crate::core::camera::{get_camera_zoom, Camera, ProjectionMatrix};
+use crate::core::console::Console;
+use crate::core::effect::EffectSystem;
+use crate::core::events::{self, flush_events, register_reader, EventReader, Events};
+use crate::core::input::{Input, InputAction, InputMapping};
+use crate::core::lifetime::update_lifetimes;
+use crate::core::profiler::{self, Profiler};
 use crate::core::random::{RandomGenerator, Seed};
-use crate::core::scene::{Scene, SceneStack};
+use crate::core::scene::{Persistent, Scene, SceneResult, SceneStack, Transition};
+use crate::core::timer::TimeScale;
 use crate::core::transform::update_transforms;
-use crate::core::window::WindowDim;
-use crate::event::GameEvent;
+use crate::core::window::{set_display_mode, DisplayMode, WindowDim};
+use crate::event::{register_game_events, GameEvent};
 use crate::gameplay::collision::CollisionWorld;
 use crate::gameplay::delete::GarbageCollector;
-use crate::render::path::debug::DebugQueue;
+use crate::gameplay::inspector::Inspector;
+use crate::render::path::debug::{self, DebugCategory, DebugQueue};
 use crate::render::ui::gui::GuiContext;
 use crate::render::Renderer;
 use crate::resources::Resources;
 use crate::{HEIGHT, WIDTH};
-use glfw::{Context, Key, MouseButton, WindowEvent};
+use glfw::{Context, Key, WindowEvent};
 use log::info;
 use luminance_glfw::GlfwSurface;
-use shrev::{EventChannel, ReaderId};
 use std::any::Any;
-use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::thread;
 use std::time::{Duration, Instant};
 
+/// Fade-in of the first scene, once its assets are loaded.
+const FIRST_SCENE_FADE: Duration = Duration::from_millis(600);
+
 /// GameBuilder is used to create a new game. Game struct has a lot of members that do not need to be
 /// exposed so gamebuilder provides a simpler way to get started.
 pub struct GameBuilder<'a, A>
@@ -37,9 +46,10 @@ where
     resources: Resources,
     phantom: PhantomData<A>,
     seed: Option<Seed>,
-    input_config: Option<(HashMap<Key, A>, HashMap<MouseButton, A>)>,
+    input_config: Option<InputMapping<A>>,
     gui_context: GuiContext,
     audio_config: AudioConfig,
+    graphics_config: GraphicsConfig,
 }
 
 impl<'a, A> GameBuilder<'a, A>
@@ -47,19 +57,21 @@ where
     A: InputAction + 'static,
 {
     pub fn new(surface: &'a mut GlfwSurface) -> Self {
-        // resources will need at least an event channel and an input
+        // resources will need at least the event channels and an input
         let mut resources = Resources::default();
-        let chan: EventChannel<GameEvent> = EventChannel::new();
-        resources.insert(chan);
+        register_game_events(&mut resources);
 
         // and some asset manager;
         crate::assets::create_asset_managers(surface, &mut resources);
 
-        // the proj matrix.
+        // the proj matrix. Real dimensions are known when building the game.
         resources.insert(ProjectionMatrix::new(WIDTH as f32, HEIGHT as f32));
         resources.insert(WindowDim::new(WIDTH, HEIGHT));
         resources.insert(CollisionWorld::default());
         resources.insert(DebugQueue::default());
+        resources.insert(CameraConfig::default());
+        resources.insert(SoundBanks::default());
+        resources.insert(TimeScale::default());
 
         Self {
             gui_context: GuiContext::new(WindowDim::new(WIDTH, HEIGHT)),
@@ -70,6 +82,7 @@ where
             phantom: PhantomData::default(),
             seed: None,
             audio_config: AudioConfig::default(),
+            graphics_config: GraphicsConfig::default(),
         }
     }
 
@@ -79,12 +92,8 @@ where
         self
     }
 
-    pub fn with_input_config(
-        mut self,
-        key_map: HashMap<Key, A>,
-        btn_map: HashMap<MouseButton, A>,
-    ) -> Self {
-        self.input_config = Some((key_map, btn_map));
+    pub fn with_input_config(mut self, mapping: InputMapping<A>) -> Self {
+        self.input_config = Some(mapping);
         self
     }
 
@@ -94,6 +103,12 @@ where
         self
     }
 
+    /// Virtual resolution and window settings.
+    pub fn with_graphics_config(mut self, graphics_config: GraphicsConfig) -> Self {
+        self.graphics_config = graphics_config;
+        self
+    }
+
     /// Add custom resources.
     pub fn with_resource<T: Any>(mut self, r: T) -> Self {
         self.resources.insert(r);
@@ -107,14 +122,61 @@ where
 
     pub fn build(mut self) -> Game<'a, A> {
         let renderer = Renderer::new(self.surface, &self.gui_context);
+
+        // Screen dimensions depend on the actual window.
+        let windowed_pos = self.surface.window.get_pos();
+        if self.graphics_config.display_mode != DisplayMode::Windowed {
+            set_display_mode(
+                &mut self.surface.window,
+                self.graphics_config.display_mode,
+                windowed_pos,
+                (
+                    self.graphics_config.window_width,
+                    self.graphics_config.window_height,
+                ),
+            );
+        }
+        let window_dim = {
+            let mut dim = WindowDim::new(self.graphics_config.width, self.graphics_config.height)
+                .with_scale_mode(self.graphics_config.scale_mode);
+            let (fb_w, fb_h) = self.surface.window.get_framebuffer_size();
+            let (w, h) = self.surface.window.get_size();
+            dim.resize((fb_w as u32, fb_h as u32), (w as u32, h as u32));
+            dim
+        };
+        self.resources.insert(window_dim);
+        self.resources.insert(ProjectionMatrix::new(
+            window_dim.width as f32,
+            window_dim.height as f32,
+        ));
+        self.gui_context.window_dim = window_dim;
+
         // Need some input :D
         let input: Input<A> = {
-            let (key_mapping, btn_mapping) = self
-                .input_config
-                .unwrap_or((A::get_default_key_mapping(), A::get_default_mouse_mapping()));
-            Input::new(key_mapping, btn_mapping)
+            let mut input = Input::new(self.input_config.unwrap_or_default());
+            input.set_window_dim(window_dim);
+            input
         };
         self.resources.insert(input);
+
+        // Debug categories enabled at the start depend on the engine config.
+        let debug_queue = self
+            .resources
+            .fetch::<GameEngineConfig>()
+            .map(|conf| DebugQueue::from_config(&conf))
+            .unwrap_or_default();
+        self.resources.insert(debug_queue);
+
+        // Frames are only measured when the profiler is enabled.
+        let profiler_enabled = self
+            .resources
+            .fetch::<GameEngineConfig>()
+            .map(|conf| conf.profiler)
+            .unwrap_or(false);
+        if profiler_enabled {
+            self.resources.insert(Profiler::new());
+        }
+
         let mut world = hecs::World::new();
 
         // if a seed is provided, let's add it to the resources.
@@ -126,30 +188,36 @@ where
 
         let scene_stack = {
             let mut scenes = SceneStack::default();
+            // The assets of the first scene are loaded before it fades in.
             if let Some(scene) = self.scene {
-                scenes.push(scene, &mut world, &mut self.resources);
+                scenes.apply_result(
+                    SceneResult::Push(scene).with_transition(Transition::fade(FIRST_SCENE_FADE)),
+                    &mut world,
+                    &mut self.resources,
+                );
             }
             scenes
         };
 
-        let rdr_id = {
-            let mut chan = self
-                .resources
-                .fetch_mut::<EventChannel<GameEvent>>()
-                .unwrap();
-            chan.register_reader()
-        };
+        let rdr_id = register_reader::<GameEvent>(&self.resources, "scene");
 
         let garbage_collector = GarbageCollector::new(&mut self.resources);
+        let effect_system = EffectSystem::new(&mut self.resources);
 
         // we need a camera :)
-        world.spawn((Camera::new(),));
+        world.spawn((Camera::new(), Persistent));
 
         info!("Finished building game");
 
-        // audio system.
-        let audio_system = AudioSystem::new(&self.resources, self.audio_config)
-            .expect("Cannot create audio system");
+        // audio system. The config is also kept in the resources so that the console can change
+        // it.
+        self.resources.insert(self.audio_config.clone());
+        let audio_system = AudioSystem::new(&self.resources, self.audio_config);
+
+        // Console variables saved during the previous session.
+        if let Some(console) = self.resources.fetch::<Console>() {
+            console.load_cvars(&self.resources);
+        }
 
         Game {
             surface: self.surface,
@@ -160,8 +228,16 @@ where
             resources: self.resources,
             rdr_id,
             garbage_collector,
+            effect_system,
+            display_mode: self.graphics_config.display_mode,
+            windowed_pos,
+            windowed_size: (
+                self.graphics_config.window_width,
+                self.graphics_config.window_height,
+            ),
             phantom: self.phantom,
             gui_context: self.gui_context,
+            inspector: Inspector::new(),
             #[cfg(feature = "hot-reload")]
             hot_reloader: HotReloader::new(),
         }
@@ -195,14 +271,25 @@ pub struct Game<'a, A> {
     /// Current entities.
     world: hecs::World,
 
-    /// Read events from the systems
-    rdr_id: ReaderId<GameEvent>,
+    /// Read the game events to give them to the current scene.
+    rdr_id: EventReader<GameEvent>,
 
     /// Clean up the dead entities.
     garbage_collector: GarbageCollector,
 
+    /// Spawn the particle effects requested by the systems.
+    effect_system: EffectSystem,
+
     gui_context: GuiContext,
 
+    /// Debug overlay to edit the entities.
+    inspector: Inspector,
+
+    /// Current display mode, and position and size of the window when not fullscreen.
+    display_mode: DisplayMode,
+    windowed_pos: (i32, i32),
+    windowed_size: (u32, u32),
+
     phantom: PhantomData<A>,
 
     #[cfg(feature = "hot-reload")]
@@ -218,46 +305,187 @@ where
         let mut current_time = Instant::now();
         let dt = Duration::from_millis(16);
         let mut back_buffer = self.surface.back_buffer().unwrap();
+        let inspector_enabled = self
+            .resources
+            .fetch::<GameEngineConfig>()
+            .map(|conf| conf.inspector)
+            .unwrap_or(false);
+        let profiler_enabled = self.resources.fetch::<Profiler>().is_some();
 
         'app: loop {
+            if let Some(mut profiler) = self.resources.fetch_mut::<Profiler>() {
+                profiler.begin_frame();
+            }
+
             // 1. Poll the events and update the Input resource
             // ------------------------------------------------
             let mut resize = false;
-            self.surface.window.glfw.poll_events();
             {
+                let _scope = profiler::scope(&self.resources, "poll");
+                self.surface.window.glfw.poll_events();
                 let mut input = self.resources.fetch_mut::<Input<A>>().unwrap();
                 input.prepare();
                 self.gui_context.reset_inputs();
+                let window_dim = *self.resources.fetch::<WindowDim>().unwrap();
+                let mut display_mode = None;
                 for (_, event) in self.surface.events_rx.try_iter() {
+                    // Cursor is in window coordinates. Everything else works with the virtual
+                    // screen.
+                    let event = if let WindowEvent::CursorPos(x, y) = event {
+                        let pos = window_dim.window_to_screen(glam::vec2(x as f32, y as f32));
+                        WindowEvent::CursorPos(pos.x() as f64, pos.y() as f64)
+                    } else {
+                        event
+                    };
+                    // The keys still go to the scene and the input mapping below as they can be
+                    // bound to actions.
+                    if debug::ENABLED {
+                        if let WindowEvent::Key(key, _, glfw::Action::Press, _) = event {
+                            if let Some(category) = DebugCategory::from_key(key) {
+                                self.resources
+                                    .fetch_mut::<DebugQueue>()
+                                    .unwrap()
+                                    .toggle(category);
+                            }
+                        }
+                    }
+
                     match event {
                         WindowEvent::Close => break 'app,
-                        WindowEvent::FramebufferSize(_, _) => resize = true,
+                        WindowEvent::FramebufferSize(_, _)
+                        | WindowEvent::Size(_, _)
+                        | WindowEvent::ContentScale(_, _) => resize = true,
+                        WindowEvent::Key(Key::F11, _, glfw::Action::Press, _) => {
+                            display_mode = Some(DisplayMode::Fullscreen)
+                        }
+                        WindowEvent::Key(Key::F10, _, glfw::Action::Press, _) => {
+                            display_mode = Some(DisplayMode::Borderless)
+                        }
+                        WindowEvent::Key(Key::F9, _, glfw::Action::Press, _)
+                            if inspector_enabled =>
+                        {
+                            self.inspector.toggle()
+                        }
+                        WindowEvent::Key(Key::F8, _, glfw::Action::Press, _)
+                            if profiler_enabled =>
+                        {
+                            self.resources.fetch_mut::<Profiler>().unwrap().toggle()
+                        }
+                        WindowEvent::Key(Key::F7, _, glfw::Action::Press, _)
+                            if profiler_enabled =>
+                        {
+                            self.resources
+                                .fetch_mut::<Profiler>()
+                                .unwrap()
+                                .toggle_recording()
+                        }
                         ev => {
                             self.gui_context.process_event(ev.clone());
-                            if let Some(scene) = self.scene_stack.current_mut() {
-                                scene.process_input(&mut self.world, ev.clone(), &self.resources);
+                            // The outgoing scene is frozen during a transition.
+                            if !self.scene_stack.is_transitioning() {
+                                if let Some(scene) = self.scene_stack.current_mut() {
+                                    scene.process_input(
+                                        &mut self.world,
+                                        ev.clone(),
+                                        &self.resources,
+                                    );
+                                }
                             }
                             input.process_event(ev)
                         }
                     }
                 }
+
+                // Menus can also be navigated with the gamepad.
+                if let Some(state) = input.process_gamepad(&self.surface.window.glfw) {
+                    self.gui_context.process_gamepad(&state);
+                }
+
+                // Pressing the same key again will go back to windowed mode.
+                if let Some(mode) = display_mode {
+                    // Remember where the window was to put it back there later.
+                    if self.display_mode == DisplayMode::Windowed {
+                        let (w, h) = self.surface.window.get_size();
+                        self.windowed_pos = self.surface.window.get_pos();
+                        self.windowed_size = (w.max(1) as u32, h.max(1) as u32);
+                    }
+                    self.display_mode = if self.display_mode == mode {
+                        DisplayMode::Windowed
+                    } else {
+                        mode
+                    };
+                    set_display_mode(
+                        &mut self.surface.window,
+                        self.display_mode,
+                        self.windowed_pos,
+                        self.windowed_size,
+                    );
+                    resize = true;
+                }
             }
 
             // 2. Update the scene.
             // ------------------------------------------------
+            // Timed debug shapes that have expired are removed.
+            self.resources.fetch_mut::<DebugQueue>().unwrap().tick(dt);
+
+            let scene_dt = self
+                .resources
+                .fetch::<TimeScale>()
+                .map(|scale| scale.scale(dt))
+                .unwrap_or(dt);
+            let is_transitioning = self.scene_stack.is_transitioning();
             let scene_result = if let Some(scene) = self.scene_stack.current_mut() {
-                let scene_res = scene.update(dt, &mut self.world, &self.resources);
+                // During a transition, the outgoing scene is still drawn but not updated.
+                let scene_res = if is_transitioning {
+                    flush_events(&self.resources);
+                    let chan = self.resources.fetch::<Events<GameEvent>>().unwrap();
+                    chan.read(&mut self.rdr_id).for_each(drop);
+                    SceneResult::Noop
+                } else {
+                    let _scope = profiler::scope(&self.resources, "update");
+                    let scene_res = scene.update(scene_dt, &mut self.world, &self.resources);
+                    update_lifetimes(&self.world, &self.resources, scene_dt);
+
+                    // Deferred events are sent after the update, before the scene and the
+                    // garbage collector read them.
+                    flush_events(&self.resources);
+                    let events: Vec<GameEvent> = {
+                        let chan = self.resources.fetch::<Events<GameEvent>>().unwrap();
+                        chan.read(&mut self.rdr_id).cloned().collect()
+                    };
+                    for ev in events {
+                        scene.process_event(&mut self.world, ev, &self.resources);
+                    }
+                    scene_res
+                };
 
+                let _scope = profiler::scope(&self.resources, "gui");
+                let mut maybe_gui =
+                    scene.prepare_gui(dt, &mut self.world, &self.resources, &mut self.gui_context);
+
+                // Debug labels, the inspector and the profiler are drawn above the scene UI.
+                let profiler_visible = self
+                    .resources
+                    .fetch::<Profiler>()
+                    .map(|profiler| profiler.visible)
+                    .unwrap_or(false);
+                if self.inspector.visible || profiler_visible || debug::has_labels(&self.resources)
                 {
-                    let chan = self.resources.fetch::<EventChannel<GameEvent>>().unwrap();
-                    for ev in chan.read(&mut self.rdr_id) {
-                        scene.process_event(&mut self.world, ev.clone(), &self.resources);
+                    let mut gui = maybe_gui.unwrap_or_else(|| self.gui_context.new_frame());
+                    debug::draw_labels(&mut gui, &self.world, &self.resources);
+                    if self.inspector.visible {
+                        self.inspector.prepare_gui(&mut self.world, &mut gui);
+                    }
+                    if profiler_visible {
+                        self.resources
+                            .fetch::<Profiler>()
+                            .unwrap()
+                            .prepare_gui(&mut gui);
                     }
+                    maybe_gui = Some(gui);
                 }
 
-                let maybe_gui =
-                    scene.prepare_gui(dt, &mut self.world, &self.resources, &mut self.gui_context);
-
                 self.renderer.prepare_ui(
                     self.surface,
                     maybe_gui,
@@ -267,34 +495,67 @@ where
 
                 Some(scene_res)
             } else {
+                flush_events(&self.resources);
                 None
             };
 
+            // Spawn the particle effects before their transform is updated.
+            {
+                let _scope = profiler::scope(&self.resources, "effects");
+                self.effect_system.process(&mut self.world, &self.resources);
+            }
+
+            // Entities spawned during the frame belong to the current scene.
+            self.scene_stack.tag_entities(&mut self.world);
+
             // Update children transforms:
             // -----------------------------
-            update_transforms(&mut self.world);
+            {
+                let _scope = profiler::scope(&self.resources, "transforms");
+                update_transforms(&mut self.world);
+
+                // Apply the camera zoom to the projection.
+                if let Some(zoom) = get_camera_zoom(&self.world) {
+                    let mut proj = self.resources.fetch_mut::<ProjectionMatrix>().unwrap();
+                    proj.set_zoom(zoom);
+                }
+            }
 
             // 3. Clean up dead entities.
             // ------------------------------------------------
-            self.garbage_collector
-                .collect(&mut self.world, &self.resources);
+            {
+                let _scope = profiler::scope(&self.resources, "gc");
+                self.garbage_collector
+                    .collect(&mut self.world, &self.resources);
+            }
 
             // 4. Render to screen
             // ------------------------------------------------
             log::debug!("RENDER");
+            let render_scope = profiler::scope(&self.resources, "render");
             self.renderer
                 .update(self.surface, &self.world, dt, &self.resources);
             if resize {
                 back_buffer = self.surface.back_buffer().unwrap();
                 let new_size = back_buffer.size();
-                let mut proj = self.resources.fetch_mut::<ProjectionMatrix>().unwrap();
-                proj.resize(new_size[0] as f32, new_size[1] as f32);
+                let window_size = self.surface.window.get_size();
 
                 let mut dim = self.resources.fetch_mut::<WindowDim>().unwrap();
-                dim.resize(new_size[0], new_size[1]);
+                dim.resize(
+                    (new_size[0], new_size[1]),
+                    (window_size.0 as u32, window_size.1 as u32),
+                );
                 self.gui_context.window_dim = *dim;
+
+                let mut proj = self.resources.fetch_mut::<ProjectionMatrix>().unwrap();
+                proj.resize(dim.width as f32, dim.height as f32);
+
+                let mut input = self.resources.fetch_mut::<Input<A>>().unwrap();
+                input.set_window_dim(*dim);
             }
 
+            self.renderer
+                .prepare_transition(self.scene_stack.transition_overlay());
             let render =
                 self.renderer
                     .render(self.surface, &mut back_buffer, &self.world, &self.resources);
@@ -303,27 +564,44 @@ where
             } else {
                 break 'app;
             }
+            drop(render_scope);
 
             // Play music :)
-            self.audio_system.process(&self.resources);
+            {
+                let _scope = profiler::scope(&self.resources, "audio");
+                self.audio_system.process(&self.world, &self.resources, dt);
+            }
 
             // Update collision world for collision queries.
             {
+                let _scope = profiler::scope(&self.resources, "collisions");
                 let mut collisions = self.resources.fetch_mut::<CollisionWorld>().unwrap();
                 collisions.synchronize(&self.world);
             }
 
             // Either clean up or load new resources.
-            crate::assets::update_asset_managers(self.surface, &self.resources);
-            #[cfg(feature = "hot-reload")]
-            self.hot_reloader.update(&self.resources);
+            {
+                let _scope = profiler::scope(&self.resources, "assets");
+                crate::assets::update_asset_managers(self.surface, &self.resources);
+                #[cfg(feature = "hot-reload")]
+                self.hot_reloader.update(&self.resources);
+            }
 
-            // Now, if need to switch scenes, do it.
+            // Now, if need to switch scenes, do it. A transition switches the scenes once the
+            // screen is covered and the assets of the next scene are loaded.
+            self.scene_stack
+                .update_transition(dt, &mut self.world, &mut self.resources);
             if let Some(res) = scene_result {
                 self.scene_stack
                     .apply_result(res, &mut self.world, &mut self.resources);
             }
 
+            let lagging_readers = events::lagging_readers(&self.resources);
+            if let Some(mut profiler) = self.resources.fetch_mut::<Profiler>() {
+                profiler.set_lagging_readers(lagging_readers);
+                profiler.end_frame(self.world.iter().count(), self.renderer.draw_calls());
+            }
+
             let now = Instant::now();
             let frame_duration = now - current_time;
             if frame_duration < dt {
@@ -1,7 +1,7 @@
 use crate::core::colors::RgbaColor;
+use crate::core::lifetime::DespawnOutOfBounds;
+use crate::core::system::{Access, System};
 use crate::core::transform::Transform;
-use crate::core::window::WindowDim;
-use crate::event::GameEvent;
 use crate::gameplay::collision::{BoundingBox, CollisionLayer};
 use crate::gameplay::health::HitDetails;
 use crate::gameplay::physics::DynamicBody;
@@ -10,7 +10,7 @@ use crate::render::sprite::{Sprite, Tint};
 use crate::resources::Resources;
 use hecs::{Entity, World};
 use log::trace;
-use shrev::EventChannel;
+use std::time::Duration;
 
 #[derive(Debug, Copy, Clone)]
 pub enum BulletType {
@@ -58,13 +58,8 @@ pub struct Missile {
     pub home_to_entity: Option<Entity>,
 }
 
-pub fn process_missiles(world: &World, resources: &Resources) {
-    let mut to_despawn = vec![];
-    let window_dim = resources.fetch::<WindowDim>().unwrap();
-
-    let max_width = 2.0 * window_dim.width as f32;
-    let max_height = 2.0 * window_dim.height as f32;
-    for (e, (t, missile, body)) in world
+pub fn process_missiles(world: &World) {
+    for (_, (t, missile, body)) in world
         .query::<(&mut Transform, &mut Missile, &mut DynamicBody)>()
         .iter()
     {
@@ -94,48 +89,18 @@ pub fn process_missiles(world: &World, resources: &Resources) {
             // no entity,  just go straight.
             body.add_force(body.velocity.normalize() * body.max_velocity);
         }
-
-        if t.translation.x() > max_width
-            || t.translation.x() < -max_width
-            || t.translation.y() > max_height
-            || t.translation.y() < -max_height
-        {
-            to_despawn.push(GameEvent::Delete(e));
-        }
-    }
-
-    {
-        let mut channel = resources.fetch_mut::<EventChannel<GameEvent>>().unwrap();
-        channel.drain_vec_write(&mut to_despawn)
     }
 }
 
-/// Every frame, will move the bullet in the given direction at the given speed.
-pub fn process_bullets(world: &World, resources: &Resources) {
+/// Every frame, will move the bullet in the given direction at the given speed. Bullets that
+/// leave the stage are despawned by `DespawnOutOfBounds`.
+pub fn process_bullets(world: &World) {
     trace!("process_bullets");
 
-    let window_dim = resources.fetch::<WindowDim>().unwrap();
-
-    let max_width = 2.0 * window_dim.width as f32;
-    let max_height = 2.0 * window_dim.height as f32;
-
-    let mut to_despawn = vec![];
-    for (e, (b, t)) in world.query::<(&Bullet, &mut Transform)>().iter() {
+    for (_, (b, t)) in world.query::<(&Bullet, &mut Transform)>().iter() {
         t.translation += b.direction * b.speed;
-
-        if t.translation.x() > max_width
-            || t.translation.x() < -max_width
-            || t.translation.y() > max_height
-            || t.translation.y() < -max_height
-        {
-            to_despawn.push(GameEvent::Delete(e));
-        }
     }
 
-    {
-        let mut channel = resources.fetch_mut::<EventChannel<GameEvent>>().unwrap();
-        channel.drain_vec_write(&mut to_despawn)
-    }
     trace!("finished process_bullets");
 }
 
@@ -171,6 +136,7 @@ pub fn spawn_player_bullet(
                 CollisionLayer::ENEMY | CollisionLayer::ASTEROID | CollisionLayer::MINE,
             ),
         },
+        DespawnOutOfBounds,
     ));
 
     if hit_details.is_crit {
@@ -216,6 +182,7 @@ pub fn spawn_enemy_bullet(
             collision_layer: CollisionLayer::ENEMY_BULLET,
             collision_mask: Some(CollisionLayer::PLAYER | CollisionLayer::ASTEROID),
         },
+        DespawnOutOfBounds,
     ))
 }
 
@@ -255,5 +222,28 @@ pub fn spawn_missile(
             collision_layer: CollisionLayer::MISSILE,
             collision_mask: Some(mask | CollisionLayer::ASTEROID | CollisionLayer::MISSILE),
         },
+        DespawnOutOfBounds,
     ))
 }
+
+/// Move the bullets and steer the missiles.
+pub struct BulletSystem;
+
+impl System for BulletSystem {
+    fn name(&self) -> &'static str {
+        "bullets"
+    }
+
+    fn access(&self) -> Access {
+        Access::new()
+            .write_component::<Bullet>()
+            .write_component::<Missile>()
+            .read_component::<Transform>()
+            .write_component::<DynamicBody>()
+    }
+
+    fn run(&mut self, world: &mut World, _resources: &Resources, _dt: Duration) {
+        process_bullets(world);
+        process_missiles(world);
+    }
+}
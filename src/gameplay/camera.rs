@@ -1,24 +1,219 @@
-use crate::core::camera::Camera;
+//! Camera controller for the gameplay. The camera follows the player with some damping, looks
+//! a bit ahead toward the mouse cursor and shakes when bad things happen (trauma based shake).
+
+use crate::config::CameraConfig;
+use crate::core::camera::{Camera, ProjectionMatrix};
+use crate::core::colors;
+use crate::core::events::{register_reader, EventReader, Events};
+use crate::core::input::Input;
+use crate::core::noise::perlin::Perlin;
+use crate::core::random::RandomGenerator;
+use crate::core::system::{Access, System};
 use crate::core::transform::Transform;
 use crate::core::window::WindowDim;
-use crate::gameplay::player::Player;
+use crate::event::{Explode, Hit};
+use crate::gameplay::player::{aim_target, get_player, Player};
+use crate::gameplay::Action;
+use crate::render::path::debug;
+use crate::render::path::debug::{DebugCategory, DebugQueue};
 use crate::resources::Resources;
 use hecs::World;
+use serde_derive::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Area in which the center of the camera can move. This is defined by the stage.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct CameraBounds {
+    pub min: glam::Vec2,
+    pub max: glam::Vec2,
+}
+
+impl Default for CameraBounds {
+    fn default() -> Self {
+        Self {
+            min: glam::vec2(-800.0, -450.0),
+            max: glam::vec2(800.0, 450.0),
+        }
+    }
+}
+
+impl CameraBounds {
+    pub fn clamp(&self, position: glam::Vec2) -> glam::Vec2 {
+        glam::vec2(
+            position.x().max(self.min.x()).min(self.max.x()),
+            position.y().max(self.min.y()).min(self.max.y()),
+        )
+    }
+}
+
+pub struct CameraSystem {
+    hit_reader: EventReader<Hit>,
+    explosion_reader: EventReader<Explode>,
+    bounds: CameraBounds,
+
+    /// Between 0 and 1. The shake is proportional to the square of the trauma.
+    trauma: f32,
+
+    /// Zoom requested by the gameplay. If None, the zoom from the config is used.
+    target_zoom: Option<f32>,
+
+    /// If true, the camera will jump to its target instead of easing toward it.
+    snap: bool,
+
+    noise: Perlin,
+    elapsed: f32,
+}
+
+impl CameraSystem {
+    pub fn new(resources: &mut Resources, bounds: CameraBounds) -> Self {
+        let hit_reader = register_reader(resources, "camera");
+        let explosion_reader = register_reader(resources, "camera");
+        let mut random = resources.fetch_mut::<RandomGenerator>().unwrap();
+        Self {
+            hit_reader,
+            explosion_reader,
+            bounds,
+            trauma: 0.0,
+            target_zoom: None,
+            snap: true,
+            noise: Perlin::new(random.rng()),
+            elapsed: 0.0,
+        }
+    }
+
+    /// Change the camera bounds, for example when a new stage starts. The camera will jump to
+    /// the player on the next update.
+    pub fn set_bounds(&mut self, bounds: CameraBounds) {
+        self.bounds = bounds;
+        self.snap = true;
+    }
 
-pub fn update_camera(world: &mut World, resources: &Resources) {
-    let dim = resources.fetch::<WindowDim>().unwrap();
-    let pos = world
-        .query::<(&Transform, &Player)>()
-        .iter()
-        .map(|(_, (t, _p))| t.translation)
-        .next();
-    if let Some(pos) = pos {
-        if let Some((_, t)) = world.query::<&mut Camera>().iter().next() {
-            t.position = pos; // - glam::vec2(WIDTH as f32 / 2.0, HEIGHT as f32 / 2.0);
-            t.position
-                .set_x((t.position.x().max(-800.0)).min(800.0) - dim.width as f32 / 2.0);
-            t.position
-                .set_y(t.position.y().max(-450.0).min(450.0) - dim.height as f32 / 2.0);
+    /// Add some trauma to shake the camera. Trauma is capped to 1.
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.0);
+    }
+
+    /// Zoom to the given level. None will go back to the zoom defined in the configuration.
+    pub fn zoom_to(&mut self, zoom: Option<f32>) {
+        self.target_zoom = zoom;
+    }
+
+    pub fn update(&mut self, world: &mut World, resources: &Resources, dt: Duration) {
+        let config = resources.fetch::<CameraConfig>().unwrap();
+        let dt = dt.as_secs_f32();
+
+        // 1. Bad things happened, let's shake the camera.
+        let player = get_player(world);
+        {
+            let explosions = resources.fetch::<Events<Explode>>().unwrap();
+            let hits = resources.fetch::<Events<Hit>>().unwrap();
+            let mut trauma =
+                explosions.read(&mut self.explosion_reader).len() as f32 * config.explosion_trauma;
+            for Hit(e, _) in hits.read(&mut self.hit_reader) {
+                if Some(*e) == player {
+                    trauma += config.hit_trauma;
+                }
+            }
+            self.add_trauma(trauma);
+        }
+        self.elapsed += dt;
+        self.trauma = (self.trauma - config.trauma_decay * dt).max(0.0);
+        let shake = self.trauma * self.trauma;
+        let t = self.elapsed * config.shake_frequency;
+        let offset = glam::vec2(
+            self.noise.perlin(t, 0.5) * 2.0 - 1.0,
+            self.noise.perlin(0.5, t) * 2.0 - 1.0,
+        ) * config.max_shake_offset
+            * shake;
+
+        // 2. Where should the camera look at ?
+        let half_screen = resources.fetch::<WindowDim>().unwrap().to_vec2() / 2.0;
+        let player_pos = world
+            .query::<(&Transform, &Player)>()
+            .iter()
+            .map(|(_, (t, _p))| t.translation)
+            .next();
+        let look_at = player_pos.map(|pos| {
+            let input = resources.fetch::<Input<Action>>().unwrap();
+            let proj = resources.fetch::<ProjectionMatrix>().unwrap();
+            let aim = aim_target(&input, pos, proj.matrix(), world);
+
+            let mut look_ahead = (aim - pos) * config.look_ahead;
+            if look_ahead.length() > config.max_look_ahead {
+                look_ahead = look_ahead.normalize() * config.max_look_ahead;
+            }
+            self.bounds.clamp(pos + look_ahead)
+        });
+        // camera position is the bottom-left corner of the screen.
+        let target = look_at.map(|center| center - half_screen);
+
+        if let (Some(pos), Some(center)) = (player_pos, look_at) {
+            if debug::ENABLED {
+                debug::arrow(resources, DebugCategory::CAMERA, pos, center, colors::BLUE);
+                debug::stroke_quad(
+                    resources,
+                    DebugCategory::CAMERA,
+                    self.bounds.min,
+                    self.bounds.max - self.bounds.min,
+                    colors::GREEN,
+                );
+                debug::label(
+                    resources,
+                    DebugCategory::CAMERA,
+                    center,
+                    format!("trauma {:.2}", self.trauma),
+                    colors::BLUE,
+                );
+            }
+        }
+
+        // 3. Move the camera.
+        let follow_t = 1.0 - (-config.follow_smoothing * dt).exp();
+        let zoom_t = 1.0 - (-config.zoom_smoothing * dt).exp();
+        let target_zoom = self.target_zoom.unwrap_or(config.zoom);
+        if let Some((_, camera)) = world.query::<&mut Camera>().iter().next() {
+            if let Some(target) = target {
+                if self.snap {
+                    camera.position = target;
+                    self.snap = false;
+                } else {
+                    camera.position = camera.position.lerp(target, follow_t);
+                }
+            }
+            camera.zoom += (target_zoom - camera.zoom) * zoom_t;
+            camera.offset = offset;
         }
     }
 }
+
+/// Put the camera back to its default state (no zoom, no shake).
+pub fn reset_camera(world: &mut World) {
+    if let Some((_, camera)) = world.query::<&mut Camera>().iter().next() {
+        camera.zoom = 1.0;
+        camera.offset = glam::Vec2::zero();
+    }
+}
+
+impl System for CameraSystem {
+    fn name(&self) -> &'static str {
+        "camera"
+    }
+
+    fn access(&self) -> Access {
+        Access::new()
+            .read::<CameraConfig>()
+            .read::<Events<Explode>>()
+            .read::<Events<Hit>>()
+            .read::<WindowDim>()
+            .read::<Input<Action>>()
+            .read::<ProjectionMatrix>()
+            .write::<DebugQueue>()
+            .read_component::<Transform>()
+            .read_component::<Player>()
+            .write_component::<Camera>()
+    }
+
+    fn run(&mut self, world: &mut World, resources: &Resources, dt: Duration) {
+        self.update(world, resources, dt);
+    }
+}
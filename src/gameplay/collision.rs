@@ -1,18 +1,21 @@
 use crate::core::colors::RgbaColor;
+use crate::core::events::Events;
+use crate::core::system::{Access, System};
 use crate::core::transform::Transform;
-use crate::event::GameEvent;
+use crate::event::{Delete, Explode, Hit};
 use crate::gameplay::bullet::{Bullet, Missile};
 use crate::gameplay::explosion::{ExplosionDetails, ExplosionType};
 use crate::gameplay::health::Health;
 use crate::gameplay::physics::DynamicBody;
 use crate::render::path::debug;
+use crate::render::path::debug::{DebugCategory, DebugQueue};
 use crate::resources::Resources;
 use glam::Vec2;
 use hecs::{Entity, World};
 use log::{debug, trace};
 use serde_derive::{Deserialize, Serialize};
-use shrev::EventChannel;
 use std::mem::swap;
+use std::time::Duration;
 
 #[derive(Debug)]
 pub struct CollisionWorld {
@@ -291,18 +294,22 @@ pub fn find_collisions(world: &World, resources: &Resources) -> Vec<(Entity, Ent
                     > transform2.translation.y() - bb2.half_extend.y()
             {
                 // if collision, let's draw the quads :)
-                debug::stroke_quad(
-                    resources,
-                    transform1.translation - bb1.half_extend,
-                    bb1.half_extend * 2.0,
-                    RgbaColor::new(255, 0, 0, 255),
-                );
-                debug::stroke_quad(
-                    resources,
-                    transform2.translation - bb2.half_extend,
-                    bb2.half_extend * 2.0,
-                    RgbaColor::new(0, 255, 0, 255),
-                );
+                if debug::ENABLED {
+                    debug::stroke_quad(
+                        resources,
+                        DebugCategory::COLLISION,
+                        transform1.translation - bb1.half_extend,
+                        bb1.half_extend * 2.0,
+                        RgbaColor::new(255, 0, 0, 255),
+                    );
+                    debug::stroke_quad(
+                        resources,
+                        DebugCategory::COLLISION,
+                        transform2.translation - bb2.half_extend,
+                        bb2.half_extend * 2.0,
+                        RgbaColor::new(0, 255, 0, 255),
+                    );
+                }
 
                 collision_pairs.push((e1, e2));
             }
@@ -337,8 +344,9 @@ pub fn process_collisions(
 ) {
     trace!("process_collisions, IN = {:?}", collision_pairs);
 
-    let mut ev_channel = resources.fetch_mut::<EventChannel<GameEvent>>().unwrap();
-    let mut events = vec![];
+    let mut deletes = vec![];
+    let mut hits = vec![];
+    let mut explosions = vec![];
     for (e1, e2) in collision_pairs {
         if e1 == e2 {
             continue;
@@ -353,16 +361,26 @@ pub fn process_collisions(
             let e1_bullet = world.get::<Bullet>(e1).is_ok();
             let e2_bullet = world.get::<Bullet>(e2).is_ok();
             match (e1_health, e2_bullet, e2_health, e1_bullet) {
-                (true, true, _, _) => events.append(&mut process_bullet_collision(world, e1, e2)),
-                (_, _, true, true) => events.append(&mut process_bullet_collision(world, e2, e1)),
+                (true, true, _, _) => {
+                    if let Some(hit) = process_bullet_collision(world, e1, e2) {
+                        deletes.push(Delete(e2));
+                        hits.push(hit);
+                    }
+                }
+                (_, _, true, true) => {
+                    if let Some(hit) = process_bullet_collision(world, e2, e1) {
+                        deletes.push(Delete(e1));
+                        hits.push(hit);
+                    }
+                }
                 (false, true, _, _) => {
                     if let Some(ev) = delete_bullet(world, e2) {
-                        events.push(ev)
+                        deletes.push(ev)
                     }
                 }
                 (_, _, _, true) => {
                     if let Some(ev) = delete_bullet(world, e1) {
-                        events.push(ev)
+                        deletes.push(ev)
                     }
                 }
                 _ => (),
@@ -378,11 +396,11 @@ pub fn process_collisions(
             has_missile = e1_missile || e2_missile;
             match (e2_missile, e1_missile) {
                 (true, _) => {
-                    events.push(GameEvent::Delete(e2));
+                    deletes.push(Delete(e2));
                     let e2_transform = world
                         .get::<Transform>(e2)
                         .expect("Missile should have a transform");
-                    events.push(GameEvent::Explosion(
+                    explosions.push(Explode(
                         e2,
                         ExplosionDetails {
                             radius: 100.0,
@@ -392,11 +410,11 @@ pub fn process_collisions(
                     ));
                 }
                 (_, true) => {
-                    events.push(GameEvent::Delete(e1));
+                    deletes.push(Delete(e1));
                     let e1_transform = world
                         .get::<Transform>(e1)
                         .expect("Missile should have a transform");
-                    events.push(GameEvent::Explosion(
+                    explosions.push(Explode(
                         e1,
                         ExplosionDetails {
                             radius: 100.0,
@@ -438,40 +456,73 @@ pub fn process_collisions(
         }
     }
 
-    if !events.is_empty() {
-        debug!("Will publish {:?}", events);
-        ev_channel.drain_vec_write(&mut events);
+    if !hits.is_empty() || !explosions.is_empty() {
+        debug!("Will publish {:?} and {:?}", hits, explosions);
     }
+    resources
+        .fetch_mut::<Events<Delete>>()
+        .unwrap()
+        .defer_all(deletes);
+    resources.fetch_mut::<Events<Hit>>().unwrap().send_all(hits);
+    resources
+        .fetch_mut::<Events<Explode>>()
+        .unwrap()
+        .send_all(explosions);
 
     trace!("Finished process_collisions");
 }
 
+/// Hit from the bullet if it is still alive. The bullet should be deleted in that case.
 fn process_bullet_collision(
     world: &mut World,
     health_entity: hecs::Entity,
     bullet_entity: hecs::Entity,
-) -> Vec<GameEvent> {
-    let mut events = vec![];
-
-    if let Ok(mut b) = world.get_mut::<Bullet>(bullet_entity) {
-        // if bullet is not alive, let's not process the rest.
-        if b.alive {
-            b.alive = false;
-            events.push(GameEvent::Delete(bullet_entity));
-            events.push(GameEvent::Hit(health_entity, b.details));
-        }
+) -> Option<Hit> {
+    let mut b = world.get_mut::<Bullet>(bullet_entity).ok()?;
+    // if bullet is not alive, let's not process the rest.
+    if b.alive {
+        b.alive = false;
+        Some(Hit(health_entity, b.details))
+    } else {
+        None
     }
-
-    events
 }
 
-fn delete_bullet(world: &mut World, bullet_entity: hecs::Entity) -> Option<GameEvent> {
+fn delete_bullet(world: &mut World, bullet_entity: hecs::Entity) -> Option<Delete> {
     let mut b = world.get_mut::<Bullet>(bullet_entity).unwrap();
     // if bullet is not alive, let's not process the rest.
     if !b.alive {
         None
     } else {
         b.alive = false;
-        Some(GameEvent::Delete(bullet_entity))
+        Some(Delete(bullet_entity))
+    }
+}
+
+pub struct CollisionSystem;
+
+impl System for CollisionSystem {
+    fn name(&self) -> &'static str {
+        "collisions"
+    }
+
+    fn access(&self) -> Access {
+        Access::new()
+            .write::<Events<Delete>>()
+            .write::<Events<Hit>>()
+            .write::<Events<Explode>>()
+            .write::<DebugQueue>()
+            .read_component::<Transform>()
+            .read_component::<BoundingBox>()
+            .write_component::<Bullet>()
+            .write_component::<Missile>()
+            .read_component::<Health>()
+            .write_component::<DynamicBody>()
+            .structural()
+    }
+
+    fn run(&mut self, world: &mut World, resources: &Resources, _dt: Duration) {
+        let collisions = find_collisions(world, resources);
+        process_collisions(world, collisions, resources);
     }
 }
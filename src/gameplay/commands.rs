@@ -0,0 +1,269 @@
+//! Commands and variables of the developer console, used while playtesting.
+use crate::assets::prefab::PrefabManager;
+use crate::config::{AudioConfig, PlayerConfig};
+use crate::core::audio;
+use crate::core::console::{CommandResult, Console, ConsoleError};
+use crate::core::events::send_event;
+use crate::core::random::{RandomGenerator, Seed};
+use crate::core::timer::TimeScale;
+use crate::core::transform::Transform;
+use crate::event::StageCommand;
+use crate::gameplay::health::Invulnerable;
+use crate::gameplay::inventory::Inventory;
+use crate::gameplay::physics::PhysicConfig;
+use crate::gameplay::player::get_player;
+use crate::resources::{FetchMut, Resources};
+use hecs::World;
+use luminance_glfw::GlfwSurface;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Prefabs are spawned this far from the player when no position is given.
+const SPAWN_DISTANCE: f32 = 300.0;
+
+/// Console with all the game commands. The variables are saved in assets/config/cvars.json.
+pub fn console() -> Console {
+    let base_path = PathBuf::from(std::env::var("ASSET_PATH").unwrap_or("assets/".to_string()));
+    let mut console = Console::new()
+        .with_save_path(base_path.join("config/cvars.json"))
+        .with_command(
+            "spawn",
+            "spawn <prefab> [x y]",
+            "Spawn a prefab next to the player or at the position",
+            spawn,
+        )
+        .with_command(
+            "wave",
+            "wave next",
+            "Finish the current wave",
+            |args, _world, resources| match args {
+                ["next"] => {
+                    send_event(resources, StageCommand::NextWave);
+                    Ok("Skipping the wave".to_string())
+                }
+                _ => Err(ConsoleError::Usage("")),
+            },
+        )
+        .with_command(
+            "stage",
+            "stage load <file>",
+            "Replace the stage by one in assets/stages",
+            load_stage,
+        )
+        .with_command(
+            "give",
+            "give scrap <amount>",
+            "Add scrap to the inventory",
+            |args, _world, resources| match args {
+                ["scrap", amount] => {
+                    let amount: u32 = parse_arg("amount", amount)?;
+                    let mut inventory = fetch_mut::<Inventory>(resources, "inventory")?;
+                    inventory.add_scratch(amount);
+                    Ok(format!("Scrap: {}", inventory.scratch()))
+                }
+                _ => Err(ConsoleError::Usage("")),
+            },
+        )
+        .with_command("god", "god", "Toggle the player invulnerability", god)
+        .with_command(
+            "timescale",
+            "timescale [scale]",
+            "Speed of the game (1 is normal speed)",
+            |args, _world, resources| {
+                let mut time_scale = fetch_mut::<TimeScale>(resources, "timescale")?;
+                match args {
+                    [] => (),
+                    [scale] => time_scale.0 = parse_arg::<f32>("scale", scale)?.max(0.0),
+                    _ => return Err(ConsoleError::Usage("")),
+                }
+                Ok(format!("timescale = {}", time_scale.0))
+            },
+        )
+        .with_command(
+            "seed",
+            "seed [n]",
+            "Reset the random generator with the seed (random seed if not given)",
+            |args, _world, resources| {
+                let seed = match args {
+                    [] => rand::random::<u64>(),
+                    [seed] => parse_arg("seed", seed)?,
+                    _ => return Err(ConsoleError::Usage("")),
+                };
+                *fetch_mut::<RandomGenerator>(resources, "random generator")? =
+                    RandomGenerator::new(Seed::from_u64(seed));
+                Ok(format!("seed = {}", seed))
+            },
+        )
+        .with_command(
+            "reload",
+            "reload",
+            "Load all the assets again from the disk",
+            |_args, _world, resources| {
+                let nb = crate::assets::reload_asset_managers::<GlfwSurface>(resources);
+                Ok(format!("Reloaded {} assets", nb))
+            },
+        )
+        .with_cvar(
+            "player.lateral_thrust",
+            "Force applied when the player moves",
+            |resources| resources.fetch::<PlayerConfig>().map(|c| c.lateral_thrust),
+            |resources, v| {
+                resources
+                    .fetch_mut::<PlayerConfig>()
+                    .map(|mut config| config.lateral_thrust = v)
+                    .is_some()
+            },
+        )
+        .with_cvar(
+            "player.rotation_delta",
+            "Rotation of the player per frame",
+            |resources| resources.fetch::<PlayerConfig>().map(|c| c.rotation_delta),
+            |resources, v| {
+                resources
+                    .fetch_mut::<PlayerConfig>()
+                    .map(|mut config| config.rotation_delta = v)
+                    .is_some()
+            },
+        )
+        .with_cvar(
+            "physics.damping",
+            "Velocity multiplier when no force is applied",
+            |resources| resources.fetch::<PhysicConfig>().map(|c| c.damping),
+            |resources, v| {
+                resources
+                    .fetch_mut::<PhysicConfig>()
+                    .map(|mut config| config.damping = v)
+                    .is_some()
+            },
+        );
+
+    for &bus in &[audio::MUSIC_BUS, audio::SFX_BUS, audio::UI_BUS] {
+        console = console
+            .with_cvar(
+                &format!("audio.{}.volume", bus),
+                "Volume of the bus (0-100)",
+                move |resources| {
+                    resources
+                        .fetch::<AudioConfig>()
+                        .and_then(|c| c.buses.get(bus).map(|b| b.volume))
+                },
+                move |resources, v: u32| {
+                    let v = v.min(100);
+                    let set = match resources.fetch_mut::<AudioConfig>() {
+                        Some(mut config) => {
+                            config.buses.get_mut(bus).map(|b| b.volume = v).is_some()
+                        }
+                        None => false,
+                    };
+                    if set {
+                        audio::set_bus_volume(resources, bus, v);
+                    }
+                    set
+                },
+            )
+            .with_cvar(
+                &format!("audio.{}.muted", bus),
+                "Mute the bus (true/false)",
+                move |resources| {
+                    resources
+                        .fetch::<AudioConfig>()
+                        .and_then(|c| c.buses.get(bus).map(|b| b.muted))
+                },
+                move |resources, muted: bool| {
+                    let set = match resources.fetch_mut::<AudioConfig>() {
+                        Some(mut config) => {
+                            config.buses.get_mut(bus).map(|b| b.muted = muted).is_some()
+                        }
+                        None => false,
+                    };
+                    if set {
+                        audio::mute_bus(resources, bus, muted);
+                    }
+                    set
+                },
+            );
+    }
+
+    console
+}
+
+fn parse_arg<T: FromStr>(name: &str, value: &str) -> Result<T, ConsoleError> {
+    value.parse().map_err(|_| ConsoleError::InvalidValue {
+        name: name.to_string(),
+        value: value.to_string(),
+    })
+}
+
+fn fetch_mut<'a, T: 'static>(
+    resources: &'a Resources,
+    name: &str,
+) -> Result<FetchMut<'a, T>, ConsoleError> {
+    resources
+        .fetch_mut::<T>()
+        .ok_or_else(|| ConsoleError::Unavailable(name.to_string()))
+}
+
+fn player_position(world: &World) -> Option<glam::Vec2> {
+    let player = get_player(world)?;
+    let transform = world.get::<Transform>(player).ok()?;
+    Some(transform.translation)
+}
+
+fn spawn(args: &[&str], world: &mut World, resources: &Resources) -> CommandResult {
+    let (name, pos) = match args {
+        [name] => {
+            let pos = player_position(world)
+                .ok_or_else(|| ConsoleError::Unavailable("player".to_string()))?;
+            (*name, pos + glam::Vec2::unit_y() * SPAWN_DISTANCE)
+        }
+        [name, x, y] => (*name, glam::vec2(parse_arg("x", x)?, parse_arg("y", y)?)),
+        _ => return Err(ConsoleError::Usage("")),
+    };
+
+    let mut prefabs = fetch_mut::<PrefabManager<GlfwSurface>>(resources, "prefabs")?;
+    let handle = prefabs.load(name.to_string());
+    let asset = prefabs.get(&handle).expect("Prefab was just loaded");
+    if asset.is_error() {
+        return Err(ConsoleError::Failed(format!("Cannot load prefab {}", name)));
+    }
+    // New prefabs are ready after the next frame.
+    let entity = asset
+        .execute(|prefab| prefab.spawn_at_pos(world, pos))
+        .ok_or_else(|| ConsoleError::Failed(format!("Prefab {} is loading, try again", name)))?;
+    Ok(format!(
+        "Spawned {} ({:?}) at ({}, {})",
+        name,
+        entity,
+        pos.x(),
+        pos.y()
+    ))
+}
+
+fn load_stage(args: &[&str], _world: &mut World, resources: &Resources) -> CommandResult {
+    let file = match args {
+        ["load", file] => *file,
+        _ => return Err(ConsoleError::Usage("")),
+    };
+    let base_path = std::env::var("ASSET_PATH").unwrap_or("assets/".to_string());
+    if !PathBuf::from(base_path).join("stages").join(file).is_file() {
+        return Err(ConsoleError::Failed(format!("No stage named {}", file)));
+    }
+    send_event(resources, StageCommand::Load(file.to_string()));
+    Ok(format!("Loading stage {}", file))
+}
+
+fn god(_args: &[&str], world: &mut World, _resources: &Resources) -> CommandResult {
+    let player =
+        get_player(world).ok_or_else(|| ConsoleError::Unavailable("player".to_string()))?;
+    if world.get::<Invulnerable>(player).is_ok() {
+        world
+            .remove_one::<Invulnerable>(player)
+            .map_err(|e| ConsoleError::Failed(e.to_string()))?;
+        Ok("god mode off".to_string())
+    } else {
+        world
+            .insert_one(player, Invulnerable)
+            .map_err(|e| ConsoleError::Failed(e.to_string()))?;
+        Ok("god mode on".to_string())
+    }
+}
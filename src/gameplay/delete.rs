@@ -1,35 +1,64 @@
 //! Clean entities the right way. Done at the end of a frame.
+//!
+//! Children with `DespawnWithParent` are deleted with their parent, recursively. The other
+//! children are detached and stay where they are.
 
-use crate::event::GameEvent;
+use crate::core::events::{register_reader, EventReader, Events};
+use crate::core::lifetime::DespawnWithParent;
+use crate::core::transform::{HasChildren, HasParent, LocalTransform};
+use crate::event::Delete;
 use crate::resources::Resources;
 use log::{debug, info};
-use shrev::{EventChannel, ReaderId};
 
 /// ahahaha what a confusing name.
 pub struct GarbageCollector {
-    rdr_id: ReaderId<GameEvent>,
+    rdr_id: EventReader<Delete>,
 }
 
 impl GarbageCollector {
     pub fn new(resources: &mut Resources) -> Self {
-        let mut chan = resources.fetch_mut::<EventChannel<GameEvent>>().unwrap();
-        let rdr_id = chan.register_reader();
+        let rdr_id = register_reader(resources, "garbage_collector");
         Self { rdr_id }
     }
 
     pub fn collect(&mut self, world: &mut hecs::World, resources: &Resources) {
-        let chan = resources.fetch::<EventChannel<GameEvent>>().unwrap();
-        for ev in chan.read(&mut self.rdr_id) {
-            if let GameEvent::Delete(e) = ev {
-                log::debug!("Will delete {:?}", e);
-
-                // remove from world
-                if let Err(e) = world.despawn(*e) {
-                    info!("Entity was already deleted (or does not exist?) = {}", e);
+        let mut to_delete: Vec<hecs::Entity> = {
+            let chan = resources.fetch::<Events<Delete>>().unwrap();
+            chan.read(&mut self.rdr_id).map(|Delete(e)| *e).collect()
+        };
+
+        while let Some(e) = to_delete.pop() {
+            log::debug!("Will delete {:?}", e);
+
+            // the parent should not update a child that does not exist anymore.
+            let parent = world.get::<HasParent>(e).map(|p| p.entity).ok();
+            if let Some(parent) = parent {
+                if let Ok(mut children) = world.get_mut::<HasChildren>(parent) {
+                    children.children.retain(|child| *child != e);
+                }
+            }
+
+            // Children are deleted with their parent, or become root entities where they are.
+            let children = world
+                .get::<HasChildren>(e)
+                .map(|c| c.children.clone())
+                .unwrap_or_default();
+            for child in children {
+                if world.get::<DespawnWithParent>(child).is_ok() {
+                    to_delete.push(child);
                 } else {
-                    debug!("Entity successfully deleted.");
+                    // The global transform is kept.
+                    let _ = world.remove_one::<HasParent>(child);
+                    let _ = world.remove_one::<LocalTransform>(child);
                 }
             }
+
+            // remove from world
+            if let Err(e) = world.despawn(e) {
+                info!("Entity was already deleted (or does not exist?) = {}", e);
+            } else {
+                debug!("Entity successfully deleted.");
+            }
         }
     }
 }
@@ -1,13 +1,17 @@
 use crate::assets::prefab::PrefabManager;
 use crate::assets::Handle;
 use crate::core::animation::AnimationController;
+use crate::core::audio::Sound;
 use crate::core::colors;
+use crate::core::events::Events;
 use crate::core::random::RandomGenerator;
+use crate::core::system::{Access, System};
 use crate::core::timer::Timer;
 use crate::core::transform::Transform;
-use crate::event::GameEvent;
+use crate::event::{AudioEvent, Delete, Explode, GameEvent};
 use crate::gameplay::bullet::{spawn_enemy_bullet, spawn_missile, BulletType};
 use crate::gameplay::collision::CollisionLayer;
+use crate::gameplay::collision::CollisionWorld;
 use crate::gameplay::explosion::{ExplosionDetails, ExplosionType};
 use crate::gameplay::health::HitDetails;
 use crate::gameplay::physics::DynamicBody;
@@ -16,13 +20,13 @@ use crate::gameplay::steering::behavior::{
     avoid_obstacles, follow_player, follow_player_bis, follow_random_path,
 };
 use crate::render::path::debug;
+use crate::render::path::debug::{DebugCategory, DebugQueue};
 use crate::resources::Resources;
 use hecs::World;
 use log::{debug, trace};
 use luminance_glfw::GlfwSurface;
 use rand::Rng;
 use serde_derive::{Deserialize, Serialize};
-use shrev::EventChannel;
 use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,6 +50,41 @@ impl Default for Enemy {
     }
 }
 
+impl Enemy {
+    /// Timers of the enemy behavior, with their name.
+    pub fn timers_mut(&mut self) -> Vec<(&'static str, &mut Timer)> {
+        match &mut self.enemy_type {
+            EnemyType::FollowPlayer(timer) => vec![("shoot", timer)],
+            EnemyType::Satellite(satellite) => vec![("shoot", &mut satellite.shoot_timer)],
+            EnemyType::Boss1(Boss1 {
+                shoot_timer,
+                salve_timer,
+                ..
+            })
+            | EnemyType::LastBoss(LastBoss {
+                shoot_timer,
+                salve_timer,
+                ..
+            })
+            | EnemyType::Spammer(Spammer {
+                shoot_timer,
+                salve_timer,
+                ..
+            }) => vec![("shoot", shoot_timer), ("salve", salve_timer)],
+            EnemyType::Carrier {
+                time_between_deploy,
+                ..
+            } => vec![("deploy", time_between_deploy)],
+            EnemyType::MineLander(timer) => vec![("mine", timer)],
+            EnemyType::Wanderer(timer) => vec![("shoot", timer)],
+            EnemyType::Mine {
+                explosion_timer, ..
+            } => vec![("explosion", explosion_timer)],
+            EnemyType::Kamikaze => vec![],
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum MovementBehavior {
     /// Move toward the player. Avoid basic obstacles
@@ -236,7 +275,7 @@ impl Route {
     }
 
     pub fn debug_draw(&self, resources: &Resources) {
-        if self.path.len() == 0 {
+        if !debug::ENABLED || self.path.len() == 0 {
             return;
         }
         //
@@ -244,7 +283,7 @@ impl Route {
             let p1 = self.path[i];
             let p2 = self.path[i + 1];
 
-            debug::stroke_line(resources, p1, p2, colors::GREEN);
+            debug::stroke_line(resources, DebugCategory::AI, p1, p2, colors::GREEN);
         }
     }
 }
@@ -257,13 +296,15 @@ pub fn update_enemies(world: &mut World, resources: &Resources, dt: Duration) {
     }
     let player = maybe_player.unwrap();
 
-    let mut ev_channel = resources.fetch_mut::<EventChannel<GameEvent>>().unwrap();
+    let mut audio_channel = resources.fetch_mut::<Events<AudioEvent>>().unwrap();
 
     // prefabs to spawn.
     let mut to_spawn: Vec<(String, glam::Vec2)> = vec![];
     let mut spaceship_to_spawn = vec![];
     let mut bullets = vec![];
     let mut to_remove = vec![];
+    let mut explosions = vec![];
+    let mut deaths = vec![];
     let mut missiles = vec![];
 
     let maybe_player = world
@@ -289,8 +330,8 @@ pub fn update_enemies(world: &mut World, resources: &Resources, dt: Duration) {
             match enemy.enemy_type {
                 EnemyType::Kamikaze => {
                     if (t.translation - player_position).length() < 60.0 {
-                        to_remove.push(GameEvent::Delete(e));
-                        to_remove.push(GameEvent::Explosion(
+                        to_remove.push(Delete(e));
+                        explosions.push(Explode(
                             e,
                             ExplosionDetails {
                                 radius: 100.0,
@@ -298,7 +339,7 @@ pub fn update_enemies(world: &mut World, resources: &Resources, dt: Duration) {
                             },
                             t.translation,
                         ));
-                        to_remove.push(GameEvent::EnemyDied(
+                        deaths.push(GameEvent::EnemyDied(
                             e,
                             t.translation,
                             enemy.scrap_drop,
@@ -335,8 +376,8 @@ pub fn update_enemies(world: &mut World, resources: &Resources, dt: Duration) {
                                     BulletType::Fast,
                                 ));
                             }
-                            ev_channel.single_write(GameEvent::PlaySound(
-                                "sounds/scifi_kit/Laser/Laser_04.wav".to_string(),
+                            audio_channel.send(AudioEvent::PlaySound(
+                                Sound::new("enemy_shoot").at(t.translation),
                             ));
 
                             boss.current_shot += 1;
@@ -378,8 +419,8 @@ pub fn update_enemies(world: &mut World, resources: &Resources, dt: Duration) {
                                 glam::Mat2::from_angle(std::f32::consts::FRAC_PI_3) * d,
                                 BulletType::Round1,
                             ));
-                            ev_channel.single_write(GameEvent::PlaySound(
-                                "sounds/scifi_kit/Laser/Laser_04.wav".to_string(),
+                            audio_channel.send(AudioEvent::PlaySound(
+                                Sound::new("enemy_shoot").at(t.translation),
                             ));
 
                             spammer.current_shot += 1;
@@ -401,8 +442,8 @@ pub fn update_enemies(world: &mut World, resources: &Resources, dt: Duration) {
                             // shoot.
                             let to_spawn = (t.translation, dir.normalize(), BulletType::Round2);
                             bullets.push(to_spawn);
-                            ev_channel.single_write(GameEvent::PlaySound(
-                                "sounds/scifi_kit/Laser/Laser_03.wav".to_string(),
+                            audio_channel.send(AudioEvent::PlaySound(
+                                Sound::new("boss_shoot").at(t.translation),
                             ));
 
                             boss1.current_shot += 1;
@@ -437,15 +478,23 @@ pub fn update_enemies(world: &mut World, resources: &Resources, dt: Duration) {
                         if shoot_timer.finished() {
                             shoot_timer.reset();
                             let to_spawn = (t.translation, dir.normalize(), BulletType::Round2);
-                            ev_channel.single_write(GameEvent::PlaySound(
-                                "sounds/scifi_kit/Laser/Laser_04.wav".to_string(),
+                            audio_channel.send(AudioEvent::PlaySound(
+                                Sound::new("enemy_shoot").at(t.translation),
                             ));
                             bullets.push(to_spawn);
                         }
                     }
 
                     // Draw stuff to the screen.
-                    debug::stroke_circle(resources, t.translation, 1500.0, colors::RED);
+                    if debug::ENABLED {
+                        debug::stroke_circle(
+                            resources,
+                            DebugCategory::AI,
+                            t.translation,
+                            1500.0,
+                            colors::RED,
+                        );
+                    }
                 }
                 EnemyType::Wanderer(ref mut timer) => {
                     timer.tick(dt);
@@ -468,8 +517,8 @@ pub fn update_enemies(world: &mut World, resources: &Resources, dt: Duration) {
                             glam::Mat2::from_angle(3.0 * std::f32::consts::FRAC_PI_2) * d,
                             BulletType::Round1,
                         ));
-                        ev_channel.single_write(GameEvent::PlaySound(
-                            "sounds/scifi_kit/Laser/Laser_04.wav".to_string(),
+                        audio_channel.send(AudioEvent::PlaySound(
+                            Sound::new("enemy_shoot").at(t.translation),
                         ));
                     }
                 }
@@ -494,8 +543,8 @@ pub fn update_enemies(world: &mut World, resources: &Resources, dt: Duration) {
                         explosion_timer.tick(dt);
                         if explosion_timer.finished() {
                             // badaboum
-                            to_remove.push(GameEvent::Delete(e));
-                            to_remove.push(GameEvent::Explosion(
+                            to_remove.push(Delete(e));
+                            explosions.push(Explode(
                                 e,
                                 ExplosionDetails {
                                     radius: trigger_distance,
@@ -504,24 +553,30 @@ pub fn update_enemies(world: &mut World, resources: &Resources, dt: Duration) {
                                 t.translation,
                             ));
                         }
-                        debug::stroke_circle(
-                            resources,
-                            t.translation,
-                            trigger_distance,
-                            colors::GREEN,
-                        );
+                        if debug::ENABLED {
+                            debug::stroke_circle(
+                                resources,
+                                DebugCategory::AI,
+                                t.translation,
+                                trigger_distance,
+                                colors::GREEN,
+                            );
+                        }
                     } else {
                         if (player_position - t.translation).length() < trigger_distance {
                             // BOOM
                             explosion_timer.reset();
                             explosion_timer.start();
                         }
-                        debug::stroke_circle(
-                            resources,
-                            t.translation,
-                            trigger_distance,
-                            colors::RED,
-                        );
+                        if debug::ENABLED {
+                            debug::stroke_circle(
+                                resources,
+                                DebugCategory::AI,
+                                t.translation,
+                                trigger_distance,
+                                colors::RED,
+                            );
+                        }
                     }
                 }
             }
@@ -583,6 +638,48 @@ pub fn update_enemies(world: &mut World, resources: &Resources, dt: Duration) {
             }
         }
     }
-    ev_channel.drain_vec_write(&mut to_remove);
+    drop(audio_channel);
+    resources
+        .fetch_mut::<Events<Delete>>()
+        .unwrap()
+        .defer_all(to_remove);
+    resources
+        .fetch_mut::<Events<Explode>>()
+        .unwrap()
+        .send_all(explosions);
+    resources
+        .fetch_mut::<Events<GameEvent>>()
+        .unwrap()
+        .defer_all(deaths);
     trace!("Finished update_enemies")
 }
+
+pub struct EnemySystem;
+
+impl System for EnemySystem {
+    fn name(&self) -> &'static str {
+        "enemies"
+    }
+
+    fn access(&self) -> Access {
+        Access::new()
+            .write::<Events<AudioEvent>>()
+            .write::<Events<Delete>>()
+            .write::<Events<Explode>>()
+            .write::<Events<GameEvent>>()
+            .write::<PrefabManager<GlfwSurface>>()
+            .write::<RandomGenerator>()
+            .read::<CollisionWorld>()
+            .write::<DebugQueue>()
+            .write_component::<Transform>()
+            .write_component::<Enemy>()
+            .write_component::<DynamicBody>()
+            .write_component::<AnimationController>()
+            .read_component::<Player>()
+            .structural()
+    }
+
+    fn run(&mut self, world: &mut World, resources: &Resources, dt: Duration) {
+        update_enemies(world, resources, dt);
+    }
+}
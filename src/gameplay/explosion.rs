@@ -1,18 +1,24 @@
 //! EXPLODE STUFF !
 
 use crate::core::animation::{Animation, AnimationController};
+use crate::core::audio::Sound;
 use crate::core::colors;
+use crate::core::events::{register_reader, EventReader, Events};
+use crate::core::lifetime::TimeToLive;
+use crate::core::system::{Access, System};
 use crate::core::transform::Transform;
-use crate::event::GameEvent;
+use crate::event::{AudioEvent, Explode, Hit};
 use crate::gameplay::collision::CollisionWorld;
 use crate::gameplay::health::HitDetails;
 use crate::gameplay::physics::DynamicBody;
 use crate::render::path::debug;
+use crate::render::path::debug::{DebugCategory, DebugQueue};
 use crate::render::sprite::Sprite;
 use crate::resources::Resources;
+use hecs::World;
 use serde_derive::{Deserialize, Serialize};
-use shrev::{EventChannel, ReaderId};
 use std::collections::HashMap;
+use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Explosive;
@@ -33,33 +39,31 @@ pub struct ExplosionDetails {
 }
 
 pub struct ExplosionSystem {
-    rdr_id: ReaderId<GameEvent>,
+    rdr_id: EventReader<Explode>,
 }
 
 impl ExplosionSystem {
     pub fn new(resources: &mut Resources) -> Self {
-        let mut channel = resources.fetch_mut::<EventChannel<GameEvent>>().unwrap();
-
         Self {
-            rdr_id: channel.register_reader(),
+            rdr_id: register_reader(resources, "explosions"),
         }
     }
 
     pub fn update(&mut self, world: &mut hecs::World, resources: &Resources) {
-        let mut channel = resources.fetch_mut::<EventChannel<GameEvent>>().unwrap();
         let collision_world = resources.fetch::<CollisionWorld>().unwrap();
 
-        let mut explosions = vec![];
-        for ev in channel.read(&mut self.rdr_id) {
-            if let GameEvent::Explosion(e, explosion, pos) = ev {
-                explosions.push((*e, *explosion, *pos));
-            }
-        }
+        let explosions: Vec<_> = resources
+            .fetch::<Events<Explode>>()
+            .unwrap()
+            .read(&mut self.rdr_id)
+            .map(|Explode(e, explosion, pos)| (*e, *explosion, *pos))
+            .collect();
 
-        let mut events = vec![];
+        let mut sounds = vec![];
+        let mut hits = vec![];
         for (entity, explosion, pos) in explosions {
             // play the sound, show the animation, then query who is hit by this explosion.
-            events.push(GameEvent::PlaySound("sounds/explosion.wav".to_string()));
+            sounds.push(AudioEvent::PlaySound(Sound::new("explosion").at(pos)));
             match explosion.ty {
                 ExplosionType::First => {
                     spawn_explosion(world, pos, explosion.radius * glam::Vec2::one())
@@ -86,15 +90,18 @@ impl ExplosionSystem {
 
                 if let Some((t, body)) = query.get() {
                     let force = (t.translation - pos).normalize() * 500.0;
-                    debug::stroke_line(
-                        resources,
-                        t.translation,
-                        t.translation + force,
-                        colors::RED,
-                    );
+                    if debug::ENABLED {
+                        debug::arrow(
+                            resources,
+                            DebugCategory::PHYSICS,
+                            t.translation,
+                            t.translation + force,
+                            colors::RED,
+                        );
+                    }
                     body.add_impulse(force / body.mass);
 
-                    events.push(GameEvent::Hit(
+                    hits.push(Hit(
                         e,
                         HitDetails {
                             hit_points: 2.0,
@@ -107,11 +114,11 @@ impl ExplosionSystem {
             }
         }
 
-        channel.drain_vec_write(&mut events);
-
-        //ev_channel.single_write(GameEvent::PlaySound(
-        //                                 "sounds/explosion.wav".to_string(),
-        //                             ));
+        resources
+            .fetch_mut::<Events<AudioEvent>>()
+            .unwrap()
+            .send_all(sounds);
+        resources.fetch_mut::<Events<Hit>>().unwrap().send_all(hits);
     }
 }
 
@@ -148,13 +155,15 @@ pub fn spawn_explosion(world: &mut hecs::World, position: glam::Vec2, scale: gla
         ]),
     );
 
+    let duration = animations["boum"].duration();
     let animation_controller = AnimationController {
         animations,
         current_animation: Some("boum".to_string()),
-        delete_on_finished: true,
+        play_once: true,
     };
 
     builder.add(animation_controller);
+    builder.add(TimeToLive::of_seconds(duration.as_secs_f32()));
     builder.add(Sprite {
         id: String::from("explosion4/k2_0001.png"),
     });
@@ -195,16 +204,39 @@ pub fn spawn_explosion_2(world: &mut hecs::World, position: glam::Vec2, scale: g
         ]),
     );
 
+    let duration = animations["boum"].duration();
     let animation_controller = AnimationController {
         animations,
         current_animation: Some("boum".to_string()),
-        delete_on_finished: true,
+        play_once: true,
     };
 
     builder.add(animation_controller);
+    builder.add(TimeToLive::of_seconds(duration.as_secs_f32()));
     builder.add(Sprite {
         id: String::from("explosion4/h_0001.png"),
     });
 
     world.spawn(builder.build());
 }
+
+impl System for ExplosionSystem {
+    fn name(&self) -> &'static str {
+        "explosions"
+    }
+
+    fn access(&self) -> Access {
+        Access::new()
+            .read::<Events<Explode>>()
+            .write::<Events<AudioEvent>>()
+            .write::<Events<Hit>>()
+            .read::<CollisionWorld>()
+            .write::<DebugQueue>()
+            .write_component::<DynamicBody>()
+            .structural()
+    }
+
+    fn run(&mut self, world: &mut World, resources: &Resources, _dt: Duration) {
+        self.update(world, resources);
+    }
+}
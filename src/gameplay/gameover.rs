@@ -1,20 +1,19 @@
+use crate::core::events::{register_reader, EventReader, Events};
 use crate::event::GameEvent;
 use crate::resources::Resources;
-use shrev::{EventChannel, ReaderId};
 
 pub struct GameOver {
-    rdr_id: ReaderId<GameEvent>,
+    rdr_id: EventReader<GameEvent>,
 }
 
 impl GameOver {
     pub fn new(resources: &mut Resources) -> Self {
-        let mut chan = resources.fetch_mut::<EventChannel<GameEvent>>().unwrap();
-        let rdr_id = chan.register_reader();
+        let rdr_id = register_reader(resources, "game_over");
         Self { rdr_id }
     }
 
     pub fn game_over(&mut self, resources: &Resources) -> bool {
-        let chan = resources.fetch::<EventChannel<GameEvent>>().unwrap();
+        let chan = resources.fetch::<Events<GameEvent>>().unwrap();
         for ev in chan.read(&mut self.rdr_id) {
             if let GameEvent::GameOver = ev {
                 return true;
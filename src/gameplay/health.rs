@@ -1,15 +1,16 @@
+use crate::core::effect::ParticleEffect;
+use crate::core::events::{register_reader, EventReader, Events};
+use crate::core::system::{Access, System};
 use crate::core::timer::Timer;
 use crate::core::transform::Transform;
-use crate::event::GameEvent;
+use crate::event::{Delete, GameEvent, Hit};
 use crate::gameplay::enemy::{Enemy, EnemyType};
 use crate::gameplay::player::Player;
-use crate::render::particle::ParticleEmitter;
 use crate::render::sprite::Blink;
 use crate::resources::Resources;
+use hecs::World;
 use log::{debug, trace};
 use serde_derive::{Deserialize, Serialize};
-use shrev::{EventChannel, ReaderId};
-use std::path::PathBuf;
 use std::time::Duration;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -27,7 +28,8 @@ pub struct Health {
 
     /// true if can hit the component.
     hittable: bool,
-    invulnerability_timer: Timer,
+    /// Time after a hit during which the entity cannot be hit again.
+    pub invulnerability_timer: Timer,
 }
 
 impl Default for Health {
@@ -67,10 +69,10 @@ pub struct Shield {
     pub current: f32,
 
     /// Timer until the shield will start to refill
-    timer_until_replenish: Timer,
+    pub timer_until_replenish: Timer,
 
     /// Amount of shield entity get back per second.
-    replenish_rate: f32,
+    pub replenish_rate: f32,
 }
 
 impl Shield {
@@ -91,138 +93,153 @@ pub struct HitDetails {
 }
 
 pub struct HealthSystem {
-    rdr_id: ReaderId<GameEvent>,
-
-    /// TODO put somewhere else.
-    explosion: ParticleEmitter,
+    rdr_id: EventReader<Hit>,
 }
 
 impl HealthSystem {
     pub fn new(resources: &mut Resources) -> Self {
-        let base_path = std::env::var("ASSET_PATH").unwrap_or("assets/".to_string());
-        let mut emitter: ParticleEmitter = serde_json::from_str(
-            &std::fs::read_to_string(PathBuf::from(base_path).join("particle/explosion.json"))
-                .unwrap(),
-        )
-        .unwrap();
-        emitter.init_pool();
-
-        let mut chan = resources.fetch_mut::<EventChannel<GameEvent>>().unwrap();
-        let rdr_id = chan.register_reader();
-        Self {
-            rdr_id,
-            explosion: emitter,
-        }
+        let rdr_id = register_reader(resources, "health");
+        Self { rdr_id }
     }
 
     pub fn update(&mut self, world: &mut hecs::World, resources: &Resources, dt: Duration) {
         trace!("Update HealthSystem");
-        let mut chan = resources.fetch_mut::<EventChannel<GameEvent>>().unwrap();
+        let chan = resources.fetch::<Events<Hit>>().unwrap();
         let mut death_events = vec![];
+        let mut deletes = vec![];
+        let mut effects = vec![];
 
         // FIRST, PROCESS ALL EVENTS TO SEE IF ANYBODY GOT HIT
         // ----------------------------------------------------
-        for ev in chan.read(&mut self.rdr_id) {
-            if let GameEvent::Hit(e, hit_details) = ev {
-                debug!("Process HIT event for {:?}", e);
-                let mut hit_points = hit_details.hit_points;
-                let mut explosion = false;
-                let mut insert_blink = false;
-                {
-                    let invulnerable = world.get::<Invulnerable>(*e);
-                    if invulnerable.is_ok() {
-                        continue;
-                    }
+        for Hit(e, hit_details) in chan.read(&mut self.rdr_id) {
+            debug!("Process HIT event for {:?}", e);
+            let mut hit_points = hit_details.hit_points;
+            let mut explosion = false;
+            let mut shield_hit = false;
+            let mut insert_blink = false;
+            {
+                let invulnerable = world.get::<Invulnerable>(*e);
+                if invulnerable.is_ok() {
+                    continue;
+                }
 
-                    let health = world.get_mut::<Health>(*e);
-                    let shield = world.get_mut::<Shield>(*e);
-                    let t = world.get::<Transform>(*e);
-                    if t.is_err() {
-                        continue;
-                    }
-                    let t = t.unwrap();
-
-                    let enemy_drop = if let Ok(mut enemy) = world.get_mut::<Enemy>(*e) {
-                        // if should explode on contact. BOUM
-                        if let EnemyType::Mine {
-                            ref mut explosion_timer,
-                            ..
-                        } = enemy.enemy_type
-                        {
-                            explosion_timer.start();
-                        }
+                let health = world.get_mut::<Health>(*e);
+                let shield = world.get_mut::<Shield>(*e);
+                let t = world.get::<Transform>(*e);
+                if t.is_err() {
+                    continue;
+                }
+                let t = t.unwrap();
 
-                        Some((t.translation, enemy.scrap_drop, enemy.pickup_drop_percent))
-                    } else {
-                        None
-                    };
-
-                    if let Ok(mut shield) = shield {
-                        // reset shield timer. Shield cannot recharge until elapsed.
-                        shield.timer_until_replenish.reset();
-                        shield.timer_until_replenish.start();
-                        if shield.current != 0.0 {
-                            if shield.current > hit_points {
-                                shield.current -= hit_points;
-                                hit_points = 0.0;
-                            } else {
-                                hit_points -= shield.current;
-                                shield.current = 0.0;
-                            }
-                        }
+                if hit_details.is_crit {
+                    effects.push(ParticleEffect::new("crit").at(t.translation));
+                }
+
+                let enemy_drop = if let Ok(mut enemy) = world.get_mut::<Enemy>(*e) {
+                    // if should explode on contact. BOUM
+                    if let EnemyType::Mine {
+                        ref mut explosion_timer,
+                        ..
+                    } = enemy.enemy_type
+                    {
+                        explosion_timer.start();
                     }
 
-                    // if no shield, then we can hit the health.
-                    if hit_points > 0.0 {
-                        if let Ok(mut health) = health {
-                            if !health.hittable {
-                                continue;
-                            }
-
-                            health.current -= hit_points;
-                            if health.is_dead() {
-                                debug!("{:?} is dead ({:?}", e, *health);
-                                Self::add_death_events(&mut death_events, world, *e, enemy_drop);
-                                explosion = true;
-                            } else {
-                                // start invulnerability frames.
-                                health.hittable = false;
-                                health.invulnerability_timer.reset();
-                                health.invulnerability_timer.start();
-                                insert_blink = true;
-                            }
+                    Some((t.translation, enemy.scrap_drop, enemy.pickup_drop_percent))
+                } else {
+                    None
+                };
+
+                if let Ok(mut shield) = shield {
+                    // reset shield timer. Shield cannot recharge until elapsed.
+                    shield.timer_until_replenish.reset();
+                    shield.timer_until_replenish.start();
+                    if shield.current != 0.0 {
+                        shield_hit = true;
+                        if shield.current > hit_points {
+                            shield.current -= hit_points;
+                            hit_points = 0.0;
                         } else {
-                            // no shield, no health,  you're dead boy.
-                            Self::add_death_events(&mut death_events, world, *e, enemy_drop);
-                            explosion = true;
+                            hit_points -= shield.current;
+                            shield.current = 0.0;
                         }
                     }
                 }
 
-                if explosion {
-                    let transform = { world.get::<Transform>(*e).unwrap().translation }; // no sense if no transform..
-                    self.make_explosion(world, transform);
-                }
+                // if no shield, then we can hit the health.
+                if hit_points > 0.0 {
+                    if let Ok(mut health) = health {
+                        if !health.hittable {
+                            continue;
+                        }
 
-                if insert_blink {
-                    debug!("WIll insert blink");
-                    world
-                        .insert(
+                        health.current -= hit_points;
+                        if health.is_dead() {
+                            debug!("{:?} is dead ({:?}", e, *health);
+                            Self::add_death_events(
+                                &mut death_events,
+                                &mut deletes,
+                                world,
+                                *e,
+                                enemy_drop,
+                            );
+                            explosion = true;
+                        } else {
+                            // start invulnerability frames.
+                            health.hittable = false;
+                            health.invulnerability_timer.reset();
+                            health.invulnerability_timer.start();
+                            insert_blink = true;
+                        }
+                    } else {
+                        // no shield, no health,  you're dead boy.
+                        Self::add_death_events(
+                            &mut death_events,
+                            &mut deletes,
+                            world,
                             *e,
-                            (Blink {
-                                color: [1.0, 0.0, 0.0, 1.0],
-                                amplitude: 10.0,
-                            },),
-                        )
-                        .unwrap();
+                            enemy_drop,
+                        );
+                        explosion = true;
+                    }
                 }
             }
+
+            if explosion {
+                let transform = { world.get::<Transform>(*e).unwrap().translation }; // no sense if no transform..
+                effects.push(ParticleEffect::new("explosion").at(transform));
+            } else if shield_hit {
+                effects.push(ParticleEffect::new("shield_hit").attached_to(*e));
+            }
+
+            if insert_blink {
+                debug!("WIll insert blink");
+                world
+                    .insert(
+                        *e,
+                        (Blink {
+                            color: [1.0, 0.0, 0.0, 1.0],
+                            amplitude: 10.0,
+                        },),
+                    )
+                    .unwrap();
+            }
         }
 
+        drop(chan);
         if !death_events.is_empty() {
             debug!("WIll publish {:?}", death_events);
-            chan.drain_vec_write(&mut death_events);
+            let mut chan = resources.fetch_mut::<Events<GameEvent>>().unwrap();
+            chan.defer_all(death_events);
         }
+        resources
+            .fetch_mut::<Events<Delete>>()
+            .unwrap()
+            .defer_all(deletes);
+        resources
+            .fetch_mut::<Events<ParticleEffect>>()
+            .unwrap()
+            .send_all(effects);
 
         // THEN, UPDATE INVULNERABILY TIMERS.
         // ----------------------------------------------------
@@ -256,6 +273,7 @@ impl HealthSystem {
 
     fn add_death_events(
         death_events: &mut Vec<GameEvent>,
+        deletes: &mut Vec<Delete>,
         world: &hecs::World,
         entity: hecs::Entity,
         is_enemy: Option<(glam::Vec2, (u32, u32), u8)>,
@@ -264,23 +282,36 @@ impl HealthSystem {
         if world.get::<Player>(entity).is_ok() {
             death_events.push(GameEvent::GameOver);
         } else {
-            death_events.push(GameEvent::Delete(entity));
+            deletes.push(Delete(entity));
         }
 
         if let Some(drop) = is_enemy {
             death_events.push(GameEvent::EnemyDied(entity, drop.0, drop.1, drop.2));
         }
     }
+}
+
+impl System for HealthSystem {
+    fn name(&self) -> &'static str {
+        "health"
+    }
+
+    fn access(&self) -> Access {
+        Access::new()
+            .read::<Events<Hit>>()
+            .write::<Events<GameEvent>>()
+            .write::<Events<Delete>>()
+            .write::<Events<ParticleEffect>>()
+            .read_component::<Invulnerable>()
+            .read_component::<Transform>()
+            .read_component::<Player>()
+            .write_component::<Health>()
+            .write_component::<Shield>()
+            .write_component::<Enemy>()
+            .structural()
+    }
 
-    fn make_explosion(&self, world: &mut hecs::World, pos: glam::Vec2) {
-        world.spawn((
-            Transform {
-                translation: pos,
-                rotation: 0.0,
-                scale: glam::Vec2::one(),
-                dirty: false,
-            },
-            self.explosion.clone(),
-        ));
+    fn run(&mut self, world: &mut World, resources: &Resources, dt: Duration) {
+        self.update(world, resources, dt);
     }
 }
@@ -0,0 +1,360 @@
+//! Debug overlay to inspect and tweak the entities while the game is running. It is opened with
+//! F9 when `inspector` is set in the engine config.
+//!
+//! The list shows the entities that have a transform. The components of the selected entity
+//! can be edited (type a value then press Enter) and the entity can be saved as a prefab in
+//! assets/prefab/.
+use crate::config::save_config;
+use crate::core::colors::RgbaColor;
+use crate::core::transform::{HasParent, Transform};
+use crate::gameplay::enemy::Enemy;
+use crate::gameplay::health::{Health, Shield};
+use crate::gameplay::physics::DynamicBody;
+use crate::gameplay::player::Player;
+use crate::render::ui::{Gui, ListState, ScrollList, Slider, TextInput};
+use hecs::{Entity, World};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+const WIDTH: f32 = 400.0;
+const MARGIN: f32 = 10.0;
+const FONT_SIZE: f32 = 14.0;
+const ROW_HEIGHT: f32 = 24.0;
+const LIST_HEIGHT: f32 = 200.0;
+/// Horizontal position of the fields relative to the labels.
+const FIELD_OFFSET: f32 = 150.0;
+const FIELD_WIDTH: f32 = 110.0;
+
+#[derive(Default)]
+pub struct Inspector {
+    pub visible: bool,
+    list_state: ListState,
+    selected: Option<Entity>,
+    /// Text of the fields being edited, by widget id.
+    fields: HashMap<String, String>,
+    prefab_name: String,
+    /// Component being displayed. Used for the ids of the widgets.
+    section: &'static str,
+    /// Result of the last save.
+    status: Option<(String, RgbaColor)>,
+}
+
+impl Inspector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn prepare_gui(&mut self, world: &mut World, gui: &mut Gui) {
+        let origin = glam::vec2(gui.window_dim.width as f32 - WIDTH, 0.0);
+        let height = gui.window_dim.height as f32;
+        gui.panel(
+            origin,
+            glam::vec2(WIDTH, height),
+            RgbaColor::new(0, 0, 0, 200),
+        );
+
+        let x = origin.x() + MARGIN;
+        let mut y = MARGIN;
+        gui.sized_label(
+            glam::vec2(x, y),
+            "Inspector (F9)".to_string(),
+            FONT_SIZE,
+            gui.style.widget_fg_color,
+        );
+        y += ROW_HEIGHT;
+
+        // Entity list
+        // -----------------------------------------------
+        let mut entities: Vec<(Entity, String)> = world
+            .query::<&Transform>()
+            .iter()
+            .map(|(e, _)| (e, entity_name(world, e)))
+            .collect();
+        entities.sort_by_key(|(e, _)| e.id());
+        if self.selected.map(|e| !world.contains(e)).unwrap_or(false) {
+            self.selected = None;
+        }
+        self.list_state.selected = self
+            .selected
+            .and_then(|selected| entities.iter().position(|(e, _)| *e == selected));
+
+        let items: Vec<String> = entities.iter().map(|(_, name)| name.clone()).collect();
+        if let Some(idx) = ScrollList::new(
+            "inspector_entities",
+            glam::vec2(x, y),
+            glam::vec2(WIDTH - 2.0 * MARGIN, LIST_HEIGHT),
+            ROW_HEIGHT,
+        )
+        .set_font_size(FONT_SIZE)
+        .build(gui, &items, &mut self.list_state)
+        {
+            self.selected = Some(entities[idx].0);
+            self.prefab_name = items[idx].to_lowercase().replace(' ', "_");
+            self.status = None;
+        }
+        y += LIST_HEIGHT + MARGIN;
+
+        let entity = match self.selected {
+            Some(e) => e,
+            None => return,
+        };
+
+        // Components
+        // -----------------------------------------------
+        if let Ok(mut t) = world.get_mut::<Transform>(entity) {
+            self.section(gui, x, &mut y, "Transform");
+            let mut changed = self.vec2_row(gui, x, &mut y, "position", &mut t.translation);
+            changed |= self.row(gui, x, &mut y, "rotation", &mut t.rotation);
+            changed |= self.vec2_row(gui, x, &mut y, "scale", &mut t.scale);
+            if changed {
+                t.dirty = true;
+            }
+        }
+
+        if let Ok(mut body) = world.get_mut::<DynamicBody>(entity) {
+            self.section(gui, x, &mut y, "DynamicBody");
+            self.vec2_row(gui, x, &mut y, "velocity", &mut body.velocity);
+            self.row(gui, x, &mut y, "max_velocity", &mut body.max_velocity);
+            self.row(gui, x, &mut y, "mass", &mut body.mass);
+            self.row(gui, x, &mut y, "max_force", &mut body.max_force);
+        }
+
+        if let Ok(mut health) = world.get_mut::<Health>(entity) {
+            self.section(gui, x, &mut y, "Health");
+            let max = health.max;
+            self.slider_row(gui, x, &mut y, "current", &mut health.current, max);
+            self.row(gui, x, &mut y, "max", &mut health.max);
+            self.timer_row(
+                gui,
+                x,
+                &mut y,
+                "invulnerability",
+                &mut health.invulnerability_timer,
+            );
+        }
+
+        if let Ok(mut shield) = world.get_mut::<Shield>(entity) {
+            self.section(gui, x, &mut y, "Shield");
+            let max = shield.max;
+            self.slider_row(gui, x, &mut y, "current", &mut shield.current, max);
+            self.row(gui, x, &mut y, "max", &mut shield.max);
+            self.row(gui, x, &mut y, "replenish_rate", &mut shield.replenish_rate);
+            self.timer_row(
+                gui,
+                x,
+                &mut y,
+                "replenish_delay",
+                &mut shield.timer_until_replenish,
+            );
+        }
+
+        if let Ok(mut enemy) = world.get_mut::<Enemy>(entity) {
+            self.section(gui, x, &mut y, "Enemy timers");
+            for (name, timer) in enemy.timers_mut() {
+                self.timer_row(gui, x, &mut y, name, timer);
+            }
+        }
+
+        // Save as prefab
+        // -----------------------------------------------
+        y += MARGIN;
+        TextInput::new("inspector_prefab_name", glam::vec2(x, y), 2.0 * FIELD_WIDTH)
+            .set_placeholder("prefab name".to_string())
+            .set_font_size(FONT_SIZE)
+            .build(gui, &mut self.prefab_name);
+        if gui.button(
+            glam::vec2(x + 2.0 * FIELD_WIDTH + MARGIN, y),
+            None,
+            "Save prefab".to_string(),
+        ) {
+            self.status = Some(match self.save_prefab(world, entity) {
+                Ok(path) => (
+                    format!("Saved to {}", path.display()),
+                    RgbaColor::new(0, 255, 0, 255),
+                ),
+                Err(e) => (
+                    format!("Cannot save: {}", e),
+                    RgbaColor::new(255, 0, 0, 255),
+                ),
+            });
+        }
+        y += ROW_HEIGHT + MARGIN;
+
+        if let Some((status, color)) = &self.status {
+            gui.sized_label(glam::vec2(x, y), status.clone(), FONT_SIZE, *color);
+        }
+    }
+
+    fn save_prefab(
+        &self,
+        world: &World,
+        entity: Entity,
+    ) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let name = self.prefab_name.trim();
+        if name.is_empty() {
+            return Err("the prefab needs a name".into());
+        }
+        let description = crate::prefab::component_registry().describe(world, entity)?;
+        let base_path = std::env::var("ASSET_PATH").unwrap_or("".to_string());
+        let path = PathBuf::from(base_path)
+            .join("assets/prefab")
+            .join(name)
+            .with_extension("json");
+        save_config(&path, &description)?;
+        info!("Saved {:?} as prefab at {}", entity, path.display());
+        Ok(path)
+    }
+
+    fn section(&mut self, gui: &mut Gui, x: f32, y: &mut f32, title: &'static str) {
+        self.section = title;
+        *y += MARGIN;
+        gui.sized_label(
+            glam::vec2(x, *y),
+            title.to_string(),
+            FONT_SIZE,
+            gui.style.widget_fg_color,
+        );
+        *y += ROW_HEIGHT;
+    }
+
+    fn widget_id(&self, name: &str) -> String {
+        format!("inspector_{}_{}", self.section, name)
+    }
+
+    /// Returns true if the value has changed.
+    fn row(&mut self, gui: &mut Gui, x: f32, y: &mut f32, name: &str, value: &mut f32) -> bool {
+        label(gui, x, *y, name);
+        let id = self.widget_id(name);
+        let changed = self.number_field(gui, &id, glam::vec2(x + FIELD_OFFSET, *y), value);
+        *y += ROW_HEIGHT;
+        changed
+    }
+
+    fn vec2_row(
+        &mut self,
+        gui: &mut Gui,
+        x: f32,
+        y: &mut f32,
+        name: &str,
+        value: &mut glam::Vec2,
+    ) -> bool {
+        label(gui, x, *y, name);
+        let (mut vx, mut vy) = (value.x(), value.y());
+        let id = self.widget_id(name);
+        let mut changed = self.number_field(
+            gui,
+            &format!("{}_x", id),
+            glam::vec2(x + FIELD_OFFSET, *y),
+            &mut vx,
+        );
+        changed |= self.number_field(
+            gui,
+            &format!("{}_y", id),
+            glam::vec2(x + FIELD_OFFSET + FIELD_WIDTH + MARGIN, *y),
+            &mut vy,
+        );
+        if changed {
+            *value = glam::vec2(vx, vy);
+        }
+        *y += ROW_HEIGHT;
+        changed
+    }
+
+    fn slider_row(
+        &mut self,
+        gui: &mut Gui,
+        x: f32,
+        y: &mut f32,
+        name: &str,
+        value: &mut f32,
+        max: f32,
+    ) {
+        label(gui, x, *y, name);
+        let id = self.widget_id(name);
+        Slider::new(&id, glam::vec2(x + FIELD_OFFSET, *y), 0.0, max.max(1.0))
+            .dimensions(glam::vec2(FIELD_WIDTH, FONT_SIZE))
+            .show_value()
+            .set_font_size(FONT_SIZE)
+            .build(gui, value);
+        *y += ROW_HEIGHT;
+    }
+
+    /// The deadline can be edited. The remaining time is displayed next to it.
+    fn timer_row(
+        &mut self,
+        gui: &mut Gui,
+        x: f32,
+        y: &mut f32,
+        name: &str,
+        timer: &mut crate::core::timer::Timer,
+    ) {
+        let mut deadline = timer.deadline();
+        gui.sized_label(
+            glam::vec2(x + FIELD_OFFSET + FIELD_WIDTH + MARGIN, *y),
+            format!("{:.2}s left", timer.remaining().max(0.0)),
+            FONT_SIZE,
+            gui.style.disabled_text_color,
+        );
+        if self.row(gui, x, y, name, &mut deadline) {
+            timer.set_deadline(deadline);
+        }
+    }
+
+    /// Text field for a number. The value is updated when Enter is pressed. Returns true if the
+    /// value has changed.
+    fn number_field(&mut self, gui: &mut Gui, id: &str, pos: glam::Vec2, value: &mut f32) -> bool {
+        let mut text = if gui.is_focused(id) {
+            self.fields
+                .remove(id)
+                .unwrap_or_else(|| format!("{:.2}", value))
+        } else {
+            self.fields.remove(id);
+            format!("{:.2}", value)
+        };
+
+        let submitted = TextInput::new(id, pos, FIELD_WIDTH)
+            .set_font_size(FONT_SIZE)
+            .build(gui, &mut text);
+
+        if submitted {
+            match text.trim().parse::<f32>() {
+                Ok(v) => {
+                    *value = v;
+                    return true;
+                }
+                Err(e) => warn!("Cannot parse {} = {}", text, e),
+            }
+        } else if gui.is_focused(id) {
+            self.fields.insert(id.to_string(), text);
+        }
+
+        false
+    }
+}
+
+/// Name displayed in the entity list.
+fn entity_name(world: &World, e: Entity) -> String {
+    let kind = if world.get::<Player>(e).is_ok() {
+        "player"
+    } else if world.get::<Enemy>(e).is_ok() {
+        "enemy"
+    } else if world.get::<HasParent>(e).is_ok() {
+        "child"
+    } else {
+        "entity"
+    };
+    format!("{} {}", kind, e.id())
+}
+
+fn label(gui: &mut Gui, x: f32, y: f32, name: &str) {
+    gui.sized_label(
+        glam::vec2(x + MARGIN, y),
+        name.to_string(),
+        FONT_SIZE,
+        gui.style.text_color,
+    );
+}
@@ -1,7 +1,10 @@
+use crate::core::lifetime::StageBounds;
 use crate::core::noise::perlin::Perlin;
 use crate::core::random::RandomGenerator;
 use crate::core::timer::Timer;
 use crate::core::transform::Transform;
+use crate::core::window::WindowDim;
+use crate::gameplay::camera::CameraBounds;
 use crate::gameplay::collision::{BoundingBox, CollisionLayer};
 use crate::gameplay::physics::DynamicBody;
 use crate::gameplay::pickup::spawn_pickup;
@@ -17,11 +20,11 @@ const NB_BLOCKS_Y: u32 = 50;
 
 pub mod difficulty;
 pub mod wave;
+use crate::core::events::defer_event;
 use crate::event::GameEvent;
 use crate::gameplay::explosion::Explosion;
 use crate::gameplay::health::Invulnerable;
 use crate::gameplay::level::difficulty::DifficultyConfig;
-use shrev::EventChannel;
 use wave::{Wave, WaveDescription};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -35,6 +38,10 @@ pub struct StageDescription {
     pub next_stage: Option<String>,
 
     pub backgrounds: Vec<String>,
+
+    /// Area the camera can move in.
+    #[serde(default)]
+    pub camera_bounds: CameraBounds,
 }
 
 impl StageDescription {
@@ -65,6 +72,7 @@ impl StageDescription {
             .iter()
             .map(|s| s.to_string())
             .collect(),
+            camera_bounds: CameraBounds::default(),
         }
     }
 }
@@ -89,9 +97,19 @@ pub struct Stage {
 
     pub is_infinite: bool,
     pub wave_number: usize,
+    pub camera_bounds: CameraBounds,
 }
 
 impl Stage {
+    /// Area where the entities can be: the camera bounds, plus a window around them.
+    pub fn bounds(&self, window_dim: &WindowDim) -> StageBounds {
+        let margin = window_dim.to_vec2();
+        StageBounds {
+            min: self.camera_bounds.min - margin,
+            max: self.camera_bounds.max + margin,
+        }
+    }
+
     pub fn new(
         world: &mut hecs::World,
         resources: &Resources,
@@ -149,6 +167,7 @@ impl Stage {
             timer_between_stages: Timer::of_seconds(10.0),
             next_stage: stage_desc.next_stage,
             is_infinite: stage_desc.is_infinite,
+            camera_bounds: stage_desc.camera_bounds,
         }
     }
 
@@ -184,17 +203,16 @@ impl Stage {
     pub fn update(&mut self, world: &mut hecs::World, resources: &Resources, dt: Duration) {
         match (self.current_wave, self.next_wave) {
             (None, None) => {
-                let mut channel = resources.fetch_mut::<EventChannel<GameEvent>>().unwrap();
                 if let Some(next_stage) = self.next_stage.as_ref() {
                     // In this case, the stage is over !
                     self.finished = true;
                     self.timer_between_stages.tick(dt);
                     if self.timer_between_stages.finished() {
-                        channel.single_write(GameEvent::NextStage(next_stage.clone()));
+                        defer_event(resources, GameEvent::NextStage(next_stage.clone()));
                     }
                 } else {
                     // no more stages, the game is finished !
-                    channel.single_write(GameEvent::YouWin);
+                    defer_event(resources, GameEvent::YouWin);
                 }
             }
             (None, Some(next_wave)) => {
@@ -240,6 +258,47 @@ impl Stage {
         }
     }
 
+    /// Despawn the enemies of the current wave, or start the next wave right away if there is
+    /// none running. Returns false if there is no wave left in the stage.
+    pub fn skip_wave(&mut self, world: &mut hecs::World) -> bool {
+        match (self.current_wave, self.next_wave) {
+            (Some(idx), _) => {
+                if let Some(wave) = self.waves.get_mut(idx) {
+                    for e in wave.enemies.drain(..) {
+                        if let Err(e) = world.despawn(e) {
+                            error!("Error while despawning enemy = {:?}", e);
+                        }
+                    }
+                }
+                true
+            }
+            (None, Some(_)) => {
+                self.timer_between_waves.finish();
+                true
+            }
+            (None, None) => false,
+        }
+    }
+
+    /// Number of enemies that are still alive in the current wave. None between waves.
+    pub fn alive_enemies(&self) -> Option<usize> {
+        self.current_wave
+            .and_then(|idx| self.waves.get(idx))
+            .map(|wave| wave.enemies.len())
+    }
+
+    /// Between 0 and 1, how close the next wave is. None if a wave is running or if there is no
+    /// next wave.
+    pub fn next_wave_progress(&self) -> Option<f32> {
+        match (self.current_wave, self.next_wave) {
+            (None, Some(_)) => {
+                let deadline = self.timer_between_waves.deadline().max(f32::EPSILON);
+                Some((1.0 - self.timer_between_waves.remaining() / deadline).clamp(0.0, 1.0))
+            }
+            _ => None,
+        }
+    }
+
     pub fn display(&self) -> Option<String> {
         if self.finished {
             return Some("Brace for next stage".to_string());
@@ -63,7 +63,7 @@ impl Wave {
                 });
             } else {
                 error!(
-                    "Prefab {} should have been loaded in the loading scene",
+                    "Prefab {} should have been loaded before the scene",
                     prefab_name
                 );
             }
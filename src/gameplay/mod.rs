@@ -1,18 +1,22 @@
+use crate::core::input::gamepad::{AxisDirection, GamepadInput};
 use crate::core::input::InputAction;
-use glfw::{Key, MouseButton};
+use glfw::{GamepadAxis, GamepadButton, Key, MouseButton};
 use serde_derive::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 pub mod bullet;
 pub mod camera;
 pub mod collision;
+pub mod commands;
 pub mod delete;
 pub mod enemy;
 pub mod explosion;
 pub mod gameover;
 pub mod health;
+pub mod inspector;
 pub mod inventory;
 pub mod level;
+pub mod music;
 pub mod physics;
 pub mod pickup;
 pub mod player;
@@ -32,6 +36,22 @@ pub enum Action {
     Boost,
 }
 
+impl Action {
+    pub fn all() -> [Action; 9] {
+        [
+            Action::MoveUp,
+            Action::MoveDown,
+            Action::MoveLeft,
+            Action::MoveRight,
+            Action::RotateLeft,
+            Action::RotateRight,
+            Action::Shoot,
+            Action::Boost,
+            Action::Pickup,
+        ]
+    }
+}
+
 pub fn get_default_button_mapping() -> HashMap<Key, Action> {
     let mut m = HashMap::new();
     m.insert(Key::Up, Action::MoveUp);
@@ -52,6 +72,37 @@ pub fn get_default_mouse_mapping() -> HashMap<MouseButton, Action> {
     m.insert(MouseButton::Button1, Action::Shoot);
     m
 }
+pub fn get_default_gamepad_mapping() -> HashMap<GamepadInput, Action> {
+    let mut m = HashMap::new();
+    m.insert(
+        GamepadInput::Axis(GamepadAxis::AxisLeftY, AxisDirection::Negative),
+        Action::MoveUp,
+    );
+    m.insert(
+        GamepadInput::Axis(GamepadAxis::AxisLeftX, AxisDirection::Negative),
+        Action::MoveLeft,
+    );
+    m.insert(
+        GamepadInput::Axis(GamepadAxis::AxisLeftX, AxisDirection::Positive),
+        Action::MoveRight,
+    );
+    m.insert(
+        GamepadInput::Axis(GamepadAxis::AxisRightTrigger, AxisDirection::Positive),
+        Action::Shoot,
+    );
+    m.insert(GamepadInput::Button(GamepadButton::ButtonA), Action::Boost);
+    m.insert(GamepadInput::Button(GamepadButton::ButtonX), Action::Pickup);
+    m.insert(
+        GamepadInput::Button(GamepadButton::ButtonLeftBumper),
+        Action::RotateLeft,
+    );
+    m.insert(
+        GamepadInput::Button(GamepadButton::ButtonRightBumper),
+        Action::RotateRight,
+    );
+    m
+}
+
 impl InputAction for Action {
     fn get_default_key_mapping() -> HashMap<Key, Self> {
         get_default_button_mapping()
@@ -60,4 +111,8 @@ impl InputAction for Action {
     fn get_default_mouse_mapping() -> HashMap<MouseButton, Self> {
         get_default_mouse_mapping()
     }
+
+    fn get_default_gamepad_mapping() -> HashMap<GamepadInput, Self> {
+        get_default_gamepad_mapping()
+    }
 }
@@ -0,0 +1,48 @@
+use crate::gameplay::enemy::{Enemy, EnemyType};
+use crate::gameplay::health::Health;
+use crate::gameplay::level::Stage;
+use crate::gameplay::player::Player;
+use hecs::World;
+
+/// Number of enemies from which a wave is considered as intense as possible.
+const MAX_ENEMIES: f32 = 8.0;
+/// Under this health ratio, the player is in danger.
+const LOW_HEALTH: f32 = 0.3;
+
+/// Intensity of the game for the adaptive music, between 0 (calm) and 1 (boss fight).
+/// - Between waves, it slowly increases until the next wave starts,
+/// - During a wave, it depends on the number of enemies that are still alive,
+/// - It is at the maximum when a boss is alive,
+/// - It increases when the player is low on health.
+pub fn music_intensity(world: &World, stage: &Stage) -> f32 {
+    let has_boss = world.query::<&Enemy>().iter().any(|(_, enemy)| {
+        matches!(
+            enemy.enemy_type,
+            EnemyType::Boss1(_) | EnemyType::LastBoss(_)
+        )
+    });
+    if has_boss {
+        return 1.0;
+    }
+
+    let mut intensity = if let Some(alive) = stage.alive_enemies() {
+        0.4 + 0.4 * (alive as f32 / MAX_ENEMIES).min(1.0)
+    } else if let Some(progress) = stage.next_wave_progress() {
+        0.1 + 0.2 * progress
+    } else {
+        0.0
+    };
+
+    let health_ratio = world
+        .query::<(&Player, &Health)>()
+        .iter()
+        .map(|(_, (_, health))| health.current / health.max.max(f32::EPSILON))
+        .next();
+    if let Some(ratio) = health_ratio {
+        if ratio < LOW_HEALTH {
+            intensity += 0.2;
+        }
+    }
+
+    intensity.min(1.0)
+}
@@ -1,6 +1,8 @@
 use crate::core::colors;
+use crate::core::system::{Access, System};
 use crate::core::transform::Transform;
 use crate::render::path::debug;
+use crate::render::path::debug::{DebugCategory, DebugQueue};
 use crate::resources::Resources;
 use hecs::World;
 use serde_derive::{Deserialize, Serialize};
@@ -53,22 +55,29 @@ impl DynamicBody {
     }
 }
 
-#[derive(Debug, Default, Clone)]
+/// Parameters of the physics, stored in the resources. Can be changed from the console.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PhysicConfig {
+    /// Velocity multiplier applied each frame when no force is applied to a body.
     pub damping: f32,
 }
 
-#[derive(Debug, Default, Clone)]
-pub struct PhysicSystem {
-    config: PhysicConfig,
+impl Default for PhysicConfig {
+    fn default() -> Self {
+        Self { damping: 0.99 }
+    }
 }
 
-impl PhysicSystem {
-    pub fn new(config: PhysicConfig) -> Self {
-        Self { config }
-    }
+#[derive(Debug, Default, Clone)]
+pub struct PhysicSystem;
 
+impl PhysicSystem {
     pub fn update(&self, world: &mut World, dt: Duration, resources: &Resources) {
+        let damping = resources
+            .fetch::<PhysicConfig>()
+            .map(|config| config.damping)
+            .unwrap_or_else(|| PhysicConfig::default().damping);
         for (_e, (transform, body)) in world.query::<(&mut Transform, &mut DynamicBody)>().iter() {
             // acceleration is sum of all forces divided by the mass
             let mut sum_force = body
@@ -91,16 +100,37 @@ impl PhysicSystem {
                 body.velocity = body.velocity.normalize() * body.max_velocity;
             }
             if acc.length_squared() == 0.0 {
-                body.velocity *= self.config.damping;
+                body.velocity *= damping;
             }
             transform.translate(body.velocity * dt.as_secs_f32());
 
-            debug::stroke_line(
-                resources,
-                transform.translation,
-                transform.translation + body.velocity,
-                colors::GREEN,
-            );
+            if debug::ENABLED {
+                debug::arrow(
+                    resources,
+                    DebugCategory::PHYSICS,
+                    transform.translation,
+                    transform.translation + body.velocity,
+                    colors::GREEN,
+                );
+            }
         }
     }
 }
+
+impl System for PhysicSystem {
+    fn name(&self) -> &'static str {
+        "physics"
+    }
+
+    fn access(&self) -> Access {
+        Access::new()
+            .read::<PhysicConfig>()
+            .write::<DebugQueue>()
+            .write_component::<Transform>()
+            .write_component::<DynamicBody>()
+    }
+
+    fn run(&mut self, world: &mut World, resources: &Resources, dt: Duration) {
+        self.update(world, dt, resources);
+    }
+}
@@ -1,8 +1,12 @@
+use crate::core::audio::{play_sound, Sound};
+use crate::core::effect::{spawn_effect, ParticleEffect};
+use crate::core::events::Events;
 use crate::core::input::Input;
 use crate::core::random::RandomGenerator;
+use crate::core::system::{Access, System};
 use crate::core::timer::Timer;
 use crate::core::transform::Transform;
-use crate::event::GameEvent;
+use crate::event::{AudioEvent, Delete, GameEvent};
 use crate::gameplay::collision::{aabb_intersection, BoundingBox, CollisionLayer};
 use crate::gameplay::health::{Health, Invulnerable, Shield};
 use crate::gameplay::inventory::Inventory;
@@ -11,10 +15,11 @@ use crate::gameplay::player::Player;
 use crate::gameplay::Action;
 use crate::render::sprite::Sprite;
 use crate::resources::Resources;
+use hecs::World;
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
 use serde_derive::{Deserialize, Serialize};
-use shrev::EventChannel;
+use std::time::Duration;
 
 pub struct Pickup {
     pub item: Items,
@@ -51,10 +56,10 @@ pub fn is_pickup(world: &hecs::World, entity: hecs::Entity) -> bool {
 }
 
 pub fn process_pickups(world: &mut hecs::World, resources: &Resources) {
-    let mut channel = resources.fetch_mut::<EventChannel<GameEvent>>().unwrap();
     let input = resources.fetch::<Input<Action>>().unwrap();
     let mut inventory = resources.fetch_mut::<Inventory>().unwrap();
     let mut to_delete = vec![];
+    let mut info_texts = vec![];
 
     let maybe_player = {
         let mut query = world.query::<(&Transform, &BoundingBox, &Player)>();
@@ -69,9 +74,10 @@ pub fn process_pickups(world: &mut hecs::World, resources: &Resources) {
             {
                 if let Ok(()) = inventory.remove_scratch(50) {
                     //
-                    channel.single_write(GameEvent::PlaySound("sounds/powerUp2.mp3".to_string()));
-                    to_delete.push(GameEvent::Delete(e));
-                    to_delete.push(GameEvent::InfoText(pickup.item.info_text()));
+                    play_sound(resources, Sound::new("pickup"));
+                    spawn_effect(resources, ParticleEffect::new("pickup").at(t.translation));
+                    to_delete.push(Delete(e));
+                    info_texts.push(GameEvent::InfoText(pickup.item.info_text()));
                     picked_up.push(pickup.item);
                 }
             }
@@ -82,7 +88,14 @@ pub fn process_pickups(world: &mut hecs::World, resources: &Resources) {
         }
     }
 
-    channel.drain_vec_write(&mut to_delete);
+    resources
+        .fetch_mut::<Events<Delete>>()
+        .unwrap()
+        .defer_all(to_delete);
+    resources
+        .fetch_mut::<Events<GameEvent>>()
+        .unwrap()
+        .defer_all(info_texts);
 }
 
 pub fn aabb_intersection2(
@@ -207,3 +220,33 @@ impl Distribution<Items> for Standard {
         }
     }
 }
+
+pub struct PickupSystem;
+
+impl System for PickupSystem {
+    fn name(&self) -> &'static str {
+        "pickups"
+    }
+
+    fn access(&self) -> Access {
+        Access::new()
+            .write::<Events<AudioEvent>>()
+            .write::<Events<ParticleEffect>>()
+            .write::<Events<Delete>>()
+            .write::<Events<GameEvent>>()
+            .read::<Input<Action>>()
+            .write::<Inventory>()
+            .read_component::<Pickup>()
+            .read_component::<Transform>()
+            .read_component::<BoundingBox>()
+            .write_component::<Player>()
+            .write_component::<Health>()
+            .write_component::<Shield>()
+            .write_component::<DynamicBody>()
+            .structural()
+    }
+
+    fn run(&mut self, world: &mut World, resources: &Resources, _dt: Duration) {
+        process_pickups(world, resources);
+    }
+}
@@ -1,11 +1,14 @@
 use super::bullet;
 use crate::config::PlayerConfig;
-use crate::core::audio;
-use crate::core::camera::screen_to_world;
+use crate::core::audio::{self, Sound};
+use crate::core::camera::{screen_to_world, ProjectionMatrix};
+use crate::core::events::Events;
 use crate::core::input::{Axis, Input};
 use crate::core::random::RandomGenerator;
+use crate::core::system::{Access, System};
 use crate::core::timer::Timer;
 use crate::core::transform::Transform;
+use crate::event::AudioEvent;
 use crate::gameplay::bullet::spawn_missile;
 use crate::gameplay::collision::CollisionLayer;
 use crate::gameplay::enemy::Enemy;
@@ -14,7 +17,6 @@ use crate::gameplay::physics::DynamicBody;
 use crate::gameplay::trail::Trail;
 use crate::gameplay::{steering, Action};
 use crate::resources::Resources;
-use crate::{HEIGHT, WIDTH};
 use bitflags::_core::time::Duration;
 use hecs::{Entity, World};
 #[allow(unused_imports)]
@@ -49,6 +51,13 @@ pub struct Stats {
     pub boost_timer: Timer,
     /// force of the boost
     pub boost_magnitude: f32,
+    /// Time between shots when the shoot button is held
+    #[serde(default = "default_fire_timer")]
+    pub fire_timer: Timer,
+}
+
+fn default_fire_timer() -> Timer {
+    Timer::of_seconds(0.25)
 }
 
 impl Default for Stats {
@@ -60,6 +69,7 @@ impl Default for Stats {
             missile_percent: 0,
             boost_timer: Timer::of_seconds(1.0),
             boost_magnitude: 500.0,
+            fire_timer: default_fire_timer(),
         }
     }
 }
@@ -86,6 +96,24 @@ pub fn get_player(world: &World) -> Option<Entity> {
     world.query::<&Player>().iter().map(|(e, _)| e).next()
 }
 
+/// Distance of the target in front of the player when aiming with the gamepad.
+const STICK_AIM_DISTANCE: f32 = 300.0;
+
+/// Where the player is aiming at in world coordinates. Either the mouse cursor or a point in the
+/// direction of the right stick.
+pub fn aim_target(
+    input: &Input<Action>,
+    position: glam::Vec2,
+    projection_matrix: glam::Mat4,
+    world: &World,
+) -> glam::Vec2 {
+    if let Some(direction) = input.aim_direction() {
+        position + direction * STICK_AIM_DISTANCE
+    } else {
+        screen_to_world(input.mouse_position(), projection_matrix, world)
+    }
+}
+
 fn x_axis() -> Axis<Action> {
     Axis {
         left: Action::MoveLeft,
@@ -109,12 +137,11 @@ fn z_axis() -> Axis<Action> {
 
 pub fn update_player(world: &mut World, dt: Duration, resources: &Resources) {
     // only one player for now.
-    let input = resources.fetch::<Input<Action>>().unwrap();
+    let mut input = resources.fetch_mut::<Input<Action>>().unwrap();
     let mut random = resources.fetch_mut::<RandomGenerator>().unwrap();
     let player_controller_conf = resources.fetch::<PlayerConfig>().unwrap();
 
-    let projection_matrix =
-        glam::Mat4::orthographic_rh_gl(0.0, WIDTH as f32, 0.0, HEIGHT as f32, -1.0, 10.0);
+    let projection_matrix = resources.fetch::<ProjectionMatrix>().unwrap().matrix();
 
     let mut bullets = vec![];
     let mut missiles = vec![];
@@ -137,8 +164,8 @@ pub fn update_player(world: &mut World, dt: Duration, resources: &Resources) {
         let dir = glam::Mat2::from_angle(transform.rotation) * glam::Vec2::unit_y();
         trail.should_display = delta_y.max(0.0) > 0.0;
 
-        // DESIRED VELOCITY IF FORWARD TO THE MOUSE CURSOR
-        let target = screen_to_world(input.mouse_position(), projection_matrix, world);
+        // DESIRED VELOCITY IF FORWARD TO THE MOUSE CURSOR (OR STICK DIRECTION)
+        let target = aim_target(&input, transform.translation, projection_matrix, world);
 
         let steering_force = steering::seek(
             transform.translation,
@@ -165,15 +192,19 @@ pub fn update_player(world: &mut World, dt: Duration, resources: &Resources) {
 
         // boost
         player.stats.boost_timer.tick(dt);
-        if player.stats.boost_timer.finished() {
-            if input.is_just_pressed(Action::Boost) {
-                player.stats.boost_timer.reset();
-                dynamic.add_impulse(dir * player.stats.boost_magnitude);
-            }
+        // a boost pressed slightly before the end of the cooldown is kept in the input buffer.
+        if player.stats.boost_timer.finished() && input.consume_buffered(Action::Boost) {
+            player.stats.boost_timer.reset();
+            dynamic.add_impulse(dir * player.stats.boost_magnitude);
         }
 
-        // Shoot stuff
-        if input.is_just_pressed(Action::Shoot) {
+        // Shoot stuff. Holding the button will shoot continuously.
+        player.stats.fire_timer.tick(dt);
+        let should_fire = input.is_just_pressed(Action::Shoot)
+            || (input.is_pressed(Action::Shoot) && player.stats.fire_timer.finished());
+        if should_fire {
+            player.stats.fire_timer.reset();
+
             // shoot from the top.
             let initial_pos = transform.translation
                 + glam::Mat2::from_angle(transform.rotation)
@@ -189,7 +220,7 @@ pub fn update_player(world: &mut World, dt: Duration, resources: &Resources) {
                 }
             }
 
-            audio::play_sound(resources, "sounds/scifi_kit/Laser/Laser_09.wav");
+            audio::play_sound(resources, Sound::new("player_shoot"));
             bullets = vec![(
                 initial_pos,
                 dir,
@@ -218,3 +249,30 @@ pub fn update_player(world: &mut World, dt: Duration, resources: &Resources) {
 
     trace!("finished update_player");
 }
+
+pub struct PlayerSystem;
+
+impl System for PlayerSystem {
+    fn name(&self) -> &'static str {
+        "player"
+    }
+
+    fn access(&self) -> Access {
+        Access::new()
+            .write::<Input<Action>>()
+            .write::<RandomGenerator>()
+            .write::<Events<AudioEvent>>()
+            .read::<PlayerConfig>()
+            .read::<ProjectionMatrix>()
+            .write_component::<Transform>()
+            .write_component::<Player>()
+            .write_component::<DynamicBody>()
+            .write_component::<Trail>()
+            .read_component::<Enemy>()
+            .structural()
+    }
+
+    fn run(&mut self, world: &mut World, resources: &Resources, dt: Duration) {
+        update_player(world, dt, resources);
+    }
+}
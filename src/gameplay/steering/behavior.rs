@@ -7,6 +7,7 @@ use crate::core::transform::Transform;
 use crate::gameplay::collision::{CollisionLayer, CollisionWorld};
 use crate::gameplay::physics::DynamicBody;
 use crate::render::path::debug;
+use crate::render::path::debug::DebugCategory;
 use crate::resources::Resources;
 use rand::Rng;
 
@@ -30,12 +31,15 @@ pub fn follow_player(
         };
 
         body.add_force(steering);
-        debug::stroke_line(
-            resources,
-            t.translation,
-            t.translation + steering,
-            colors::RED,
-        );
+        if debug::ENABLED {
+            debug::arrow(
+                resources,
+                DebugCategory::STEERING,
+                t.translation,
+                t.translation + steering,
+                colors::RED,
+            );
+        }
 
         // rotate toward the player
         {
@@ -62,12 +66,15 @@ pub fn follow_player_bis(
         );
 
         body.add_force(steering);
-        debug::stroke_line(
-            resources,
-            t.translation,
-            t.translation + steering,
-            colors::RED,
-        );
+        if debug::ENABLED {
+            debug::arrow(
+                resources,
+                DebugCategory::STEERING,
+                t.translation,
+                t.translation + steering,
+                colors::RED,
+            );
+        }
 
         // rotate toward the player
         {
@@ -106,7 +113,15 @@ pub fn avoid_obstacles(
                 ignore_mask,
             ) {
                 body.add_force(f);
-                debug::stroke_line(resources, t.translation, t.translation + f, colors::BLUE);
+                if debug::ENABLED {
+                    debug::arrow(
+                        resources,
+                        DebugCategory::STEERING,
+                        t.translation,
+                        t.translation + f,
+                        colors::BLUE,
+                    );
+                }
             }
         }
     }
@@ -126,16 +141,27 @@ pub fn follow_random_path(
     }
 
     let steering = go_to_path_point(*target, t.translation, body.velocity, body.max_velocity);
-    debug::stroke_line(resources, t.translation, *target, colors::BLUE);
-
-    if let Some(steering) = steering {
-        body.add_force(steering);
+    if debug::ENABLED {
         debug::stroke_line(
             resources,
+            DebugCategory::STEERING,
             t.translation,
-            t.translation + steering,
-            colors::RED,
+            *target,
+            colors::BLUE,
         );
+    }
+
+    if let Some(steering) = steering {
+        body.add_force(steering);
+        if debug::ENABLED {
+            debug::arrow(
+                resources,
+                DebugCategory::STEERING,
+                t.translation,
+                t.translation + steering,
+                colors::RED,
+            );
+        }
     } else {
         // Need to generate new target.
         //
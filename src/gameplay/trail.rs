@@ -1,15 +1,19 @@
+use crate::core::system::{Access, ParallelSystem, System};
 use crate::core::transform::Transform;
 use crate::gameplay::physics::DynamicBody;
 use crate::render::particle::ParticleEmitter;
+use crate::resources::Resources;
 use hecs::World;
+use serde_derive::{Deserialize, Serialize};
+use std::time::Duration;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Trail {
     pub should_display: bool,
     pub offset: f32,
 }
 
-pub fn update_trails(world: &mut World) {
+pub fn update_trails(world: &World) {
     for (_, (trail, transform, emitter, _body)) in world
         .query::<(&Trail, &Transform, &mut ParticleEmitter, &DynamicBody)>()
         .iter()
@@ -29,3 +33,33 @@ pub fn update_trails(world: &mut World) {
         }
     }
 }
+
+pub struct TrailSystem;
+
+impl System for TrailSystem {
+    fn name(&self) -> &'static str {
+        "trails"
+    }
+
+    fn access(&self) -> Access {
+        Access::new()
+            .read_component::<Trail>()
+            .read_component::<Transform>()
+            .read_component::<DynamicBody>()
+            .write_component::<ParticleEmitter>()
+    }
+
+    fn run(&mut self, world: &mut World, _resources: &Resources, _dt: Duration) {
+        update_trails(world);
+    }
+
+    fn as_parallel(&mut self) -> Option<&mut dyn ParallelSystem> {
+        Some(self)
+    }
+}
+
+impl ParallelSystem for TrailSystem {
+    fn run_parallel(&mut self, world: &World, _dt: Duration) {
+        update_trails(world);
+    }
+}
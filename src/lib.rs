@@ -4,8 +4,6 @@ extern crate bitflags;
 #[macro_use]
 extern crate log;
 
-use luminance_windowing::WindowDim;
-
 pub mod assets;
 pub mod config;
 pub mod core;
@@ -18,9 +16,6 @@ pub mod resources;
 pub mod save;
 pub mod scene;
 pub mod ui;
+/// Default virtual resolution.
 pub const WIDTH: u32 = 1600;
 pub const HEIGHT: u32 = 960;
-pub const DIMENSIONS: WindowDim = WindowDim::Windowed {
-    width: WIDTH,
-    height: HEIGHT,
-};
@@ -1,2 +1,37 @@
+use crate::assets::prefab::ComponentRegistry;
+use crate::core::animation::AnimationController;
+use crate::core::lifetime::{DespawnOnExit, DespawnOutOfBounds, DespawnWithParent, TimeToLive};
+use crate::core::transform::{LocalTransform, Transform};
+use crate::gameplay::collision::BoundingBox;
+use crate::gameplay::enemy::Enemy;
+use crate::gameplay::health::{Health, Shield};
+use crate::gameplay::physics::DynamicBody;
+use crate::gameplay::player::Player;
+use crate::gameplay::trail::Trail;
+use crate::render::mesh::MeshRender;
+use crate::render::particle::ParticleEmitter;
+use crate::render::sprite::Sprite;
+
 pub mod enemies;
-pub mod player;
+
+/// Components that can be used in the prefabs, with their name in the JSON files.
+pub fn component_registry() -> ComponentRegistry {
+    ComponentRegistry::new()
+        .with_component::<Transform>("transform")
+        .with_component::<LocalTransform>("local_transform")
+        .with_component::<DynamicBody>("dynamic_body")
+        .with_component::<Sprite>("sprite")
+        .with_component::<MeshRender>("mesh_render")
+        .with_component::<BoundingBox>("bounding_box")
+        .with_component::<Health>("health")
+        .with_component::<Shield>("shield")
+        .with_component::<Enemy>("enemy")
+        .with_component::<Player>("player")
+        .with_component::<AnimationController>("animation")
+        .with_component::<Trail>("trail")
+        .with_component::<TimeToLive>("time_to_live")
+        .with_component::<DespawnOutOfBounds>("despawn_out_of_bounds")
+        .with_component::<DespawnWithParent>("despawn_with_parent")
+        .with_component::<DespawnOnExit>("despawn_on_exit")
+        .with_prepared_component::<ParticleEmitter>("particle_emitter", ParticleEmitter::init_pool)
+}
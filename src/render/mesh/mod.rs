@@ -13,6 +13,7 @@ use luminance::tess::{Mode, Tess};
 use luminance::texture::Dim2;
 use luminance_derive::{Semantics, UniformInterface, Vertex};
 use luminance_gl::GL33;
+use serde_derive::{Deserialize, Serialize};
 use std::time::Instant;
 
 // Vertex definition
@@ -72,6 +73,7 @@ pub struct ShaderUniform {
     time: Uniform<f32>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Material {
     /// Will use the given vertex and fragment shaders for the mesh.
     Shader {
@@ -91,8 +93,11 @@ where
     tess: Tess<S::Backend, Vertex, u32>,
     /// used to send elapsed time to shader.
     creation_time: Instant,
+    /// Number of meshes drawn during the last frame.
+    draw_calls: usize,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MeshRender {
     pub enabled: bool,
     pub material: Material,
@@ -142,8 +147,13 @@ where
         Self {
             tess,
             creation_time: Instant::now(),
+            draw_calls: 0,
         }
     }
+
+    pub fn draw_calls(&self) -> usize {
+        self.draw_calls
+    }
     pub fn render(
         &mut self,
         _pipeline: &Pipeline<S::Backend>,
@@ -170,6 +180,7 @@ where
                 },
             );
         let elapsed = self.creation_time.elapsed().as_secs_f32();
+        self.draw_calls = 0;
 
         for (_, (t, render)) in world.query::<(&Transform, &MeshRender)>().iter() {
             if !render.enabled {
@@ -201,6 +212,7 @@ where
                         }
                     }) {
                         ret?;
+                        self.draw_calls += 1;
                     }
                 } else {
                     shader_manager.load(handle.0);
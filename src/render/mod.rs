@@ -2,16 +2,19 @@ use crate::assets::shader::ShaderManager;
 use crate::assets::sprite::SpriteAsset;
 use crate::assets::AssetManager;
 use crate::core::camera::ProjectionMatrix;
+use crate::core::scene::TransitionOverlay;
+use crate::core::window::WindowDim;
 use crate::render::mesh::MeshRenderer;
 use crate::render::particle::ParticleSystem;
 use crate::render::path::PathRenderer;
 use crate::render::sprite::SpriteRenderer;
+use crate::render::transition::TransitionRenderer;
 use crate::render::ui::{text, Gui, GuiContext, UiRenderer};
 use crate::resources::Resources;
 use glyph_brush::GlyphBrush;
 use luminance::context::GraphicsContext;
 use luminance::framebuffer::Framebuffer;
-use luminance::pipeline::{PipelineError, PipelineState, Render};
+use luminance::pipeline::{PipelineError, PipelineState, Render, Viewport};
 use luminance::texture::Dim2;
 use luminance_gl::GL33;
 use std::time::Duration;
@@ -20,6 +23,7 @@ pub mod mesh;
 pub mod particle;
 pub mod path;
 pub mod sprite;
+pub mod transition;
 pub mod ui;
 
 pub struct Renderer<S>
@@ -35,6 +39,9 @@ where
     ui_renderer: UiRenderer<S>,
 
     path_renderer: PathRenderer<S>,
+
+    /// Drawn above everything when switching scenes.
+    transition_renderer: TransitionRenderer<S>,
 }
 
 impl<S> Renderer<S>
@@ -47,12 +54,14 @@ where
         let ui_renderer = UiRenderer::new(surface, gui_context);
         let path_renderer = PathRenderer::new(surface);
         let mesh_renderer = MeshRenderer::new(surface);
+        let transition_renderer = TransitionRenderer::new(surface);
         Self {
             sprite_renderer,
             mesh_renderer,
             particle_renderer,
             ui_renderer,
             path_renderer,
+            transition_renderer,
         }
     }
 
@@ -67,6 +76,11 @@ where
         self.path_renderer.prepare(surface, resources);
     }
 
+    /// Transition between scenes to draw during the next render.
+    pub fn prepare_transition(&mut self, overlay: Option<TransitionOverlay>) {
+        self.transition_renderer.prepare(overlay);
+    }
+
     pub fn render(
         &mut self,
         surface: &mut S,
@@ -74,8 +88,17 @@ where
         world: &hecs::World,
         resources: &Resources,
     ) -> Render<PipelineError> {
-        let projection_matrix = resources.fetch::<ProjectionMatrix>().unwrap().0;
+        let projection_matrix = resources.fetch::<ProjectionMatrix>().unwrap().matrix();
         let view = crate::core::camera::get_view_matrix(world).unwrap();
+        let viewport = {
+            let (x, y, width, height) = resources.fetch::<WindowDim>().unwrap().viewport();
+            Viewport::Specific {
+                x,
+                y,
+                width,
+                height,
+            }
+        };
 
         let mut textures = resources
             .fetch_mut::<AssetManager<S, SpriteAsset<S>>>()
@@ -85,7 +108,9 @@ where
             .new_pipeline_gate()
             .pipeline(
                 back_buffer,
-                &PipelineState::default().set_clear_color([0.0, 0.0, 0.0, 1.0]),
+                &PipelineState::default()
+                    .set_clear_color([0.0, 0.0, 0.0, 1.0])
+                    .set_viewport(viewport),
                 |pipeline, mut shd_gate| {
                     self.sprite_renderer.render(
                         &pipeline,
@@ -110,26 +135,38 @@ where
                         &mut shd_gate,
                         &projection_matrix,
                         &view,
-                        world,
                         &mut *textures,
                     )?;
 
-                    self.ui_renderer.render(&pipeline, &mut shd_gate)?;
+                    self.ui_renderer
+                        .render(&pipeline, &mut shd_gate, &mut *textures)?;
                     self.path_renderer
-                        .render(&projection_matrix, &view, &mut shd_gate)
+                        .render(&projection_matrix, &view, &mut shd_gate)?;
+                    self.transition_renderer.render(&mut shd_gate)
                 },
             )
             .assume()
     }
 
+    /// Number of draw calls of the last frame.
+    pub fn draw_calls(&self) -> usize {
+        self.sprite_renderer.draw_calls()
+            + self.mesh_renderer.draw_calls()
+            + self.particle_renderer.draw_calls()
+            + self.ui_renderer.draw_calls()
+            + self.path_renderer.draw_calls()
+            + self.transition_renderer.draw_calls()
+    }
+
     pub fn update(
         &mut self,
-        _surface: &mut S,
+        surface: &mut S,
         world: &hecs::World,
         dt: Duration,
         resources: &Resources,
     ) {
         // update particle systems.
         self.particle_renderer.update(world, dt, resources);
+        self.particle_renderer.prepare(surface, world);
     }
 }
@@ -2,8 +2,9 @@ use crate::assets::sprite::SpriteAsset;
 use crate::assets::{AssetManager, Handle};
 use crate::core::colors::RgbaColor;
 use crate::core::curve::Curve;
-use crate::core::transform::Transform;
-use crate::event::GameEvent;
+use crate::core::random::RandomGenerator;
+use crate::core::transform::{HasParent, Transform};
+use crate::gameplay::physics::DynamicBody;
 use crate::resources::Resources;
 use hecs::World;
 use luminance::blending::{Blending, Equation, Factor};
@@ -13,13 +14,14 @@ use luminance::pixel::NormUnsigned;
 use luminance::render_state::RenderState;
 use luminance::shader::{Program, Uniform};
 use luminance::shading_gate::ShadingGate;
-use luminance::tess::{Mode, Tess};
+use luminance::tess::{Deinterleaved, Mode, Tess, TessMapError, TessView};
 use luminance::texture::Dim2;
-use luminance_derive::UniformInterface;
+use luminance::vertex::Deinterleave;
+use luminance_derive::{Semantics, UniformInterface, Vertex};
 use luminance_gl::GL33;
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use serde_derive::{Deserialize, Serialize};
-use shrev::EventChannel;
 use std::path::Path;
 use std::time::Duration;
 
@@ -31,8 +33,9 @@ pub enum ParticleScale {
 
 #[derive(Debug, Clone, Default)]
 struct Particle {
-    life: u32,
-    initial_life: u32,
+    /// Remaining life in seconds.
+    life: f32,
+    initial_life: f32,
     position: glam::Vec2,
     velocity: glam::Vec2,
     colors: Curve<RgbaColor>,
@@ -40,12 +43,14 @@ struct Particle {
     scale_over_lifetime: Option<Curve<f32>>,
 
     rotation: f32,
+    /// Radians per second.
+    angular_velocity: f32,
 }
 
 impl Particle {
     fn respawn(
         &mut self,
-        life: u32,
+        life: f32,
         origin: glam::Vec2,
         velocity: glam::Vec2,
         scale: glam::Vec2,
@@ -63,16 +68,25 @@ impl Particle {
 
     /// return true if the particle is still alive
     fn alive(&self) -> bool {
-        self.life > 0
+        self.life > 0.0
     }
 
-    fn update(&mut self, dt: f32) {
+    fn update(&mut self, dt: f32, gravity: glam::Vec2, drag: f32) {
+        self.velocity += gravity * dt;
+        if drag > 0.0 {
+            self.velocity /= 1.0 + drag * dt;
+        }
         self.position += self.velocity * dt;
-        self.life -= 1; // one frame.
+        self.rotation += self.angular_velocity * dt;
+        self.life -= dt;
     }
 
     fn t(&self) -> f32 {
-        1.0 - self.life as f32 / self.initial_life as f32
+        if self.initial_life > 0.0 {
+            (1.0 - self.life / self.initial_life).clamp(0.0, 1.0)
+        } else {
+            1.0
+        }
     }
 
     fn color(&self) -> RgbaColor {
@@ -135,25 +149,104 @@ pub enum EmitterSource {
     /// Line relative to emitter's transform, so first point will be transform + v1, next point will be
     /// transform + v2
     Line(glam::Vec2, glam::Vec2),
+
+    /// Spawn particle randomly inside a circle of this radius.
+    Circle(f32),
+
+    /// Spawn particle randomly between two circles (inner and outer radius).
+    Ring(f32, f32),
+
+    /// Spawn particle randomly on an arc of circle (radius, start angle and end angle in radians).
+    Arc(f32, f32, f32),
+
+    /// Spawn particle randomly inside a rectangle of this size, centered on the emitter.
+    Rectangle(glam::Vec2),
 }
 
 impl EmitterSource {
     fn spawn_position<R: Rng>(&self, emitter_position: glam::Vec2, rand: &mut R) -> glam::Vec2 {
-        match self {
+        match *self {
             Self::Point => emitter_position,
             Self::Line(p1, p2) => {
-                (emitter_position - *p1).lerp(emitter_position + *p2, rand.gen_range(0.0, 1.0f32))
+                (emitter_position - p1).lerp(emitter_position + p2, rand.gen_range(0.0, 1.0f32))
+            }
+            Self::Circle(radius) => {
+                // sqrt so that the particles are evenly distributed in the circle.
+                let distance = radius * rand.gen_range(0.0, 1.0f32).sqrt();
+                emitter_position + random_direction(rand) * distance
+            }
+            Self::Ring(inner, outer) => {
+                let t = rand.gen_range(0.0, 1.0f32);
+                let distance = (inner * inner + t * (outer * outer - inner * inner)).sqrt();
+                emitter_position + random_direction(rand) * distance
+            }
+            Self::Arc(radius, start, end) => {
+                let angle = if end > start {
+                    rand.gen_range(start, end)
+                } else {
+                    start
+                };
+                emitter_position + glam::Mat2::from_angle(angle) * glam::Vec2::unit_x() * radius
+            }
+            Self::Rectangle(size) => {
+                let x = rand.gen_range(-0.5, 0.5f32) * size.x();
+                let y = rand.gen_range(-0.5, 0.5f32) * size.y();
+                emitter_position + glam::vec2(x, y)
             }
         }
     }
 }
 
+fn random_direction<R: Rng>(rand: &mut R) -> glam::Vec2 {
+    let angle = rand.gen_range(0.0, 2.0 * std::f32::consts::PI);
+    glam::Mat2::from_angle(angle) * glam::Vec2::unit_x()
+}
+
+fn random_in_range<R: Rng>(rand: &mut R, (min, max): (f32, f32)) -> f32 {
+    if max > min {
+        rand.gen_range(min, max)
+    } else {
+        min
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ParticleShape {
     Quad,
     Texture(String),
 }
 
+/// How the particles are blended with what is behind them.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum ParticleBlending {
+    /// The color of the particles is added to the background. Good for fire, sparks and lasers.
+    #[default]
+    Additive,
+    /// The particles cover the background depending on their alpha. Good for smoke and debris.
+    Alpha,
+}
+
+impl ParticleBlending {
+    fn render_state(self) -> RenderState {
+        let blending = match self {
+            // The alpha still fades the particles out.
+            ParticleBlending::Additive => Blending {
+                equation: Equation::Additive,
+                src: Factor::SrcAlpha,
+                dst: Factor::One,
+            },
+            ParticleBlending::Alpha => Blending {
+                equation: Equation::Additive,
+                src: Factor::SrcAlpha,
+                dst: Factor::SrcAlphaComplement,
+            },
+        };
+        RenderState::default()
+            .set_depth_test(None)
+            .set_blending(blending)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ParticleEmitter {
     enabled: bool,
@@ -162,14 +255,33 @@ pub struct ParticleEmitter {
     particles: ParticlePool,
     pub source: EmitterSource,
     pub shape: ParticleShape,
+    #[serde(default)]
+    pub blending: ParticleBlending,
 
     pub velocity_range: (f32, f32),
     pub angle_range: (f32, f32),
 
+    /// Angular velocity of the particles in radians per second.
+    #[serde(default)]
+    pub angular_velocity_range: (f32, f32),
+
+    /// Acceleration applied to all particles.
+    #[serde(default)]
+    pub gravity: glam::Vec2,
+
+    /// Slow down the particles. 0 for no drag.
+    #[serde(default)]
+    pub drag: f32,
+
+    /// Part of the velocity of the emitter's DynamicBody (or its parent's) that is added to the
+    /// particles. Between 0 and 1.
+    #[serde(default)]
+    pub inherit_velocity: f32,
+
     pub scale: ParticleScale,
     pub scale_over_lifetime: Option<Curve<f32>>,
 
-    /// Particle per frame to emit.
+    /// Particles to emit per second. For a burst, number of particles to emit at once.
     particle_number: f32,
 
     /// when particle_number < 1, we need to know when we should spawn a particle.
@@ -179,9 +291,9 @@ pub struct ParticleEmitter {
     /// Color of the particle
     pub colors: Curve<RgbaColor>,
 
-    /// How long does the particle (in frames)
+    /// How long does the particle live (in seconds)
     #[serde(default)]
-    particle_life: u32,
+    particle_life: f32,
 
     /// Offset applied to a particle position on spawn.
     #[serde(default)]
@@ -190,6 +302,20 @@ pub struct ParticleEmitter {
     /// If true, only spawn stuff once
     #[serde(default)]
     pub burst: bool,
+
+    /// Emitter that is started where a particle dies. It is always a burst, otherwise it would
+    /// never stop.
+    #[serde(default)]
+    pub sub_emitter: Option<Box<ParticleEmitter>>,
+
+    /// Sub emitters that are running, with their position.
+    #[serde(skip)]
+    active_sub_emitters: Vec<(glam::Vec2, ParticleEmitter)>,
+
+    /// Seeded from the RandomGenerator on the first update so that particles are deterministic
+    /// for a given seed.
+    #[serde(skip)]
+    rng: Option<StdRng>,
 }
 
 impl Default for ParticleEmitter {
@@ -199,16 +325,24 @@ impl Default for ParticleEmitter {
             particles: Default::default(),
             source: EmitterSource::Point,
             shape: ParticleShape::Quad,
+            blending: ParticleBlending::Additive,
             velocity_range: (0.0, 10.0),
             angle_range: (0.0, 2.0 * std::f32::consts::PI),
+            angular_velocity_range: (0.0, 0.0),
+            gravity: glam::Vec2::zero(),
+            drag: 0.0,
+            inherit_velocity: 0.0,
             scale: ParticleScale::Constant(glam::vec2(5.0, 5.0)),
             scale_over_lifetime: None,
-            particle_number: 1.0,
+            particle_number: 60.0,
             nb_accumulator: 0.0,
             colors: Default::default(),
-            particle_life: 10,
+            particle_life: 0.2,
             position_offset: Default::default(),
             burst: false,
+            sub_emitter: None,
+            active_sub_emitters: vec![],
+            rng: None,
         }
     }
 }
@@ -229,20 +363,50 @@ impl ParticleEmitter {
         self.enabled = false;
     }
 
-    /// Necessary when getting the emitter from a file.
+    /// Necessary when getting the emitter from a file. Sub emitters are turned into bursts.
     pub fn init_pool(&mut self) {
-        let frame_needed = if self.burst {
-            1
+        let nb = if self.burst {
+            self.particle_number.ceil()
         } else {
-            self.particle_life as usize + 1
+            // a bit more than needed in case of a long frame.
+            (self.particle_number * self.particle_life * 1.1).ceil() + 1.0
         };
-        self.particles = ParticlePool::of_size(self.particle_number.ceil() as usize * frame_needed);
+        self.particles = ParticlePool::of_size(nb as usize);
+        if let Some(sub_emitter) = self.sub_emitter.as_mut() {
+            if !sub_emitter.burst {
+                warn!("Sub emitter is not a burst, it will be played as a burst");
+                sub_emitter.burst = true;
+            }
+            sub_emitter.init_pool();
+        }
+    }
+
+    /// For a burst, time after which all the particles and the particles of the sub emitters are
+    /// dead. `None` if the emitter never stops.
+    pub fn lifetime(&self) -> Option<f32> {
+        if !self.burst {
+            return None;
+        }
+        let sub_lifetime = match self.sub_emitter.as_ref() {
+            Some(sub_emitter) => sub_emitter.lifetime()?,
+            None => 0.0,
+        };
+        Some(self.particle_life + sub_lifetime)
     }
 
     /// Update the position and velocity of all particles. If a particle is dead, respawn it :)
-    /// Return true if should despawn the particle emitter.
-    fn update(&mut self, position: glam::Vec2, dt: f32) -> bool {
-        let mut rng = rand::thread_rng();
+    /// `velocity` is the velocity of the entity that holds the emitter.
+    fn update(
+        &mut self,
+        position: glam::Vec2,
+        velocity: glam::Vec2,
+        dt: f32,
+        random: &mut RandomGenerator,
+    ) {
+        let mut rng = match self.rng.take() {
+            Some(rng) => rng,
+            None => StdRng::from_rng(random.rng()).expect("Should be able to seed rng"),
+        };
 
         // emit particles.
         trace!(
@@ -250,7 +414,13 @@ impl ParticleEmitter {
             self.particle_number,
             self.nb_accumulator
         );
-        self.nb_accumulator += self.particle_number;
+        if self.burst {
+            if self.enabled {
+                self.nb_accumulator += self.particle_number;
+            }
+        } else {
+            self.nb_accumulator += self.particle_number * dt;
+        }
 
         let entire_nb = self.nb_accumulator.floor() as u32;
         if entire_nb > 0 {
@@ -259,16 +429,16 @@ impl ParticleEmitter {
                     if let Some(particle) = self.particles.get_available() {
                         trace!("Emit particle");
 
-                        let angle = rng.gen_range(self.angle_range.0, self.angle_range.1);
+                        let angle = random_in_range(&mut rng, self.angle_range);
                         let rotation = glam::Mat2::from_angle(angle);
-                        let speed = rng.gen_range(self.velocity_range.0, self.velocity_range.1);
+                        let speed = random_in_range(&mut rng, self.velocity_range);
 
                         // PARTICLE SCALE. -> initial scale.
                         let scale = match self.scale {
                             ParticleScale::Constant(s) => s,
                             ParticleScale::Random(low, high) => {
-                                let x = rng.gen_range(low.x(), high.x());
-                                let y = rng.gen_range(low.y(), high.y());
+                                let x = random_in_range(&mut rng, (low.x(), high.x()));
+                                let y = random_in_range(&mut rng, (low.y(), high.y()));
                                 glam::vec2(x, y)
                             }
                         };
@@ -276,11 +446,14 @@ impl ParticleEmitter {
                         particle.respawn(
                             self.particle_life,
                             self.source.spawn_position(position, &mut rng) + self.position_offset,
-                            rotation * (speed * glam::Vec2::unit_x()),
+                            rotation * (speed * glam::Vec2::unit_x())
+                                + velocity * self.inherit_velocity,
                             scale,
                             self.scale_over_lifetime.clone(),
                             angle,
                         );
+                        particle.angular_velocity =
+                            random_in_range(&mut rng, self.angular_velocity_range);
                         particle.colors = self.colors.clone();
                         trace!("{:?}", particle);
                     }
@@ -292,36 +465,118 @@ impl ParticleEmitter {
         // update existing particles.
         for (idx, p) in self.particles.particles.iter_mut().enumerate() {
             if p.alive() {
-                p.update(dt);
-            } else {
-                if !self.particles.free.contains(&idx) {
+                p.update(dt, self.gravity, self.drag);
+                if !p.alive() {
                     self.particles.free.push(idx);
+                    let sub_emitter = match self.sub_emitter.as_ref() {
+                        Some(sub_emitter) if self.active_sub_emitters.len() < MAX_SUB_EMITTERS => {
+                            sub_emitter
+                        }
+                        _ => continue,
+                    };
+                    let mut sub_emitter = (**sub_emitter).clone();
+                    sub_emitter.rng =
+                        Some(StdRng::from_rng(&mut rng).expect("Should be able to seed rng"));
+                    self.active_sub_emitters.push((p.position, sub_emitter));
                 }
             }
         }
 
+        // sub emitters stay where they were spawned.
+        self.active_sub_emitters
+            .retain(|(_, sub_emitter)| sub_emitter.enabled || !sub_emitter.particles.all_dead());
+        for (sub_position, sub_emitter) in self.active_sub_emitters.iter_mut() {
+            sub_emitter.update(*sub_position, glam::Vec2::zero(), dt, random);
+        }
+
+        self.rng = Some(rng);
+
         if self.burst {
             self.disable();
+        }
+    }
 
-            if self.particles.all_dead() {
-                return false;
-            }
+    /// State of the live particles, to send to the GPU.
+    fn instances(&self) -> impl Iterator<Item = ParticleInstance> + '_ {
+        self.particles
+            .particles
+            .iter()
+            .filter(|p| p.alive())
+            .map(ParticleInstance::from)
+    }
+
+    fn nb_alive(&self) -> usize {
+        self.particles
+            .particles
+            .iter()
+            .filter(|p| p.alive())
+            .count()
+    }
+
+    /// This emitter and all its running sub emitters.
+    fn with_sub_emitters(&self) -> Vec<&ParticleEmitter> {
+        let mut emitters = vec![self];
+        for (_, sub_emitter) in &self.active_sub_emitters {
+            emitters.extend(sub_emitter.with_sub_emitters());
         }
+        emitters
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Semantics)]
+pub enum ParticleSemantics {
+    #[sem(name = "position", repr = "[f32; 2]", wrapper = "InstancePosition")]
+    Position,
+
+    #[sem(name = "scale", repr = "[f32; 2]", wrapper = "InstanceScale")]
+    Scale,
+
+    #[sem(name = "rotation", repr = "f32", wrapper = "InstanceRotation")]
+    Rotation,
+
+    #[sem(name = "color", repr = "[f32; 4]", wrapper = "InstanceColor")]
+    Color,
+}
 
-        true
+/// State of a particle, as sent to the GPU.
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Vertex, Copy, Debug, Clone)]
+#[vertex(sem = "ParticleSemantics", instanced = "true")]
+pub struct ParticleInstance {
+    position: InstancePosition,
+    scale: InstanceScale,
+    rotation: InstanceRotation,
+    color: InstanceColor,
+}
+
+impl From<&Particle> for ParticleInstance {
+    fn from(p: &Particle) -> Self {
+        Self {
+            position: InstancePosition::new(p.position.into()),
+            scale: InstanceScale::new(p.scale().into()),
+            rotation: InstanceRotation::new(p.rotation),
+            color: InstanceColor::new(p.color().to_normalized()),
+        }
     }
 }
 
+/// Maximum number of sub emitters running at the same time for an emitter. Particles that die
+/// above this number do not start a sub emitter.
+const MAX_SUB_EMITTERS: usize = 32;
+
 const VS: &'static str = include_str!("particle-vs.glsl");
 const FS: &'static str = include_str!("particle-fs.glsl");
 const FS_TEXTURE: &'static str = include_str!("particle-texture-fs.glsl");
 
-pub fn new_shader<B>(surface: &mut B) -> Program<GL33, (), (), ParticleShaderInterface>
+pub fn new_shader<B>(
+    surface: &mut B,
+) -> Program<GL33, ParticleSemantics, (), ParticleShaderInterface>
 where
     B: GraphicsContext<Backend = GL33>,
 {
     surface
-        .new_shader_program::<(), (), ParticleShaderInterface>()
+        .new_shader_program::<ParticleSemantics, (), ParticleShaderInterface>()
         .from_strings(VS, None, None, FS)
         .expect("Program creation")
         .ignore_warnings()
@@ -329,12 +584,12 @@ where
 
 pub fn new_texture_shader<B>(
     surface: &mut B,
-) -> Program<GL33, (), (), TextureParticleShaderInterface>
+) -> Program<GL33, ParticleSemantics, (), TextureParticleShaderInterface>
 where
     B: GraphicsContext<Backend = GL33>,
 {
     surface
-        .new_shader_program::<(), (), TextureParticleShaderInterface>()
+        .new_shader_program::<ParticleSemantics, (), TextureParticleShaderInterface>()
         .from_strings(VS, None, None, FS_TEXTURE)
         .expect("Program creation")
         .ignore_warnings()
@@ -345,8 +600,6 @@ pub struct ParticleShaderInterface {
     pub projection: Uniform<[[f32; 4]; 4]>,
     #[uniform(unbound)]
     pub view: Uniform<[[f32; 4]; 4]>,
-    pub model: Uniform<[[f32; 4]; 4]>,
-    pub color: Uniform<[f32; 4]>,
 }
 
 #[derive(UniformInterface)]
@@ -354,49 +607,168 @@ pub struct TextureParticleShaderInterface {
     pub projection: Uniform<[[f32; 4]; 4]>,
     #[uniform(unbound)]
     pub view: Uniform<[[f32; 4]; 4]>,
-    pub model: Uniform<[[f32; 4]; 4]>,
-    pub color: Uniform<[f32; 4]>,
 
     /// Texture for the sprite.
     tex: Uniform<TextureBinding<Dim2, NormUnsigned>>,
 }
 
-pub struct ParticleSystem<S>
+/// Particles of an emitter, drawn with one instanced draw call. The instance buffers are kept
+/// between frames and only grow when an emitter has more live particles than they can hold.
+/// They are deinterleaved (one buffer per attribute) so that they can be mapped and updated.
+struct ParticleBatch<S>
 where
     S: GraphicsContext<Backend = GL33>,
 {
-    tess: Tess<S::Backend, ()>,
-    shader: Program<S::Backend, (), (), ParticleShaderInterface>,
-    texture_shader: Program<S::Backend, (), (), TextureParticleShaderInterface>,
+    tess: Tess<S::Backend, (), (), ParticleInstance, Deinterleaved>,
+    /// Number of instances to draw this frame. The rest of the buffer is unused.
+    len: usize,
+    shape: ParticleShape,
+    blending: ParticleBlending,
 }
 
-impl<S> ParticleSystem<S>
+impl<S> ParticleBatch<S>
 where
     S: GraphicsContext<Backend = GL33>,
 {
-    pub fn new(surface: &mut S) -> Self {
+    fn new(surface: &mut S, emitter: &ParticleEmitter, nb: usize) -> Self {
+        // Room for the emitter to grow a bit without allocating again.
+        let capacity = nb.next_power_of_two();
         let tess = surface
-            .new_tess()
+            .new_deinterleaved_tess::<(), ParticleInstance>()
             .set_vertex_nb(4)
+            .set_instance_attributes(vec![InstancePosition::new([0.0; 2]); capacity])
+            .set_instance_attributes(vec![InstanceScale::new([0.0; 2]); capacity])
+            .set_instance_attributes(vec![InstanceRotation::new(0.0); capacity])
+            .set_instance_attributes(vec![InstanceColor::new([0.0; 4]); capacity])
             .set_mode(Mode::TriangleFan)
             .build()
             .expect("Tess creation");
         Self {
             tess,
+            len: 0,
+            shape: emitter.shape.clone(),
+            blending: emitter.blending,
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        self.tess.inst_nb()
+    }
+
+    /// Copy the live particles of the emitter to the instance buffer.
+    fn upload(&mut self, emitter: &ParticleEmitter, nb: usize) {
+        self.shape = emitter.shape.clone();
+        self.blending = emitter.blending;
+        let res = write_attribute(&mut self.tess, emitter, |i| i.position)
+            .and_then(|_| write_attribute(&mut self.tess, emitter, |i| i.scale))
+            .and_then(|_| write_attribute(&mut self.tess, emitter, |i| i.rotation))
+            .and_then(|_| write_attribute(&mut self.tess, emitter, |i| i.color));
+        match res {
+            Ok(()) => self.len = nb,
+            Err(e) => {
+                error!("Cannot map particle instances = {:?}", e);
+                self.len = 0;
+            }
+        }
+    }
+}
+
+/// Copy one attribute of the live particles to its instance buffer.
+fn write_attribute<T, F>(
+    tess: &mut Tess<GL33, (), (), ParticleInstance, Deinterleaved>,
+    emitter: &ParticleEmitter,
+    attribute: F,
+) -> Result<(), TessMapError>
+where
+    ParticleInstance: Deinterleave<T>,
+    F: Fn(ParticleInstance) -> T,
+{
+    let mut slice = tess.instances_mut::<T>()?;
+    for (dst, instance) in slice.iter_mut().zip(emitter.instances()) {
+        *dst = attribute(instance);
+    }
+    Ok(())
+}
+
+pub struct ParticleSystem<S>
+where
+    S: GraphicsContext<Backend = GL33>,
+{
+    batches: Vec<ParticleBatch<S>>,
+    shader: Program<S::Backend, ParticleSemantics, (), ParticleShaderInterface>,
+    texture_shader: Program<S::Backend, ParticleSemantics, (), TextureParticleShaderInterface>,
+}
+
+impl<S> ParticleSystem<S>
+where
+    S: GraphicsContext<Backend = GL33>,
+{
+    pub fn new(surface: &mut S) -> Self {
+        Self {
+            batches: vec![],
             shader: new_shader(surface),
             texture_shader: new_texture_shader(surface),
         }
     }
 
     pub fn update(&mut self, world: &World, dt: Duration, resources: &Resources) {
-        let mut chan = resources.fetch_mut::<EventChannel<GameEvent>>().unwrap();
-        let mut remove_events = vec![];
-        for (e, (t, emitter)) in world.query::<(&Transform, &mut ParticleEmitter)>().iter() {
-            if !emitter.update(t.translation, dt.as_secs_f32()) {
-                chan.single_write(GameEvent::Delete(e));
+        let mut random = resources.fetch_mut::<RandomGenerator>().unwrap();
+        for (_, (t, emitter, body, parent)) in world
+            .query::<(
+                &Transform,
+                &mut ParticleEmitter,
+                Option<&DynamicBody>,
+                Option<&HasParent>,
+            )>()
+            .iter()
+        {
+            // the emitter can be attached to a child entity (e.g. trail), so use the parent's body.
+            let velocity = if let Some(body) = body {
+                body.velocity
+            } else if let Some(parent) = parent {
+                world
+                    .get::<DynamicBody>(parent.entity)
+                    .map(|body| body.velocity)
+                    .unwrap_or_default()
+            } else {
+                glam::Vec2::zero()
+            };
+
+            emitter.update(t.translation, velocity, dt.as_secs_f32(), &mut random);
+        }
+    }
+
+    /// Upload the live particles of each emitter to the GPU. The batches of the previous frame
+    /// are reused; a batch is only created again when it is too small.
+    pub fn prepare(&mut self, surface: &mut S, world: &World) {
+        let mut query = world.query::<&ParticleEmitter>();
+        let emitters = query
+            .iter()
+            .flat_map(|(_, emitter)| emitter.with_sub_emitters());
+        let mut used = 0;
+        for emitter in emitters {
+            let nb = emitter.nb_alive();
+            if nb == 0 {
+                continue;
+            }
+
+            match self.batches.get(used) {
+                Some(batch) if batch.capacity() >= nb => (),
+                Some(_) => self.batches[used] = ParticleBatch::new(surface, emitter, nb),
+                None => self.batches.push(ParticleBatch::new(surface, emitter, nb)),
             }
+            self.batches[used].upload(emitter, nb);
+            used += 1;
+        }
+
+        for batch in &mut self.batches[used..] {
+            batch.len = 0;
         }
-        chan.drain_vec_write(&mut remove_events);
+    }
+
+    /// One per batch of particles.
+    pub fn draw_calls(&self) -> usize {
+        self.batches.iter().filter(|batch| batch.len > 0).count()
     }
 
     pub fn render(
@@ -405,45 +777,19 @@ where
         shd_gate: &mut ShadingGate<S::Backend>,
         projection: &glam::Mat4,
         view: &glam::Mat4,
-        world: &World,
-
         textures: &mut AssetManager<S, SpriteAsset<S>>,
     ) -> Result<(), PipelineError> {
-        let tess = &self.tess;
-        let render_st = RenderState::default()
-            .set_depth_test(None)
-            .set_blending(Blending {
-                equation: Equation::Additive,
-                src: Factor::One,
-                dst: Factor::SrcAlphaComplement,
-            });
-        for (_, emitter) in world.query::<&mut ParticleEmitter>().iter() {
-            match &emitter.shape {
+        for batch in self.batches.iter().filter(|batch| batch.len > 0) {
+            let tess = &batch.tess;
+            let render_st = batch.blending.render_state();
+            match &batch.shape {
                 ParticleShape::Quad => {
                     shd_gate.shade(&mut self.shader, |mut iface, uni, mut rdr_gate| {
                         iface.set(&uni.projection, projection.to_cols_array_2d());
                         iface.set(&uni.view, view.to_cols_array_2d());
-
-                        for p in &emitter.particles.particles {
-                            if !p.alive() {
-                                continue;
-                            }
-
-                            iface.set(&uni.color, p.color().to_normalized());
-                            iface.set(
-                                &uni.model,
-                                glam::Mat4::from_scale_rotation_translation(
-                                    p.scale().extend(1.0),
-                                    glam::Quat::from_rotation_z(p.rotation),
-                                    p.position.extend(0.0),
-                                )
-                                .to_cols_array_2d(),
-                            );
-
-                            rdr_gate.render(&render_st, |mut tess_gate| tess_gate.render(tess))?;
-                        }
-
-                        Ok(())
+                        rdr_gate.render(&render_st, |mut tess_gate| {
+                            tess_gate.render(TessView::inst_whole(tess, batch.len))
+                        })
                     })?;
                 }
                 ParticleShape::Texture(id) => {
@@ -457,28 +803,9 @@ where
                                     iface.set(&uni.projection, projection.to_cols_array_2d());
                                     iface.set(&uni.view, view.to_cols_array_2d());
                                     iface.set(&uni.tex, bound_tex.binding());
-                                    for p in &emitter.particles.particles {
-                                        if !p.alive() {
-                                            continue;
-                                        }
-
-                                        iface.set(&uni.color, p.color().to_normalized());
-                                        iface.set(
-                                            &uni.model,
-                                            glam::Mat4::from_scale_rotation_translation(
-                                                p.scale().extend(1.0),
-                                                glam::Quat::from_rotation_z(p.rotation),
-                                                p.position.extend(0.0),
-                                            )
-                                            .to_cols_array_2d(),
-                                        );
-
-                                        rdr_gate.render(&render_st, |mut tess_gate| {
-                                            tess_gate.render(tess)
-                                        })?;
-                                    }
-
-                                    Ok(())
+                                    rdr_gate.render(&render_st, |mut tess_gate| {
+                                        tess_gate.render(TessView::inst_whole(tess, batch.len))
+                                    })
                                 });
                             }
                         });
@@ -1,157 +1,440 @@
 //! Draw path, lines and so on for debug purposes.
+//!
+//! Each shape belongs to a category (physics, collision, AI, steering or camera) that can be
+//! toggled at runtime with F1 to F5. All the categories are enabled at the start when
+//! `show_gizmos` is set in the engine config.
+//!
+//! Shapes are drawn for one frame, or for some time with `draw_for`. Labels are drawn with the UI
+//! at their position in the world (see `draw_labels`).
+//!
+//! Nothing is drawn in release builds. Wrap the calls in `if debug::ENABLED` so that their
+//! arguments (e.g. the text of a label) are not computed either.
 
 use crate::config::GameEngineConfig;
+use crate::core::camera::{get_view_matrix, ProjectionMatrix};
 use crate::core::colors::RgbaColor;
 use crate::render::path::{Color, Position, Vertex};
+use crate::render::ui::Gui;
 use crate::resources::Resources;
-use lyon::math::Point;
+use glfw::Key;
+use hecs::World;
+use lyon::math::{Point, Rect, Size};
 use lyon::tessellation::geometry_builder::simple_builder;
-use lyon::tessellation::{basic_shapes, StrokeOptions, VertexBuffers};
+use lyon::tessellation::{basic_shapes, FillOptions, StrokeOptions, VertexBuffers};
+use std::time::Duration;
 
-pub struct DebugQueue(Vec<(Vec<Vertex>, Vec<u16>)>);
+/// False in release builds. The debug shapes are not even tessellated, and the calls to this
+/// module should be skipped.
+pub const ENABLED: bool = cfg!(debug_assertions);
+
+/// Font size of the labels.
+const LABEL_SIZE: f32 = 14.0;
+
+bitflags! {
+    pub struct DebugCategory: u32 {
+        /// Velocities and impulses.
+        const PHYSICS = 0b00001;
+        /// Bounding boxes that collide.
+        const COLLISION = 0b00010;
+        /// Enemy behaviors (ranges, routes...)
+        const AI = 0b00100;
+        /// Steering forces.
+        const STEERING = 0b01000;
+        /// Camera target and bounds.
+        const CAMERA = 0b10000;
+    }
+}
+
+impl DebugCategory {
+    /// Key that toggles the category.
+    pub fn from_key(key: Key) -> Option<Self> {
+        match key {
+            Key::F1 => Some(Self::PHYSICS),
+            Key::F2 => Some(Self::COLLISION),
+            Key::F3 => Some(Self::AI),
+            Key::F4 => Some(Self::STEERING),
+            Key::F5 => Some(Self::CAMERA),
+            _ => None,
+        }
+    }
+}
+
+/// Shape to draw, in world coordinates.
+#[derive(Debug, Clone)]
+pub enum DebugShape {
+    Line {
+        from: glam::Vec2,
+        to: glam::Vec2,
+    },
+    Arrow {
+        from: glam::Vec2,
+        to: glam::Vec2,
+    },
+    Circle {
+        center: glam::Vec2,
+        radius: f32,
+        filled: bool,
+    },
+    /// Position is the bottom-left corner.
+    Quad {
+        position: glam::Vec2,
+        dimensions: glam::Vec2,
+        filled: bool,
+    },
+    Label {
+        position: glam::Vec2,
+        text: String,
+    },
+}
+
+type Mesh = (Vec<Vertex>, Vec<u16>);
+
+#[derive(Debug, Clone)]
+pub struct DebugLabel {
+    pub position: glam::Vec2,
+    pub text: String,
+    pub color: RgbaColor,
+}
+
+pub struct DebugQueue {
+    /// Categories that are drawn.
+    pub enabled: DebugCategory,
+    meshes: Vec<Mesh>,
+    labels: Vec<DebugLabel>,
+    /// Shapes drawn until their remaining time (in seconds) runs out.
+    timed_meshes: Vec<(Mesh, f32)>,
+    timed_labels: Vec<(DebugLabel, f32)>,
+}
 
 impl Default for DebugQueue {
     fn default() -> Self {
-        Self(vec![])
+        Self {
+            enabled: DebugCategory::empty(),
+            meshes: vec![],
+            labels: vec![],
+            timed_meshes: vec![],
+            timed_labels: vec![],
+        }
     }
 }
 
 impl DebugQueue {
-    pub fn drain(&mut self) -> std::vec::Drain<(Vec<Vertex>, Vec<u16>)> {
-        self.0.drain(..)
+    /// All the categories are enabled if `show_gizmos` is set.
+    pub fn from_config(config: &GameEngineConfig) -> Self {
+        Self {
+            enabled: if config.show_gizmos {
+                DebugCategory::all()
+            } else {
+                DebugCategory::empty()
+            },
+            ..Self::default()
+        }
     }
-}
 
-fn show_gizmo(resources: &Resources) -> bool {
-    resources
-        .fetch::<GameEngineConfig>()
-        .map(|conf| conf.show_gizmos)
-        .unwrap_or(false)
-}
+    pub fn toggle(&mut self, category: DebugCategory) {
+        self.enabled.toggle(category);
+        info!("Debug categories = {:?}", self.enabled);
+    }
 
-pub fn stroke_circle(resources: &Resources, position: glam::Vec2, radius: f32, color: RgbaColor) {
-    if !show_gizmo(resources) {
-        return;
+    /// Meshes to draw this frame.
+    pub fn drain(&mut self) -> impl Iterator<Item = Mesh> + '_ {
+        self.meshes
+            .drain(..)
+            .chain(self.timed_meshes.iter().map(|(mesh, _)| mesh.clone()))
     }
 
-    match resources.fetch_mut::<DebugQueue>() {
-        Some(mut debug_queue) => {
-            let mut geometry: VertexBuffers<Point, u16> = VertexBuffers::new();
-            let color = color.to_normalized();
-            if let Err(e) = basic_shapes::stroke_circle(
-                Point::new(position.x(), position.y()),
-                radius,
-                &StrokeOptions::default(),
-                &mut simple_builder(&mut geometry),
-            ) {
-                error!("Error during stroke_line = {:?}", e);
-                return;
+    /// Labels to draw this frame.
+    pub fn drain_labels(&mut self) -> Vec<DebugLabel> {
+        self.labels
+            .drain(..)
+            .chain(self.timed_labels.iter().map(|(label, _)| label.clone()))
+            .collect()
+    }
+
+    /// Remove the timed shapes that have expired.
+    pub fn tick(&mut self, dt: Duration) {
+        let dt = dt.as_secs_f32();
+        for (_, remaining) in &mut self.timed_meshes {
+            *remaining -= dt;
+        }
+        for (_, remaining) in &mut self.timed_labels {
+            *remaining -= dt;
+        }
+        self.timed_meshes.retain(|(_, remaining)| *remaining > 0.0);
+        self.timed_labels.retain(|(_, remaining)| *remaining > 0.0);
+    }
+
+    fn push(&mut self, shape: DebugShape, color: RgbaColor, duration: Option<f32>) {
+        if let DebugShape::Label { position, text } = shape {
+            let label = DebugLabel {
+                position,
+                text,
+                color,
+            };
+            match duration {
+                Some(d) => self.timed_labels.push((label, d)),
+                None => self.labels.push(label),
             }
+            return;
+        }
 
-            debug_queue.0.push((
-                geometry
-                    .vertices
-                    .iter()
-                    .map(|p| Vertex {
-                        position: Position::new([p.x, p.y]),
-                        color: Color::new(color),
-                    })
-                    .collect::<Vec<_>>(),
-                geometry.indices,
-            ));
+        match tessellate(&shape, color) {
+            Ok(mesh) => match duration {
+                Some(d) => self.timed_meshes.push((mesh, d)),
+                None => self.meshes.push(mesh),
+            },
+            Err(e) => error!("Error during tessellation of {:?} = {:?}", shape, e),
         }
-        None => error!("No DebugQueue in resources"),
     }
 }
 
-pub fn stroke_quad(
+fn tessellate(
+    shape: &DebugShape,
+    color: RgbaColor,
+) -> Result<Mesh, lyon::tessellation::TessellationError> {
+    let mut geometry: VertexBuffers<Point, u16> = VertexBuffers::new();
+    let to_point = |v: glam::Vec2| Point::new(v.x(), v.y());
+    let stroke = StrokeOptions::default();
+    let fill = FillOptions::default();
+
+    match *shape {
+        DebugShape::Line { from, to } => {
+            basic_shapes::stroke_polyline(
+                vec![to_point(from), to_point(to)],
+                false,
+                &stroke,
+                &mut simple_builder(&mut geometry),
+            )?;
+        }
+        DebugShape::Arrow { from, to } => {
+            basic_shapes::stroke_polyline(
+                vec![to_point(from), to_point(to)],
+                false,
+                &stroke,
+                &mut simple_builder(&mut geometry),
+            )?;
+            let length = (to - from).length();
+            if length > 0.0 {
+                let back = (from - to) / length * length.min(20.0) * 0.5;
+                let left = glam::Mat2::from_angle(0.5) * back;
+                let right = glam::Mat2::from_angle(-0.5) * back;
+                basic_shapes::stroke_polyline(
+                    vec![to_point(to + left), to_point(to), to_point(to + right)],
+                    false,
+                    &stroke,
+                    &mut simple_builder(&mut geometry),
+                )?;
+            }
+        }
+        DebugShape::Circle {
+            center,
+            radius,
+            filled,
+        } => {
+            if filled {
+                basic_shapes::fill_circle(
+                    to_point(center),
+                    radius,
+                    &fill,
+                    &mut simple_builder(&mut geometry),
+                )?;
+            } else {
+                basic_shapes::stroke_circle(
+                    to_point(center),
+                    radius,
+                    &stroke,
+                    &mut simple_builder(&mut geometry),
+                )?;
+            }
+        }
+        DebugShape::Quad {
+            position,
+            dimensions,
+            filled,
+        } => {
+            if filled {
+                basic_shapes::fill_rectangle(
+                    &Rect::new(
+                        to_point(position),
+                        Size::new(dimensions.x(), dimensions.y()),
+                    ),
+                    &fill,
+                    &mut simple_builder(&mut geometry),
+                )?;
+            } else {
+                let p1 = position;
+                let p2 = position + glam::Vec2::unit_x() * dimensions.x();
+                let p3 = position + dimensions;
+                let p4 = position + glam::Vec2::unit_y() * dimensions.y();
+                basic_shapes::stroke_quad(
+                    to_point(p1),
+                    to_point(p2),
+                    to_point(p3),
+                    to_point(p4),
+                    &stroke,
+                    &mut simple_builder(&mut geometry),
+                )?;
+            }
+        }
+        DebugShape::Label { .. } => (),
+    }
+
+    let color = color.to_normalized();
+    Ok((
+        geometry
+            .vertices
+            .iter()
+            .map(|p| Vertex {
+                position: Position::new([p.x, p.y]),
+                color: Color::new(color),
+            })
+            .collect::<Vec<_>>(),
+        geometry.indices,
+    ))
+}
+
+fn add_shape(
     resources: &Resources,
-    position: glam::Vec2,
-    dimensions: glam::Vec2,
+    category: DebugCategory,
+    shape: DebugShape,
     color: RgbaColor,
+    duration: Option<f32>,
 ) {
-    if !show_gizmo(resources) {
+    if !ENABLED {
         return;
     }
 
     match resources.fetch_mut::<DebugQueue>() {
         Some(mut debug_queue) => {
-            let mut geometry: VertexBuffers<Point, u16> = VertexBuffers::new();
-            let color = color.to_normalized();
-
-            let p1 = position;
-            let p2 = position + glam::Vec2::unit_x() * dimensions.x();
-            let p3 = position
-                + glam::Vec2::unit_x() * dimensions.x()
-                + glam::Vec2::unit_y() * dimensions.y();
-            let p4 = position + glam::Vec2::unit_y() * dimensions.y();
-            if let Err(e) = basic_shapes::stroke_quad(
-                Point::new(p1.x(), p1.y()),
-                Point::new(p2.x(), p2.y()),
-                Point::new(p3.x(), p3.y()),
-                Point::new(p4.x(), p4.y()),
-                &StrokeOptions::default(),
-                &mut simple_builder(&mut geometry),
-            ) {
-                error!("Error during stroke_line = {:?}", e);
-                return;
+            if debug_queue.enabled.contains(category) {
+                debug_queue.push(shape, color, duration);
             }
-
-            debug_queue.0.push((
-                geometry
-                    .vertices
-                    .iter()
-                    .map(|p| Vertex {
-                        position: Position::new([p.x, p.y]),
-                        color: Color::new(color),
-                    })
-                    .collect::<Vec<_>>(),
-                geometry.indices,
-            ));
         }
         None => error!("No DebugQueue in resources"),
     }
 }
 
+/// Draw the shape for one frame.
+pub fn draw(resources: &Resources, category: DebugCategory, shape: DebugShape, color: RgbaColor) {
+    add_shape(resources, category, shape, color, None);
+}
+
+/// Draw the shape for some time (in seconds).
+pub fn draw_for(
+    resources: &Resources,
+    category: DebugCategory,
+    shape: DebugShape,
+    color: RgbaColor,
+    seconds: f32,
+) {
+    add_shape(resources, category, shape, color, Some(seconds));
+}
+
+pub fn stroke_circle(
+    resources: &Resources,
+    category: DebugCategory,
+    center: glam::Vec2,
+    radius: f32,
+    color: RgbaColor,
+) {
+    draw(
+        resources,
+        category,
+        DebugShape::Circle {
+            center,
+            radius,
+            filled: false,
+        },
+        color,
+    );
+}
+
+pub fn stroke_quad(
+    resources: &Resources,
+    category: DebugCategory,
+    position: glam::Vec2,
+    dimensions: glam::Vec2,
+    color: RgbaColor,
+) {
+    draw(
+        resources,
+        category,
+        DebugShape::Quad {
+            position,
+            dimensions,
+            filled: false,
+        },
+        color,
+    );
+}
+
 pub fn stroke_line(
     resources: &Resources,
+    category: DebugCategory,
+    from: glam::Vec2,
+    to: glam::Vec2,
+    color: RgbaColor,
+) {
+    draw(resources, category, DebugShape::Line { from, to }, color);
+}
+
+pub fn arrow(
+    resources: &Resources,
+    category: DebugCategory,
+    from: glam::Vec2,
+    to: glam::Vec2,
+    color: RgbaColor,
+) {
+    draw(resources, category, DebugShape::Arrow { from, to }, color);
+}
+
+pub fn label(
+    resources: &Resources,
+    category: DebugCategory,
     position: glam::Vec2,
-    target: glam::Vec2,
+    text: String,
     color: RgbaColor,
 ) {
-    if !show_gizmo(resources) {
-        return;
-    }
+    draw(
+        resources,
+        category,
+        DebugShape::Label { position, text },
+        color,
+    );
+}
 
-    match resources.fetch_mut::<DebugQueue>() {
-        Some(mut debug_queue) => {
-            let mut geometry: VertexBuffers<Point, u16> = VertexBuffers::new();
-            let color = color.to_normalized();
-            if let Err(e) = basic_shapes::stroke_polyline(
-                vec![
-                    Point::new(position.x(), position.y()),
-                    Point::new(target.x(), target.y()),
-                ],
-                false,
-                &StrokeOptions::default(),
-                &mut simple_builder(&mut geometry),
-            ) {
-                error!("Error during stroke_line = {:?}", e);
-                return;
-            }
+/// True if some labels should be drawn this frame.
+pub fn has_labels(resources: &Resources) -> bool {
+    ENABLED
+        && resources
+            .fetch::<DebugQueue>()
+            .map(|queue| !queue.labels.is_empty() || !queue.timed_labels.is_empty())
+            .unwrap_or(false)
+}
 
-            debug_queue.0.push((
-                geometry
-                    .vertices
-                    .iter()
-                    .map(|p| Vertex {
-                        position: Position::new([p.x, p.y]),
-                        color: Color::new(color),
-                    })
-                    .collect::<Vec<_>>(),
-                geometry.indices,
-            ));
-        }
-        None => error!("No DebugQueue in resources"),
+/// Add the labels to the UI, at their position on the screen.
+pub fn draw_labels(gui: &mut Gui, world: &World, resources: &Resources) {
+    let labels = match resources.fetch_mut::<DebugQueue>() {
+        Some(mut queue) => queue.drain_labels(),
+        None => return,
+    };
+    let (view, proj) = match (
+        get_view_matrix(world),
+        resources.fetch::<ProjectionMatrix>(),
+    ) {
+        (Some(view), Some(proj)) => (view, proj.matrix()),
+        _ => return,
+    };
+
+    let pv = proj * view;
+    let screen = glam::vec2(gui.window_dim.width as f32, gui.window_dim.height as f32);
+    for label in labels {
+        let ndc = pv * label.position.extend(0.0).extend(1.0);
+        // UI coordinates start at the top-left corner.
+        let pos = glam::vec2(
+            (ndc.x() + 1.0) / 2.0 * screen.x(),
+            (1.0 - ndc.y()) / 2.0 * screen.y(),
+        );
+        gui.sized_label(pos, label.text, LABEL_SIZE, label.color);
     }
 }
@@ -88,6 +88,10 @@ where
         }
     }
 
+    pub fn draw_calls(&self) -> usize {
+        self.tesses.len()
+    }
+
     pub fn render(
         &mut self,
         projection: &glam::Mat4,
@@ -39,22 +39,22 @@ pub struct Tint {
 #[derive(UniformInterface)]
 pub struct ShaderUniform {
     /// PROJECTION matrix in MVP
-    projection: Uniform<[[f32; 4]; 4]>,
+    pub(crate) projection: Uniform<[[f32; 4]; 4]>,
     /// VIEW matrix in MVP
-    view: Uniform<[[f32; 4]; 4]>,
+    pub(crate) view: Uniform<[[f32; 4]; 4]>,
     /// MODEL matrix in MVP
-    model: Uniform<[[f32; 4]; 4]>,
+    pub(crate) model: Uniform<[[f32; 4]; 4]>,
 
     /// Texture for the sprite.
-    tex: Uniform<TextureBinding<Dim2, NormUnsigned>>,
+    pub(crate) tex: Uniform<TextureBinding<Dim2, NormUnsigned>>,
 
     /// true if should blink.
-    should_blink: Uniform<bool>,
-    blink_color: Uniform<[f32; 4]>,
-    should_tint: Uniform<bool>,
-    tint_color: Uniform<[f32; 4]>,
-    time: Uniform<f32>,
-    amplitude: Uniform<f32>,
+    pub(crate) should_blink: Uniform<bool>,
+    pub(crate) blink_color: Uniform<[f32; 4]>,
+    pub(crate) should_tint: Uniform<bool>,
+    pub(crate) tint_color: Uniform<[f32; 4]>,
+    pub(crate) time: Uniform<f32>,
+    pub(crate) amplitude: Uniform<f32>,
 }
 
 pub fn new_shader<B>(surface: &mut B) -> Program<GL33, (), (), ShaderUniform>
@@ -79,6 +79,9 @@ where
     creation_time: Instant,
 
     shader: Program<S::Backend, (), (), ShaderUniform>,
+
+    /// Number of sprites drawn during the last frame.
+    draw_calls: usize,
 }
 
 impl<S> SpriteRenderer<S>
@@ -111,9 +114,14 @@ where
             tess,
             creation_time: Instant::now(),
             shader: new_shader(surface),
+            draw_calls: 0,
         }
     }
 
+    pub fn draw_calls(&self) -> usize {
+        self.draw_calls
+    }
+
     pub fn render(
         &mut self,
         pipeline: &Pipeline<S::Backend>,
@@ -128,6 +136,8 @@ where
         let tess = &self.tess;
 
         let elapsed = self.creation_time.elapsed().as_secs_f32();
+        let draw_calls = &mut self.draw_calls;
+        *draw_calls = 0;
 
         shd_gate.shade(shader, |mut iface, uni, mut rdr_gate| {
             iface.set(&uni.projection, proj_matrix.to_cols_array_2d());
@@ -166,6 +176,7 @@ where
                                     res = rdr_gate.render(render_state, |mut tess_gate| {
                                         tess_gate.render(tess)
                                     });
+                                    *draw_calls += 1;
                                 }
                                 Err(e) => {
                                     res = Err(e);
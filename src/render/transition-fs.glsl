@@ -0,0 +1,34 @@
+in vec2 v_uv;
+out vec4 frag;
+
+uniform int kind;
+uniform float coverage;
+uniform bool revealing;
+uniform vec4 color;
+
+const int FADE = 0;
+const int WIPE = 1;
+const int DISSOLVE = 2;
+
+// Size of the dissolve blocks in pixels.
+const float BLOCK_SIZE = 8.;
+// Width of the soft edge of the wipe.
+const float EDGE = .05;
+
+float hash(vec2 p) {
+  return fract(sin(dot(p, vec2(12.9898, 78.233))) * 43758.5453);
+}
+
+void main() {
+  float alpha = coverage;
+  if (kind == WIPE) {
+    // Covers from the left, then reveals from the left as well.
+    float x = revealing ? 1. - v_uv.x : v_uv.x;
+    float edge = coverage * (1. + EDGE);
+    alpha = 1. - smoothstep(edge - EDGE, edge, x);
+  } else if (kind == DISSOLVE) {
+    float noise = hash(floor(gl_FragCoord.xy / BLOCK_SIZE));
+    alpha = step(noise, coverage);
+  }
+  frag = vec4(color.rgb, color.a * alpha);
+}
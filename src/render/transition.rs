@@ -0,0 +1,99 @@
+//! Full-screen overlay drawn above everything while the scenes are switching.
+use crate::core::scene::{TransitionKind, TransitionOverlay};
+use luminance::blending::{Blending, Equation, Factor};
+use luminance::context::GraphicsContext;
+use luminance::pipeline::PipelineError;
+use luminance::render_state::RenderState;
+use luminance::shader::{Program, Uniform};
+use luminance::shading_gate::ShadingGate;
+use luminance::tess::{Mode, Tess};
+use luminance_derive::UniformInterface;
+use luminance_gl::GL33;
+
+const VS: &str = include_str!("transition-vs.glsl");
+const FS: &str = include_str!("transition-fs.glsl");
+
+#[derive(UniformInterface)]
+pub struct TransitionUniform {
+    /// Fade, wipe or dissolve. See the constants in the fragment shader.
+    kind: Uniform<i32>,
+    coverage: Uniform<f32>,
+    revealing: Uniform<bool>,
+    color: Uniform<[f32; 4]>,
+}
+
+pub struct TransitionRenderer<S>
+where
+    S: GraphicsContext<Backend = GL33>,
+{
+    render_st: RenderState,
+    tess: Tess<S::Backend, ()>,
+    shader: Program<S::Backend, (), (), TransitionUniform>,
+    /// Transition to draw this frame.
+    overlay: Option<TransitionOverlay>,
+}
+
+impl<S> TransitionRenderer<S>
+where
+    S: GraphicsContext<Backend = GL33>,
+{
+    pub fn new(surface: &mut S) -> Self {
+        let render_st = RenderState::default()
+            .set_depth_test(None)
+            .set_blending(Blending {
+                equation: Equation::Additive,
+                src: Factor::SrcAlpha,
+                dst: Factor::SrcAlphaComplement,
+            });
+        let tess = surface
+            .new_tess()
+            .set_vertex_nb(4)
+            .set_mode(Mode::TriangleFan)
+            .build()
+            .expect("Tess creation");
+        let shader = surface
+            .new_shader_program::<(), (), TransitionUniform>()
+            .from_strings(VS, None, None, FS)
+            .expect("Program creation")
+            .ignore_warnings();
+        Self {
+            render_st,
+            tess,
+            shader,
+            overlay: None,
+        }
+    }
+
+    pub fn prepare(&mut self, overlay: Option<TransitionOverlay>) {
+        self.overlay = overlay;
+    }
+
+    pub fn draw_calls(&self) -> usize {
+        if self.overlay.is_some() {
+            1
+        } else {
+            0
+        }
+    }
+
+    pub fn render(&mut self, shd_gate: &mut ShadingGate<S::Backend>) -> Result<(), PipelineError> {
+        let overlay = match self.overlay {
+            Some(overlay) => overlay,
+            None => return Ok(()),
+        };
+        let render_state = &self.render_st;
+        let tess = &self.tess;
+        shd_gate.shade(&mut self.shader, |mut iface, uni, mut rdr_gate| {
+            let kind = match overlay.kind {
+                TransitionKind::Fade => 0,
+                TransitionKind::Wipe => 1,
+                TransitionKind::Dissolve => 2,
+            };
+            iface.set(&uni.kind, kind);
+            iface.set(&uni.coverage, overlay.coverage);
+            iface.set(&uni.revealing, overlay.revealing);
+            iface.set(&uni.color, overlay.color.to_normalized());
+            rdr_gate.render(render_state, |mut tess_gate| tess_gate.render(tess))
+        })
+    }
+}
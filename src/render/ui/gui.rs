@@ -1,8 +1,9 @@
 use crate::core::colors::RgbaColor;
 use crate::core::window::WindowDim;
+use crate::render::ui::layout::Rect;
 use crate::render::ui::text::Text;
 use crate::render::ui::{text, Button, DrawData, Panel, FONT_DATA};
-use glfw::{Action, MouseButton, WindowEvent};
+use glfw::{Action, GamepadButton, GamepadState, Key, Modifiers, MouseButton, WindowEvent};
 use glyph_brush::GlyphBrushBuilder;
 use serde_derive::{Deserialize, Serialize};
 use std::cell::RefCell;
@@ -11,13 +12,83 @@ use std::rc::Rc;
 use glyph_brush::rusttype::Scale;
 use glyph_brush::{GlyphBrush, GlyphCruncher, Layout, Section};
 
+/// State of the widgets that needs to be kept between frames.
+#[derive(Debug, Default)]
+pub(crate) struct WidgetState {
+    /// Widget that receives the keyboard inputs.
+    pub(crate) focused: Option<String>,
+    /// Widget that is currently pressed (e.g. slider being dragged).
+    pub(crate) active: Option<String>,
+    /// Dropdown that is currently open.
+    pub(crate) open: Option<String>,
+    /// Widgets that can receive the focus, in the order they were built during the last frame.
+    pub(crate) focusables: Vec<String>,
+    /// Widgets that use the arrows themselves when focused (e.g. slider). Only tab will move
+    /// the focus away from them.
+    pub(crate) captures: Vec<String>,
+    /// Areas covered by an overlay (e.g. open dropdown) during the last frame. A click inside
+    /// them is only given to the overlay.
+    pub(crate) overlay_rects: Vec<Rect>,
+}
+
+/// Navigation between and inside widgets, from the keyboard or the gamepad.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NavInput {
+    Up,
+    Down,
+    Left,
+    Right,
+    /// Tab
+    Next,
+    /// Shift+Tab
+    Previous,
+    /// Enter, Space or gamepad A
+    Activate,
+    /// Escape or gamepad B
+    Cancel,
+}
+
+/// Gamepad buttons used for navigation.
+const GAMEPAD_NAV: [(GamepadButton, NavInput); 6] = [
+    (GamepadButton::ButtonDpadUp, NavInput::Up),
+    (GamepadButton::ButtonDpadDown, NavInput::Down),
+    (GamepadButton::ButtonDpadLeft, NavInput::Left),
+    (GamepadButton::ButtonDpadRight, NavInput::Right),
+    (GamepadButton::ButtonA, NavInput::Activate),
+    (GamepadButton::ButtonB, NavInput::Cancel),
+];
+
+/// Inputs received during the frame.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct FrameInputs {
+    pub(crate) mouse_clicked: Vec<MouseButton>,
+    /// The left click landed on an overlay. It is not in `mouse_clicked` so the widgets below
+    /// the overlay do not receive it.
+    pub(crate) overlay_clicked: bool,
+    pub(crate) mouse_released: Vec<MouseButton>,
+    /// Left mouse button is held.
+    pub(crate) mouse_down: bool,
+    /// Text typed during the frame.
+    pub(crate) chars: Vec<char>,
+    /// Keys pressed (or repeated) during the frame.
+    pub(crate) keys: Vec<Key>,
+    /// Vertical scroll during the frame.
+    pub(crate) scroll: f32,
+    /// Navigation inputs during the frame.
+    pub(crate) nav: Vec<NavInput>,
+}
+
 pub struct GuiContext {
     pub(crate) window_dim: WindowDim,
     pub(crate) mouse_pos: glam::Vec2,
-    pub(crate) mouse_clicked: Vec<MouseButton>,
+    pub(crate) inputs: FrameInputs,
     pub(crate) style: Style,
 
     pub(crate) fonts: Rc<RefCell<GlyphBrush<'static, text::Instance>>>,
+    pub(crate) state: Rc<RefCell<WidgetState>>,
+
+    /// Gamepad navigation buttons that were held during the last frame.
+    gamepad_held: [bool; GAMEPAD_NAV.len()],
 }
 
 impl GuiContext {
@@ -30,62 +101,348 @@ impl GuiContext {
             fonts: Rc::new(RefCell::new(fonts)),
             window_dim,
             mouse_pos: glam::Vec2::zero(),
-            mouse_clicked: vec![],
+            inputs: FrameInputs::default(),
             style: Style::default(),
+            state: Rc::new(RefCell::new(WidgetState::default())),
+            gamepad_held: [false; GAMEPAD_NAV.len()],
         }
     }
 
     pub fn reset_inputs(&mut self) {
-        self.mouse_clicked.clear();
+        self.inputs.mouse_clicked.clear();
+        self.inputs.mouse_released.clear();
+        self.inputs.chars.clear();
+        self.inputs.keys.clear();
+        self.inputs.scroll = 0.0;
+        self.inputs.nav.clear();
     }
 
     pub fn process_event(&mut self, window_event: WindowEvent) {
         match window_event {
-            WindowEvent::MouseButton(btn, Action::Press, _) => self.mouse_clicked.push(btn),
+            WindowEvent::MouseButton(btn, Action::Press, _) => {
+                if let MouseButton::Button1 = btn {
+                    self.inputs.mouse_down = true;
+                }
+                self.inputs.mouse_clicked.push(btn)
+            }
+            WindowEvent::MouseButton(btn, Action::Release, _) => {
+                if let MouseButton::Button1 = btn {
+                    self.inputs.mouse_down = false;
+                }
+                self.inputs.mouse_released.push(btn)
+            }
             WindowEvent::CursorPos(x, y) => {
                 self.mouse_pos.set_x(x as f32);
                 self.mouse_pos.set_y(y as f32);
             }
+            WindowEvent::Char(c) => self.inputs.chars.push(c),
+            WindowEvent::Key(key, _, Action::Press, mods)
+            | WindowEvent::Key(key, _, Action::Repeat, mods) => {
+                let nav = match key {
+                    Key::Up => Some(NavInput::Up),
+                    Key::Down => Some(NavInput::Down),
+                    Key::Left => Some(NavInput::Left),
+                    Key::Right => Some(NavInput::Right),
+                    Key::Tab if mods.contains(Modifiers::Shift) => Some(NavInput::Previous),
+                    Key::Tab => Some(NavInput::Next),
+                    Key::Enter | Key::KpEnter | Key::Space => Some(NavInput::Activate),
+                    Key::Escape => Some(NavInput::Cancel),
+                    _ => None,
+                };
+                self.inputs.nav.extend(nav);
+                self.inputs.keys.push(key)
+            }
+            WindowEvent::Scroll(_, y) => self.inputs.scroll += y as f32,
             _ => (),
         }
     }
 
+    /// Generate navigation inputs from the gamepad. Only the buttons that were just pressed
+    /// are used.
+    pub fn process_gamepad(&mut self, state: &GamepadState) {
+        for (i, (button, nav)) in GAMEPAD_NAV.iter().enumerate() {
+            let held = state.get_button_state(*button) == Action::Press;
+            if held && !self.gamepad_held[i] {
+                self.inputs.nav.push(*nav);
+            }
+            self.gamepad_held[i] = held;
+        }
+    }
+
+    /// Remove the keyboard focus from all widgets.
+    pub fn clear_focus(&self) {
+        self.state.borrow_mut().focused = None;
+    }
+
+    /// Move the focus according to the navigation inputs. Widgets are ordered as they were
+    /// built during the last frame. Returns the inputs that were not used.
+    fn navigate(&self) -> Vec<NavInput> {
+        let mut state = self.state.borrow_mut();
+        let order = std::mem::take(&mut state.focusables);
+        let captures = std::mem::take(&mut state.captures);
+
+        let mut current = state
+            .focused
+            .as_ref()
+            .and_then(|f| order.iter().position(|o| o == f));
+        let mut captured = current
+            .map(|i| captures.contains(&order[i]))
+            .unwrap_or(false);
+
+        let mut remaining = vec![];
+        for nav in &self.inputs.nav {
+            let dir: isize = match nav {
+                NavInput::Next => 1,
+                NavInput::Previous => -1,
+                NavInput::Down | NavInput::Right if !captured => 1,
+                NavInput::Up | NavInput::Left if !captured => -1,
+                _ => {
+                    remaining.push(*nav);
+                    continue;
+                }
+            };
+            if order.is_empty() {
+                remaining.push(*nav);
+                continue;
+            }
+
+            let len = order.len() as isize;
+            let next = match current {
+                Some(i) => (i as isize + dir).rem_euclid(len) as usize,
+                None if dir > 0 => 0,
+                None => order.len() - 1,
+            };
+            state.focused = Some(order[next].clone());
+            captured = captures.contains(&order[next]);
+            current = Some(next);
+        }
+
+        remaining
+    }
+
     pub fn new_frame(&self) -> Gui {
+        let mut inputs = self.inputs.clone();
+        inputs.nav = self.navigate();
+        let overlay_rects = std::mem::take(&mut self.state.borrow_mut().overlay_rects);
+        if inputs.mouse_clicked.contains(&MouseButton::Button1)
+            && overlay_rects.iter().any(|r| r.contains(self.mouse_pos))
+        {
+            inputs
+                .mouse_clicked
+                .retain(|btn| *btn != MouseButton::Button1);
+            inputs.overlay_clicked = true;
+        }
         Gui::new(
             self.window_dim,
             self.mouse_pos,
-            self.mouse_clicked.clone(),
+            inputs,
             self.style,
             Rc::clone(&self.fonts),
+            Rc::clone(&self.state),
         )
     }
 }
 
+/// Result of the interaction between the mouse and a widget.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Interaction {
+    /// Mouse is above the widget
+    pub hovered: bool,
+    /// Mouse button is held after pressing the widget.
+    pub pressed: bool,
+    /// Widget has been clicked during this frame.
+    pub clicked: bool,
+    /// Widget has the keyboard focus.
+    pub focused: bool,
+}
+
 pub struct Gui {
     pub(crate) draw_data: Vec<DrawData>,
+    /// Drawn after everything else (e.g. open dropdown).
+    pub(crate) overlay: Vec<DrawData>,
     pub(crate) window_dim: WindowDim,
     pub(crate) mouse_pos: glam::Vec2,
-    pub(crate) mouse_clicked: Vec<MouseButton>,
+    pub(crate) inputs: FrameInputs,
     pub(crate) style: Style,
     pub(crate) fonts: Rc<RefCell<GlyphBrush<'static, text::Instance>>>,
+    pub(crate) state: Rc<RefCell<WidgetState>>,
 }
 
 impl Gui {
-    pub fn new(
+    pub(crate) fn new(
         window_dim: WindowDim,
         mouse_pos: glam::Vec2,
-        mouse_clicked: Vec<MouseButton>,
+        inputs: FrameInputs,
         style: Style,
         fonts: Rc<RefCell<GlyphBrush<'static, text::Instance>>>,
+        state: Rc<RefCell<WidgetState>>,
     ) -> Self {
         Self {
             draw_data: vec![],
+            overlay: vec![],
             window_dim,
-            mouse_clicked,
+            inputs,
             mouse_pos,
             style,
             fonts,
+            state,
+        }
+    }
+
+    /// True if the mouse is above the rectangle.
+    pub fn is_hovered(&self, anchor: glam::Vec2, dimensions: glam::Vec2) -> bool {
+        Rect::new(anchor, dimensions).contains(self.mouse_pos)
+    }
+
+    pub fn is_focused(&self, id: &str) -> bool {
+        self.state.borrow().focused.as_deref() == Some(id)
+    }
+
+    /// Give the keyboard focus to the widget.
+    pub fn focus(&self, id: &str) {
+        self.state.borrow_mut().focused = Some(id.to_string());
+    }
+
+    /// Widgets will ignore the mouse clicks and navigation inputs of this frame.
+    pub fn consume_inputs(&mut self) {
+        self.inputs.mouse_clicked.clear();
+        self.inputs.overlay_clicked = false;
+        self.inputs.nav.clear();
+    }
+
+    /// True if a widget has the focus.
+    pub fn has_focus(&self) -> bool {
+        self.state.borrow().focused.is_some()
+    }
+
+    /// True if the key has been pressed during this frame.
+    pub fn key_pressed(&self, key: Key) -> bool {
+        self.inputs.keys.contains(&key)
+    }
+
+    /// True if the navigation input has been received during this frame and was not used to
+    /// move the focus.
+    pub fn nav(&self, input: NavInput) -> bool {
+        self.inputs.nav.contains(&input)
+    }
+
+    /// The focused widget will use the arrows instead of moving the focus to another widget.
+    pub fn capture_nav(&mut self, id: &str) {
+        self.state.borrow_mut().captures.push(id.to_string());
+    }
+
+    /// Highlight the focused widget.
+    pub fn draw_focus(&mut self, pos: glam::Vec2, dimensions: glam::Vec2) {
+        let color = self.style.focus_color;
+        self.outline(pos, dimensions, color);
+    }
+
+    /// Find how the mouse interacts with a widget. Clicking on the widget will give it the focus
+    /// and clicking somewhere else will remove it. The widget will also be reachable with the
+    /// navigation inputs.
+    pub fn interact(
+        &mut self,
+        id: &str,
+        anchor: glam::Vec2,
+        dimensions: glam::Vec2,
+    ) -> Interaction {
+        let hovered = self.is_hovered(anchor, dimensions);
+        let clicked_now = self.inputs.mouse_clicked.contains(&MouseButton::Button1);
+        let mut state = self.state.borrow_mut();
+        state.focusables.push(id.to_string());
+
+        let mut clicked = false;
+        if clicked_now {
+            if hovered {
+                clicked = true;
+                state.active = Some(id.to_string());
+                state.focused = Some(id.to_string());
+            } else if state.focused.as_deref() == Some(id) {
+                state.focused = None;
+            }
         }
+
+        let is_active = state.active.as_deref() == Some(id);
+        if is_active && !self.inputs.mouse_down {
+            state.active = None;
+        }
+
+        Interaction {
+            hovered,
+            pressed: is_active && self.inputs.mouse_down,
+            clicked,
+            focused: state.focused.as_deref() == Some(id),
+        }
+    }
+
+    /// Draw a rectangle outline.
+    pub fn outline(&mut self, pos: glam::Vec2, dimensions: glam::Vec2, color: RgbaColor) {
+        let t = 2.0;
+        self.panel(
+            pos - glam::vec2(t, t),
+            glam::vec2(dimensions.x() + 2.0 * t, t),
+            color,
+        );
+        self.panel(
+            pos + glam::vec2(-t, dimensions.y()),
+            glam::vec2(dimensions.x() + 2.0 * t, t),
+            color,
+        );
+        self.panel(
+            pos - glam::vec2(t, 0.0),
+            glam::vec2(t, dimensions.y()),
+            color,
+        );
+        self.panel(
+            pos + glam::vec2(dimensions.x(), 0.0),
+            glam::vec2(t, dimensions.y()),
+            color,
+        );
+    }
+
+    /// Draw a panel above all the other widgets. The clicks on the panel during the next frame
+    /// are not given to the widgets below it (see `FrameInputs::overlay_clicked`).
+    pub(crate) fn overlay_panel(
+        &mut self,
+        pos: glam::Vec2,
+        dimensions: glam::Vec2,
+        color: RgbaColor,
+    ) {
+        self.state
+            .borrow_mut()
+            .overlay_rects
+            .push(Rect::new(pos, dimensions));
+        let (vertices, indices) = Panel {
+            anchor: pos,
+            dimensions,
+            color,
+        }
+        .vertices(self.window_dim);
+        self.overlay.push(DrawData::Vertices(vertices, indices));
+    }
+
+    pub(crate) fn overlay_label(
+        &mut self,
+        pos: glam::Vec2,
+        text: String,
+        font_size: f32,
+        color: RgbaColor,
+    ) {
+        self.overlay.push(DrawData::Text(
+            Text {
+                content: text,
+                font_size,
+                color,
+                align: (HorizontalAlign::Left, VerticalAlign::Top),
+            },
+            pos,
+        ));
+    }
+
+    /// Draw a sprite.
+    pub fn image(&mut self, sprite_id: &str, pos: glam::Vec2, dimensions: glam::Vec2) {
+        self.draw_data
+            .push(DrawData::Image(sprite_id.to_string(), pos, dimensions));
     }
     pub fn panel(&mut self, pos: glam::Vec2, dimensions: glam::Vec2, color: RgbaColor) {
         let (vertices, indices) = Panel {
@@ -134,6 +491,18 @@ impl Gui {
         ));
     }
 
+    pub fn sized_label(&mut self, pos: glam::Vec2, text: String, font_size: f32, color: RgbaColor) {
+        self.draw_data.push(DrawData::Text(
+            Text {
+                content: text,
+                font_size,
+                color,
+                align: (HorizontalAlign::Left, VerticalAlign::Top),
+            },
+            pos,
+        ));
+    }
+
     pub fn button(
         &mut self,
         pos: glam::Vec2,
@@ -221,6 +590,20 @@ pub struct Style {
     pub button_text_color: RgbaColor,
     /// color for text when button is hovered
     pub button_hovered_text_color: RgbaColor,
+    /// background color for buttons that are pressed
+    pub button_pressed_bg_color: RgbaColor,
+    /// background of the widgets (slider track, checkbox, text input...)
+    pub widget_bg_color: RgbaColor,
+    /// background of the widgets when hovered
+    pub widget_hover_bg_color: RgbaColor,
+    /// foreground of the widgets (slider fill, checkbox mark, selected item...)
+    pub widget_fg_color: RgbaColor,
+    /// foreground of the widgets when pressed
+    pub widget_pressed_fg_color: RgbaColor,
+    /// color of the outline around the focused widget
+    pub focus_color: RgbaColor,
+    /// color of the text in disabled widgets
+    pub disabled_text_color: RgbaColor,
     /// Horizontal and vertical align for text in a button
     pub button_text_align: (HorizontalAlign, VerticalAlign),
     /// text color
@@ -236,6 +619,13 @@ impl Default for Style {
             button_hover_bg_color: RgbaColor::new(155, 155, 155, 155),
             button_text_color: RgbaColor::new(255, 255, 255, 255),
             button_hovered_text_color: RgbaColor::new(255, 255, 255, 255),
+            button_pressed_bg_color: RgbaColor::new(100, 100, 100, 200),
+            widget_bg_color: RgbaColor::new(60, 60, 60, 200),
+            widget_hover_bg_color: RgbaColor::new(80, 80, 80, 220),
+            widget_fg_color: RgbaColor::new(1, 200, 200, 255),
+            widget_pressed_fg_color: RgbaColor::new(1, 255, 255, 255),
+            focus_color: RgbaColor::new(1, 255, 255, 255),
+            disabled_text_color: RgbaColor::new(128, 128, 128, 255),
             button_text_align: (HorizontalAlign::Center, VerticalAlign::Center),
             text_color: RgbaColor::new(255, 255, 255, 255),
             font_size: 16.0,
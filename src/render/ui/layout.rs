@@ -0,0 +1,532 @@
+//! Retained layout on top of the immediate mode `Gui`. A layout is a tree of nodes that can be
+//! defined in code or loaded from a JSON file. Positions are computed from anchors, stacks,
+//! padding, margins and sizes so that the UI reflows when the window is resized.
+//!
+//! ```no_run
+//! use spacegame::render::ui::layout::{Layout, Node};
+//!
+//! let mut layout = Layout::new(
+//!     Node::vertical_stack(16.0)
+//!         .with_child(Node::label("Hello"))
+//!         .with_child(Node::button("ok", "OK")),
+//! );
+//! // then, in prepare_gui: `let clicked = layout.build(&mut gui);`
+//! ```
+use crate::core::colors::RgbaColor;
+use crate::render::ui::{Button, Gui, HorizontalAlign, VerticalAlign};
+use serde_derive::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Rectangle on the screen. Position is the top-left corner.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Rect {
+    pub position: glam::Vec2,
+    pub size: glam::Vec2,
+}
+
+impl Rect {
+    pub fn new(position: glam::Vec2, size: glam::Vec2) -> Self {
+        Self { position, size }
+    }
+
+    /// Remove the spacing from all sides of the rectangle.
+    pub fn shrink(&self, spacing: Spacing) -> Self {
+        Self {
+            position: self.position + glam::vec2(spacing.left, spacing.top),
+            size: (self.size - spacing.total()).max(glam::Vec2::zero()),
+        }
+    }
+
+    /// The left and top edges are inside, the right and bottom edges are outside so that a point
+    /// is in only one of two adjacent rectangles. Used for all the hit tests of the UI.
+    pub fn contains(&self, point: glam::Vec2) -> bool {
+        let rel = point - self.position;
+        rel.x() >= 0.0 && rel.x() < self.size.x() && rel.y() >= 0.0 && rel.y() < self.size.y()
+    }
+}
+
+/// Where a node is placed in the area given by its parent.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Anchor {
+    #[default]
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Fraction of the free space that is left before the node, horizontally and vertically.
+    fn factors(self) -> glam::Vec2 {
+        use Anchor::*;
+        match self {
+            TopLeft => glam::vec2(0.0, 0.0),
+            Top => glam::vec2(0.5, 0.0),
+            TopRight => glam::vec2(1.0, 0.0),
+            Left => glam::vec2(0.0, 0.5),
+            Center => glam::vec2(0.5, 0.5),
+            Right => glam::vec2(1.0, 0.5),
+            BottomLeft => glam::vec2(0.0, 1.0),
+            Bottom => glam::vec2(0.5, 1.0),
+            BottomRight => glam::vec2(1.0, 1.0),
+        }
+    }
+}
+
+/// How to compute the width or height of a node.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Size {
+    /// Size in pixels.
+    Fixed(f32),
+    /// Fraction of the parent content size (between 0 and 1).
+    Relative(f32),
+    /// Just enough space for the content.
+    #[default]
+    Fit,
+}
+
+/// Space around a node (margin) or between a node and its content (padding).
+#[derive(Debug, Copy, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Spacing {
+    #[serde(default)]
+    pub left: f32,
+    #[serde(default)]
+    pub right: f32,
+    #[serde(default)]
+    pub top: f32,
+    #[serde(default)]
+    pub bottom: f32,
+}
+
+impl Spacing {
+    pub fn all(value: f32) -> Self {
+        Self {
+            left: value,
+            right: value,
+            top: value,
+            bottom: value,
+        }
+    }
+
+    /// Horizontal and vertical total.
+    pub fn total(&self) -> glam::Vec2 {
+        glam::vec2(self.left + self.right, self.top + self.bottom)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum NodeKind {
+    /// Children are placed according to their anchor in the content area.
+    Container,
+    /// Children are placed on top of each other.
+    VerticalStack { spacing: f32 },
+    /// Children are placed next to each other.
+    HorizontalStack { spacing: f32 },
+    Label {
+        text: String,
+        #[serde(default)]
+        font_size: Option<f32>,
+        #[serde(default)]
+        color: Option<RgbaColor>,
+    },
+    Button {
+        text: String,
+        #[serde(default)]
+        font_size: Option<f32>,
+        /// Normal and hovered colors of the text.
+        #[serde(default)]
+        text_color: Option<(RgbaColor, RgbaColor)>,
+        /// Normal and hovered colors of the background.
+        #[serde(default)]
+        bg_color: Option<(RgbaColor, RgbaColor)>,
+    },
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Node {
+    /// Used to find the node from the code, and as the name of the clicked buttons.
+    #[serde(default)]
+    pub id: Option<String>,
+    pub kind: NodeKind,
+
+    #[serde(default)]
+    pub anchor: Anchor,
+    #[serde(default)]
+    pub width: Size,
+    #[serde(default)]
+    pub height: Size,
+    #[serde(default)]
+    pub padding: Spacing,
+    #[serde(default)]
+    pub margin: Spacing,
+    #[serde(default)]
+    pub background: Option<RgbaColor>,
+
+    /// Hidden nodes do not take any space.
+    #[serde(default = "default_true")]
+    pub visible: bool,
+    /// Disabled buttons are greyed out and cannot be clicked.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    #[serde(default)]
+    pub children: Vec<Node>,
+
+    /// Computed by the layout.
+    #[serde(skip)]
+    rect: Rect,
+}
+
+impl Node {
+    pub fn new(kind: NodeKind) -> Self {
+        Self {
+            id: None,
+            kind,
+            anchor: Anchor::default(),
+            width: Size::default(),
+            height: Size::default(),
+            padding: Spacing::default(),
+            margin: Spacing::default(),
+            background: None,
+            visible: true,
+            enabled: true,
+            children: vec![],
+            rect: Rect::default(),
+        }
+    }
+
+    pub fn container() -> Self {
+        Self::new(NodeKind::Container)
+    }
+
+    pub fn vertical_stack(spacing: f32) -> Self {
+        Self::new(NodeKind::VerticalStack { spacing })
+    }
+
+    pub fn horizontal_stack(spacing: f32) -> Self {
+        Self::new(NodeKind::HorizontalStack { spacing })
+    }
+
+    pub fn label(text: &str) -> Self {
+        Self::new(NodeKind::Label {
+            text: text.to_string(),
+            font_size: None,
+            color: None,
+        })
+    }
+
+    pub fn button(id: &str, text: &str) -> Self {
+        Self::new(NodeKind::Button {
+            text: text.to_string(),
+            font_size: None,
+            text_color: None,
+            bg_color: None,
+        })
+        .with_id(id)
+    }
+
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    pub fn with_anchor(mut self, anchor: Anchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    pub fn with_size(mut self, width: Size, height: Size) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn with_padding(mut self, padding: Spacing) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    pub fn with_margin(mut self, margin: Spacing) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    pub fn with_background(mut self, color: RgbaColor) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    pub fn with_child(mut self, child: Node) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Position and size computed during the last layout.
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn find_mut(&mut self, id: &str) -> Option<&mut Node> {
+        if self.id.as_deref() == Some(id) {
+            return Some(self);
+        }
+        self.children.iter_mut().find_map(|c| c.find_mut(id))
+    }
+
+    fn find(&self, id: &str) -> Option<&Node> {
+        if self.id.as_deref() == Some(id) {
+            return Some(self);
+        }
+        self.children.iter().find_map(|c| c.find(id))
+    }
+
+    /// Size needed by the content, padding included.
+    fn measure(&self, gui: &mut Gui) -> glam::Vec2 {
+        let content = match &self.kind {
+            NodeKind::Label {
+                text, font_size, ..
+            }
+            | NodeKind::Button {
+                text, font_size, ..
+            } => gui.text_bounds(text, font_size.unwrap_or(gui.style.font_size)),
+            NodeKind::Container => self
+                .visible_children()
+                .map(|c| c.outer_size(gui, glam::Vec2::zero()))
+                .fold(glam::Vec2::zero(), |a, b| a.max(b)),
+            NodeKind::VerticalStack { spacing } => {
+                let (size, nb) =
+                    self.visible_children()
+                        .fold((glam::Vec2::zero(), 0), |(acc, nb), c| {
+                            let s = c.outer_size(gui, glam::Vec2::zero());
+                            (glam::vec2(acc.x().max(s.x()), acc.y() + s.y()), nb + 1)
+                        });
+                size + glam::Vec2::unit_y() * *spacing * (nb.max(1) - 1) as f32
+            }
+            NodeKind::HorizontalStack { spacing } => {
+                let (size, nb) =
+                    self.visible_children()
+                        .fold((glam::Vec2::zero(), 0), |(acc, nb), c| {
+                            let s = c.outer_size(gui, glam::Vec2::zero());
+                            (glam::vec2(acc.x() + s.x(), acc.y().max(s.y())), nb + 1)
+                        });
+                size + glam::Vec2::unit_x() * *spacing * (nb.max(1) - 1) as f32
+            }
+        };
+
+        content + self.padding.total()
+    }
+
+    /// Size of the node without margins. Relative sizes are computed from `parent_content`.
+    fn size(&self, gui: &mut Gui, parent_content: glam::Vec2) -> glam::Vec2 {
+        let measured = if self.width == Size::Fit || self.height == Size::Fit {
+            self.measure(gui)
+        } else {
+            glam::Vec2::zero()
+        };
+
+        let resolve = |size: Size, parent: f32, measured: f32| match size {
+            Size::Fixed(v) => v,
+            Size::Relative(f) => f * parent,
+            Size::Fit => measured,
+        };
+        glam::vec2(
+            resolve(self.width, parent_content.x(), measured.x()),
+            resolve(self.height, parent_content.y(), measured.y()),
+        )
+    }
+
+    /// Size of the node with its margins.
+    fn outer_size(&self, gui: &mut Gui, parent_content: glam::Vec2) -> glam::Vec2 {
+        self.size(gui, parent_content) + self.margin.total()
+    }
+
+    fn visible_children(&self) -> impl Iterator<Item = &Node> {
+        self.children.iter().filter(|c| c.visible)
+    }
+
+    /// Place the node in the given slot, then place its children.
+    fn arrange(&mut self, gui: &mut Gui, slot: Rect, parent_content: glam::Vec2) {
+        let size = self.size(gui, parent_content);
+        let area = slot.shrink(self.margin);
+        let free = (area.size - size).max(glam::Vec2::zero());
+        self.rect = Rect::new(area.position + free * self.anchor.factors(), size);
+
+        let content = self.rect.shrink(self.padding);
+        match self.kind {
+            NodeKind::Container => {
+                for child in self.children.iter_mut().filter(|c| c.visible) {
+                    child.arrange(gui, content, content.size);
+                }
+            }
+            NodeKind::VerticalStack { spacing } => {
+                let mut cursor = content.position;
+                for child in self.children.iter_mut().filter(|c| c.visible) {
+                    let height = child.outer_size(gui, content.size).y();
+                    let slot = Rect::new(cursor, glam::vec2(content.size.x(), height));
+                    child.arrange(gui, slot, content.size);
+                    cursor += glam::Vec2::unit_y() * (height + spacing);
+                }
+            }
+            NodeKind::HorizontalStack { spacing } => {
+                let mut cursor = content.position;
+                for child in self.children.iter_mut().filter(|c| c.visible) {
+                    let width = child.outer_size(gui, content.size).x();
+                    let slot = Rect::new(cursor, glam::vec2(width, content.size.y()));
+                    child.arrange(gui, slot, content.size);
+                    cursor += glam::Vec2::unit_x() * (width + spacing);
+                }
+            }
+            NodeKind::Label { .. } | NodeKind::Button { .. } => (),
+        }
+    }
+
+    fn draw(&self, gui: &mut Gui, clicked: &mut Vec<String>) {
+        if !self.visible {
+            return;
+        }
+
+        if let Some(color) = self.background {
+            gui.panel(self.rect.position, self.rect.size, color);
+        }
+
+        let content = self.rect.shrink(self.padding);
+        match &self.kind {
+            NodeKind::Label {
+                text,
+                font_size,
+                color,
+            } => {
+                let font_size = font_size.unwrap_or(gui.style.font_size);
+                let color = color.unwrap_or(gui.style.text_color);
+                gui.sized_label(content.position, text.clone(), font_size, color);
+            }
+            NodeKind::Button {
+                text,
+                font_size,
+                text_color,
+                bg_color,
+            } => {
+                let mut button = Button::new(text.clone(), content.position)
+                    .dimensions(content.size)
+                    .set_text_align(HorizontalAlign::Left, VerticalAlign::Top)
+                    .set_padding(0.0)
+                    .set_enabled(self.enabled);
+                if let Some(ref id) = self.id {
+                    button = button.set_id(id);
+                }
+                if let Some(font_size) = font_size {
+                    button = button.set_font_size(*font_size);
+                }
+                if let Some((color, hover)) = bg_color {
+                    button = button.set_bg_color(*color, *hover);
+                }
+                if let Some((color, hover)) = text_color {
+                    button = button.set_text_color(*color, *hover);
+                }
+
+                if button.build(gui) {
+                    if let Some(ref id) = self.id {
+                        clicked.push(id.clone());
+                    }
+                }
+            }
+            NodeKind::Container
+            | NodeKind::VerticalStack { .. }
+            | NodeKind::HorizontalStack { .. } => (),
+        }
+
+        for child in &self.children {
+            child.draw(gui, clicked);
+        }
+    }
+}
+
+/// Layout tree kept between frames. The positions are computed again only when the window is
+/// resized or when a node has been modified.
+#[derive(Debug, Clone)]
+pub struct Layout {
+    root: Node,
+    screen: Option<glam::Vec2>,
+    dirty: bool,
+}
+
+impl Layout {
+    pub fn new(root: Node) -> Self {
+        Self {
+            root,
+            screen: None,
+            dirty: true,
+        }
+    }
+
+    pub fn load_from_path<P: AsRef<Path>>(p: P) -> Result<Self, anyhow::Error> {
+        let content = std::fs::read_to_string(p)?;
+        let root: Node = serde_json::from_str(&content)?;
+        Ok(Self::new(root))
+    }
+
+    /// Get a node to modify it. The layout will be computed again.
+    pub fn node_mut(&mut self, id: &str) -> Option<&mut Node> {
+        self.dirty = true;
+        self.root.find_mut(id)
+    }
+
+    pub fn node(&self, id: &str) -> Option<&Node> {
+        self.root.find(id)
+    }
+
+    /// Change the text of a label or a button.
+    pub fn set_text(&mut self, id: &str, new_text: String) {
+        if let Some(node) = self.root.find_mut(id) {
+            match &mut node.kind {
+                NodeKind::Label { text, .. } | NodeKind::Button { text, .. } => {
+                    if *text != new_text {
+                        *text = new_text;
+                        self.dirty = true;
+                    }
+                }
+                _ => warn!("Node {} has no text", id),
+            }
+        }
+    }
+
+    pub fn set_visible(&mut self, id: &str, visible: bool) {
+        if let Some(node) = self.root.find_mut(id) {
+            if node.visible != visible {
+                node.visible = visible;
+                self.dirty = true;
+            }
+        }
+    }
+
+    pub fn set_enabled(&mut self, id: &str, enabled: bool) {
+        if let Some(node) = self.root.find_mut(id) {
+            node.enabled = enabled;
+        }
+    }
+
+    /// Compute the layout if needed and draw it. Returns the ids of the buttons that have been
+    /// clicked.
+    pub fn build(&mut self, gui: &mut Gui) -> Vec<String> {
+        let screen = gui.window_dim.to_vec2();
+        if self.dirty || self.screen != Some(screen) {
+            let screen_rect = Rect::new(glam::Vec2::zero(), screen);
+            self.root.arrange(gui, screen_rect, screen);
+            self.screen = Some(screen);
+            self.dirty = false;
+        }
+
+        let mut clicked = vec![];
+        self.root.draw(gui, &mut clicked);
+        clicked
+    }
+}
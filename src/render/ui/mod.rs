@@ -1,3 +1,7 @@
+use crate::assets::sprite::SpriteAsset;
+use crate::assets::{AssetManager, Handle};
+use crate::core::window::WindowDim;
+use crate::render::sprite::ShaderUniform;
 use crate::render::ui::text::{Text, TextRenderer};
 use crate::resources::Resources;
 use glyph_brush::GlyphBrush;
@@ -13,6 +17,7 @@ use luminance_gl::GL33;
 
 pub mod gui;
 pub use gui::*;
+pub mod layout;
 pub mod text;
 pub mod widgets;
 pub use widgets::*;
@@ -56,14 +61,35 @@ where
     S: GraphicsContext<Backend = GL33>,
 {
     tesses: Vec<Tess<S::Backend, Vertex, u32>>,
+    /// Drawn after the images (e.g. open dropdown).
+    overlay_tesses: Vec<Tess<S::Backend, Vertex, u32>>,
     shader: Program<S::Backend, VertexSemantics, (), ()>,
     render_state: RenderState,
     text_renderer: TextRenderer<S>,
+
+    /// Sprite id and model matrix in normalized device coordinates.
+    images: Vec<(String, glam::Mat4)>,
+    image_tess: Tess<S::Backend, ()>,
+    image_shader: Program<S::Backend, (), (), ShaderUniform>,
 }
 
 pub enum DrawData {
     Vertices(Vec<Vertex>, Vec<u32>),
     Text(Text, glam::Vec2),
+    /// Sprite id, top-left corner and dimensions.
+    Image(String, glam::Vec2, glam::Vec2),
+}
+
+/// Model matrix that will place the sprite quad (from -1 to 1) at the correct screen position.
+fn image_model(window_dim: WindowDim, pos: glam::Vec2, dimensions: glam::Vec2) -> glam::Mat4 {
+    let w = window_dim.width as f32;
+    let h = window_dim.height as f32;
+    let center = pos + dimensions / 2.0;
+    glam::Mat4::from_scale_rotation_translation(
+        glam::vec3(dimensions.x() / w, dimensions.y() / h, 1.0),
+        glam::Quat::identity(),
+        glam::vec3(center.x() / w * 2.0 - 1.0, 1.0 - center.y() / h * 2.0, 0.0),
+    )
 }
 
 impl<S> UiRenderer<S>
@@ -88,11 +114,22 @@ where
                 },
             );
 
+        let image_tess = surface
+            .new_tess()
+            .set_vertex_nb(4)
+            .set_mode(Mode::TriangleFan)
+            .build()
+            .expect("Tess creation");
+
         Self {
             tesses: vec![],
+            overlay_tesses: vec![],
             shader,
             render_state,
             text_renderer: TextRenderer::new(surface, &mut *gui_context.fonts.borrow_mut()),
+            images: vec![],
+            image_tess,
+            image_shader: crate::render::sprite::new_shader(surface),
         }
     }
 
@@ -105,22 +142,35 @@ where
         fonts: &mut GlyphBrush<'static, text::Instance>,
     ) {
         self.tesses.clear();
+        self.overlay_tesses.clear();
+        self.images.clear();
 
         if let Some(gui) = gui {
             let mut text_data = vec![];
-            for draw_data in gui.draw_data {
-                match draw_data {
-                    DrawData::Vertices(vertices, indices) => {
-                        let tess = surface
-                            .new_tess()
-                            .set_mode(Mode::Triangle)
-                            .set_indices(indices)
-                            .set_vertices(vertices)
-                            .build()
-                            .unwrap();
-                        self.tesses.push(tess);
+            let window_dim = gui.window_dim;
+            let layers = vec![(gui.draw_data, false), (gui.overlay, true)];
+            for (draw_data, is_overlay) in layers {
+                for data in draw_data {
+                    match data {
+                        DrawData::Vertices(vertices, indices) => {
+                            let tess = surface
+                                .new_tess()
+                                .set_mode(Mode::Triangle)
+                                .set_indices(indices)
+                                .set_vertices(vertices)
+                                .build()
+                                .unwrap();
+                            if is_overlay {
+                                self.overlay_tesses.push(tess);
+                            } else {
+                                self.tesses.push(tess);
+                            }
+                        }
+                        DrawData::Text(text, pos) => text_data.push((text, pos)),
+                        DrawData::Image(sprite_id, pos, dimensions) => self
+                            .images
+                            .push((sprite_id, image_model(window_dim, pos, dimensions))),
                     }
-                    DrawData::Text(text, pos) => text_data.push((text, pos)),
                 }
             }
 
@@ -131,15 +181,61 @@ where
         }
     }
 
+    pub fn draw_calls(&self) -> usize {
+        self.tesses.len()
+            + self.images.len()
+            + self.overlay_tesses.len()
+            + self.text_renderer.draw_calls()
+    }
+
     pub fn render(
         &mut self,
         pipeline: &Pipeline<S::Backend>,
         shd_gate: &mut ShadingGate<S::Backend>,
+        textures: &mut AssetManager<S, SpriteAsset<S>>,
     ) -> Result<(), PipelineError> {
-        let tesses = &self.tesses;
         let render_state = &self.render_state;
 
-        for tess in tesses {
+        for tess in &self.tesses {
+            shd_gate.shade(&mut self.shader, |_iface, _uni, mut rdr_gate| {
+                rdr_gate.render(render_state, |mut tess_gate| tess_gate.render(tess))
+            })?;
+        }
+
+        if !self.images.is_empty() {
+            let images = &self.images;
+            let image_tess = &self.image_tess;
+            shd_gate.shade(&mut self.image_shader, |mut iface, uni, mut rdr_gate| {
+                iface.set(&uni.projection, glam::Mat4::identity().to_cols_array_2d());
+                iface.set(&uni.view, glam::Mat4::identity().to_cols_array_2d());
+                iface.set(&uni.should_blink, false);
+                iface.set(&uni.should_tint, false);
+
+                for (sprite_id, model) in images {
+                    if let Some(tex) = textures.get_mut(&Handle(sprite_id.clone())) {
+                        let mut res = Ok(());
+                        tex.execute_mut(|asset| {
+                            if let Some(tex) = asset.texture() {
+                                res = pipeline.bind_texture(tex).and_then(|bound_tex| {
+                                    iface.set(&uni.tex, bound_tex.binding());
+                                    iface.set(&uni.model, model.to_cols_array_2d());
+                                    rdr_gate.render(render_state, |mut tess_gate| {
+                                        tess_gate.render(image_tess)
+                                    })
+                                });
+                            }
+                        });
+                        res?;
+                    } else {
+                        textures.load(sprite_id.clone());
+                    }
+                }
+
+                Ok(())
+            })?;
+        }
+
+        for tess in &self.overlay_tesses {
             shd_gate.shade(&mut self.shader, |_iface, _uni, mut rdr_gate| {
                 rdr_gate.render(render_state, |mut tess_gate| tess_gate.render(tess))
             })?;
@@ -201,6 +201,11 @@ where
         };
     }
 
+    /// All the text is drawn at once.
+    pub fn draw_calls(&self) -> usize {
+        self.tess.is_some() as usize
+    }
+
     pub fn render(
         &mut self,
         pipeline: &Pipeline<S::Backend>,
@@ -1,10 +1,11 @@
 use crate::core::colors::RgbaColor;
 use crate::render::ui::gui::{HorizontalAlign, VerticalAlign};
 use crate::render::ui::text::Text;
-use crate::render::ui::{DrawData, Gui, Panel};
-use glfw::MouseButton;
+use crate::render::ui::{DrawData, Gui, NavInput, Panel};
 
 pub struct Button {
+    /// Used for the focus. If none, the text is used instead
+    id: Option<String>,
     /// Text of the button
     text: String,
     /// dimension of the button. If none, will use enough space for the text
@@ -19,11 +20,14 @@ pub struct Button {
     font_size: Option<f32>,
     text_align: Option<(HorizontalAlign, VerticalAlign)>,
     padding: Option<f32>,
+    /// Disabled buttons cannot be clicked or focused
+    enabled: bool,
 }
 
 impl Button {
     pub fn new(text: String, position: glam::Vec2) -> Self {
         Self {
+            id: None,
             text,
             dimensions: None,
             anchor: position,
@@ -34,9 +38,20 @@ impl Button {
             font_size: None,
             text_align: None,
             padding: None,
+            enabled: true,
         }
     }
 
+    pub fn set_id(mut self, id: &str) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    pub fn set_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
     pub fn dimensions(mut self, dim: glam::Vec2) -> Self {
         self.dimensions = Some(dim);
         self
@@ -74,7 +89,9 @@ impl Button {
     }
 
     fn text_color(&self, ui: &Gui, is_above: bool) -> RgbaColor {
-        if is_above {
+        if !self.enabled {
+            ui.style.disabled_text_color
+        } else if is_above {
             self.hover_text_color
                 .unwrap_or(ui.style.button_hovered_text_color)
         } else {
@@ -104,12 +121,19 @@ impl Button {
             ui.text_bounds(self.text.as_str(), font_size)
         };
 
-        let mouse_pos_rel = ui.mouse_pos - self.anchor;
-        let is_above = mouse_pos_rel.x() >= 0.0
-            && mouse_pos_rel.x() < dimensions.x()
-            && mouse_pos_rel.y() >= 0.0
-            && mouse_pos_rel.y() <= dimensions.y();
-        let color = self.background_color(ui, is_above);
+        let interaction = if self.enabled {
+            let id = self.id.clone().unwrap_or_else(|| self.text.clone());
+            ui.interact(&id, self.anchor, dimensions)
+        } else {
+            Default::default()
+        };
+        // Focused buttons look the same as hovered buttons.
+        let is_above = interaction.hovered || interaction.focused;
+        let color = if interaction.pressed && self.background_color.is_none() {
+            ui.style.button_pressed_bg_color
+        } else {
+            self.background_color(ui, is_above)
+        };
         let text_color = self.text_color(ui, is_above);
         let (vertices, indices) = Panel {
             anchor: self.anchor,
@@ -131,10 +155,10 @@ impl Button {
             text_position,
         ));
 
-        if ui.mouse_clicked.contains(&MouseButton::Button1) {
-            return is_above;
+        if interaction.focused {
+            ui.draw_focus(self.anchor, dimensions);
         }
 
-        false
+        interaction.clicked || (interaction.focused && ui.nav(NavInput::Activate))
     }
 }
@@ -0,0 +1,84 @@
+use crate::render::ui::Gui;
+use crate::render::ui::NavInput;
+
+/// A box that can be toggled by clicking on it, or with Enter/Space/A when it has the focus.
+pub struct Checkbox {
+    id: String,
+    /// Text displayed on the right of the box
+    text: String,
+    /// top-left corner of the checkbox
+    anchor: glam::Vec2,
+    font_size: Option<f32>,
+}
+
+impl Checkbox {
+    pub fn new(id: &str, text: String, position: glam::Vec2) -> Self {
+        Self {
+            id: id.to_string(),
+            text,
+            anchor: position,
+            font_size: None,
+        }
+    }
+
+    pub fn set_font_size(mut self, size: f32) -> Self {
+        self.font_size = Some(size);
+        self
+    }
+
+    /// Returns true if the value has been toggled.
+    pub fn build(self, ui: &mut Gui, checked: &mut bool) -> bool {
+        let font_size = self.font_size.unwrap_or(ui.style.font_size);
+        let box_dim = glam::vec2(font_size, font_size);
+        let text_bounds = ui.text_bounds(self.text.as_str(), font_size);
+        let spacing = font_size / 2.0;
+
+        // The text is also clickable.
+        let dimensions = glam::vec2(
+            box_dim.x() + spacing + text_bounds.x(),
+            box_dim.y().max(text_bounds.y()),
+        );
+        let interaction = ui.interact(&self.id, self.anchor, dimensions);
+
+        let mut toggled = interaction.clicked;
+        if interaction.focused && ui.nav(NavInput::Activate) {
+            toggled = true;
+        }
+        if toggled {
+            *checked = !*checked;
+        }
+
+        let bg_color = if interaction.hovered {
+            ui.style.widget_hover_bg_color
+        } else {
+            ui.style.widget_bg_color
+        };
+        ui.panel(self.anchor, box_dim, bg_color);
+        if *checked {
+            let margin = font_size / 5.0;
+            let fg_color = ui.style.widget_fg_color;
+            ui.panel(
+                self.anchor + glam::vec2(margin, margin),
+                box_dim - glam::vec2(margin, margin) * 2.0,
+                fg_color,
+            );
+        }
+        if interaction.focused {
+            ui.draw_focus(self.anchor, box_dim);
+        }
+
+        let text_color = if interaction.hovered {
+            ui.style.button_hovered_text_color
+        } else {
+            ui.style.button_text_color
+        };
+        ui.sized_label(
+            self.anchor + glam::vec2(box_dim.x() + spacing, 0.0),
+            self.text,
+            font_size,
+            text_color,
+        );
+
+        toggled
+    }
+}
@@ -0,0 +1,136 @@
+use crate::render::ui::Gui;
+use crate::render::ui::NavInput;
+use glfw::MouseButton;
+
+/// Select one option among many. The list of options is displayed above the other widgets
+/// when the dropdown is open.
+pub struct Dropdown {
+    id: String,
+    /// top-left corner of the dropdown
+    anchor: glam::Vec2,
+    width: f32,
+    options: Vec<String>,
+    font_size: Option<f32>,
+}
+
+impl Dropdown {
+    pub fn new(id: &str, position: glam::Vec2, width: f32, options: Vec<String>) -> Self {
+        Self {
+            id: id.to_string(),
+            anchor: position,
+            width,
+            options,
+            font_size: None,
+        }
+    }
+
+    pub fn set_font_size(mut self, size: f32) -> Self {
+        self.font_size = Some(size);
+        self
+    }
+
+    fn is_open(&self, ui: &Gui) -> bool {
+        ui.state.borrow().open.as_deref() == Some(self.id.as_str())
+    }
+
+    fn set_open(&self, ui: &Gui, open: bool) {
+        let mut state = ui.state.borrow_mut();
+        if open {
+            state.open = Some(self.id.clone());
+        } else if state.open.as_deref() == Some(self.id.as_str()) {
+            state.open = None;
+        }
+    }
+
+    /// Returns true if the selection has changed.
+    pub fn build(self, ui: &mut Gui, selected: &mut usize) -> bool {
+        let font_size = self.font_size.unwrap_or(ui.style.font_size);
+        let padding = font_size / 4.0;
+        let item_height = font_size + 2.0 * padding;
+        let dimensions = glam::vec2(self.width, item_height);
+        let was_open = self.is_open(ui);
+        let old_selected = *selected;
+
+        // When the list is open, clicking on an option will select it and clicking anywhere
+        // else will close it. The list is an overlay so the widgets below do not get the click.
+        let clicked = ui.inputs.mouse_clicked.contains(&MouseButton::Button1);
+        if was_open && (clicked || ui.inputs.overlay_clicked) {
+            let list_anchor = self.anchor + glam::vec2(0.0, item_height);
+            let list_dim = glam::vec2(self.width, item_height * self.options.len() as f32);
+            if ui.inputs.overlay_clicked && ui.is_hovered(list_anchor, list_dim) {
+                let idx = ((ui.mouse_pos.y() - list_anchor.y()) / item_height) as usize;
+                *selected = idx.min(self.options.len().saturating_sub(1));
+            }
+            self.set_open(ui, false);
+        }
+
+        let interaction = ui.interact(&self.id, self.anchor, dimensions);
+        if interaction.clicked && !was_open {
+            self.set_open(ui, true);
+        }
+
+        // When open, the arrows change the selection instead of moving the focus.
+        if interaction.focused && self.is_open(ui) && !self.options.is_empty() {
+            if ui.nav(NavInput::Up) && *selected > 0 {
+                *selected -= 1;
+            }
+            if ui.nav(NavInput::Down) && *selected + 1 < self.options.len() {
+                *selected += 1;
+            }
+            if ui.nav(NavInput::Activate) || ui.nav(NavInput::Cancel) {
+                self.set_open(ui, false);
+            }
+        } else if interaction.focused && ui.nav(NavInput::Activate) {
+            self.set_open(ui, true);
+        }
+
+        let bg_color = if interaction.hovered {
+            ui.style.widget_hover_bg_color
+        } else {
+            ui.style.widget_bg_color
+        };
+        ui.panel(self.anchor, dimensions, bg_color);
+        if interaction.focused {
+            ui.draw_focus(self.anchor, dimensions);
+        }
+        let text_color = ui.style.button_text_color;
+        if let Some(option) = self.options.get(*selected) {
+            ui.sized_label(
+                self.anchor + glam::vec2(padding, padding),
+                option.clone(),
+                font_size,
+                text_color,
+            );
+        }
+        // small marker on the right to show this is a dropdown.
+        let fg_color = ui.style.widget_fg_color;
+        ui.panel(
+            self.anchor + glam::vec2(self.width - item_height + padding, padding * 2.0),
+            glam::vec2(item_height - 2.0 * padding, item_height - 4.0 * padding),
+            fg_color,
+        );
+
+        if self.is_open(ui) {
+            ui.capture_nav(&self.id);
+            for (i, option) in self.options.iter().enumerate() {
+                let pos = self.anchor + glam::vec2(0.0, item_height * (i + 1) as f32);
+                let color = if i == *selected {
+                    ui.style.widget_fg_color
+                } else if ui.is_hovered(pos, dimensions) {
+                    ui.style.widget_hover_bg_color
+                } else {
+                    ui.style.widget_bg_color
+                };
+                ui.overlay_panel(pos, dimensions, color);
+                ui.overlay_label(
+                    pos + glam::vec2(padding, padding),
+                    option.clone(),
+                    font_size,
+                    text_color,
+                );
+            }
+        }
+
+        old_selected != *selected
+    }
+}
@@ -0,0 +1,29 @@
+use crate::render::ui::Gui;
+
+/// Display a sprite in the UI. The sprite id is the same as for the Sprite component.
+pub struct Image {
+    sprite_id: String,
+    /// top-left corner of the image
+    anchor: glam::Vec2,
+    dimensions: glam::Vec2,
+}
+
+impl Image {
+    pub fn new(sprite_id: &str, position: glam::Vec2, dimensions: glam::Vec2) -> Self {
+        Self {
+            sprite_id: sprite_id.to_string(),
+            anchor: position,
+            dimensions,
+        }
+    }
+
+    /// Returns true if the image has been clicked.
+    pub fn build(self, ui: &mut Gui) -> bool {
+        ui.image(self.sprite_id.as_str(), self.anchor, self.dimensions);
+        ui.is_hovered(self.anchor, self.dimensions)
+            && ui
+                .inputs
+                .mouse_clicked
+                .contains(&glfw::MouseButton::Button1)
+    }
+}
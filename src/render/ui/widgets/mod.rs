@@ -1,4 +1,16 @@
 pub mod button;
+pub mod checkbox;
+pub mod dropdown;
+pub mod image;
 pub mod panel;
+pub mod scroll_list;
+pub mod slider;
+pub mod text_input;
 pub use button::*;
+pub use checkbox::*;
+pub use dropdown::*;
+pub use image::*;
 pub use panel::*;
+pub use scroll_list::*;
+pub use slider::*;
+pub use text_input::*;
@@ -0,0 +1,142 @@
+use crate::render::ui::Gui;
+use crate::render::ui::NavInput;
+
+/// State of a scroll list that needs to be kept between frames.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ListState {
+    /// Index of the selected item.
+    pub selected: Option<usize>,
+    /// Index of the first visible item.
+    pub scroll: usize,
+}
+
+/// Vertical list of items. Only the items that fit in the list dimensions are displayed, the
+/// rest can be reached with the mouse wheel or the up/down arrows when the list has the focus.
+pub struct ScrollList {
+    id: String,
+    /// top-left corner of the list
+    anchor: glam::Vec2,
+    dimensions: glam::Vec2,
+    item_height: f32,
+    font_size: Option<f32>,
+}
+
+impl ScrollList {
+    pub fn new(id: &str, position: glam::Vec2, dimensions: glam::Vec2, item_height: f32) -> Self {
+        Self {
+            id: id.to_string(),
+            anchor: position,
+            dimensions,
+            item_height,
+            font_size: None,
+        }
+    }
+
+    pub fn set_font_size(mut self, size: f32) -> Self {
+        self.font_size = Some(size);
+        self
+    }
+
+    /// Returns the index of the item that has been clicked (or selected with Enter).
+    pub fn build(self, ui: &mut Gui, items: &[String], state: &mut ListState) -> Option<usize> {
+        let font_size = self.font_size.unwrap_or(ui.style.font_size);
+        let visible = ((self.dimensions.y() / self.item_height).floor() as usize).max(1);
+        let max_scroll = items.len().saturating_sub(visible);
+        let scrollbar_width = 8.0;
+        let item_dim = glam::vec2(self.dimensions.x() - scrollbar_width, self.item_height);
+
+        let interaction = ui.interact(&self.id, self.anchor, self.dimensions);
+        ui.capture_nav(&self.id);
+        let mut activated = None;
+
+        if interaction.hovered && ui.inputs.scroll != 0.0 {
+            let delta = -ui.inputs.scroll.round() as isize;
+            state.scroll = (state.scroll as isize + delta).max(0) as usize;
+        }
+
+        if interaction.clicked {
+            let idx =
+                ((ui.mouse_pos.y() - self.anchor.y()) / self.item_height) as usize + state.scroll;
+            if idx < items.len() && ui.mouse_pos.x() < self.anchor.x() + item_dim.x() {
+                state.selected = Some(idx);
+                activated = Some(idx);
+            }
+        }
+
+        if interaction.focused && !items.is_empty() {
+            if ui.nav(NavInput::Up) {
+                state.selected = Some(state.selected.map(|s| s.saturating_sub(1)).unwrap_or(0));
+            }
+            if ui.nav(NavInput::Down) {
+                state.selected = Some(
+                    state
+                        .selected
+                        .map(|s| (s + 1).min(items.len() - 1))
+                        .unwrap_or(0),
+                );
+            }
+            if ui.nav(NavInput::Activate) {
+                activated = state.selected;
+            }
+
+            // Keep the selection visible.
+            if let Some(selected) = state.selected {
+                if selected < state.scroll {
+                    state.scroll = selected;
+                } else if selected >= state.scroll + visible {
+                    state.scroll = selected + 1 - visible;
+                }
+            }
+        }
+        state.scroll = state.scroll.min(max_scroll);
+
+        let bg_color = ui.style.widget_bg_color;
+        ui.panel(self.anchor, self.dimensions, bg_color);
+
+        let text_color = ui.style.button_text_color;
+        let hovered_text_color = ui.style.button_hovered_text_color;
+        for (i, item) in items.iter().enumerate().skip(state.scroll).take(visible) {
+            let pos = self.anchor + glam::vec2(0.0, (i - state.scroll) as f32 * self.item_height);
+            let hovered = ui.is_hovered(pos, item_dim);
+            if state.selected == Some(i) {
+                let color = ui.style.widget_fg_color;
+                ui.panel(pos, item_dim, color);
+            } else if hovered {
+                let color = ui.style.widget_hover_bg_color;
+                ui.panel(pos, item_dim, color);
+            }
+
+            let padding = ((self.item_height - font_size) / 2.0).max(0.0);
+            ui.sized_label(
+                pos + glam::vec2(padding, padding),
+                item.clone(),
+                font_size,
+                if hovered {
+                    hovered_text_color
+                } else {
+                    text_color
+                },
+            );
+        }
+
+        // Scrollbar
+        if max_scroll > 0 {
+            let ratio = visible as f32 / items.len() as f32;
+            let handle_height = self.dimensions.y() * ratio;
+            let handle_y =
+                (self.dimensions.y() - handle_height) * state.scroll as f32 / max_scroll as f32;
+            let color = ui.style.widget_fg_color;
+            ui.panel(
+                self.anchor + glam::vec2(item_dim.x(), handle_y),
+                glam::vec2(scrollbar_width, handle_height),
+                color,
+            );
+        }
+
+        if interaction.focused {
+            ui.draw_focus(self.anchor, self.dimensions);
+        }
+
+        activated
+    }
+}
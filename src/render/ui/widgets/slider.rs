@@ -0,0 +1,122 @@
+use crate::render::ui::Gui;
+use crate::render::ui::NavInput;
+
+/// Horizontal slider to select a value between min and max. The value can be changed by
+/// dragging the handle with the mouse or with the left/right keys when the slider has the focus.
+pub struct Slider {
+    id: String,
+    /// top-left corner of the slider
+    anchor: glam::Vec2,
+    dimensions: glam::Vec2,
+    min: f32,
+    max: f32,
+    /// Increment when using the keyboard. If none, will use 1/20th of the range
+    step: Option<f32>,
+    /// Display the current value on the right of the slider.
+    show_value: bool,
+    font_size: Option<f32>,
+}
+
+impl Slider {
+    pub fn new(id: &str, position: glam::Vec2, min: f32, max: f32) -> Self {
+        Self {
+            id: id.to_string(),
+            anchor: position,
+            dimensions: glam::vec2(200.0, 20.0),
+            min,
+            max,
+            step: None,
+            show_value: false,
+            font_size: None,
+        }
+    }
+
+    pub fn dimensions(mut self, dim: glam::Vec2) -> Self {
+        self.dimensions = dim;
+        self
+    }
+
+    pub fn set_step(mut self, step: f32) -> Self {
+        self.step = Some(step);
+        self
+    }
+
+    pub fn show_value(mut self) -> Self {
+        self.show_value = true;
+        self
+    }
+
+    pub fn set_font_size(mut self, size: f32) -> Self {
+        self.font_size = Some(size);
+        self
+    }
+
+    /// Returns true if the value has changed.
+    pub fn build(self, ui: &mut Gui, value: &mut f32) -> bool {
+        let interaction = ui.interact(&self.id, self.anchor, self.dimensions);
+        ui.capture_nav(&self.id);
+        let range = self.max - self.min;
+        let old_value = *value;
+
+        if interaction.pressed && range > 0.0 {
+            let t = ((ui.mouse_pos.x() - self.anchor.x()) / self.dimensions.x()).clamp(0.0, 1.0);
+            *value = self.min + t * range;
+            if let Some(step) = self.step {
+                *value = self.min + ((*value - self.min) / step).round() * step;
+            }
+        }
+
+        if interaction.focused {
+            let step = self.step.unwrap_or(range / 20.0);
+            if ui.nav(NavInput::Left) {
+                *value -= step;
+            }
+            if ui.nav(NavInput::Right) {
+                *value += step;
+            }
+        }
+        *value = value.max(self.min).min(self.max);
+
+        // Track
+        let track_color = if interaction.hovered {
+            ui.style.widget_hover_bg_color
+        } else {
+            ui.style.widget_bg_color
+        };
+        ui.panel(self.anchor, self.dimensions, track_color);
+
+        // Filled part
+        let t = if range > 0.0 {
+            (*value - self.min) / range
+        } else {
+            0.0
+        };
+        let fill_color = if interaction.pressed {
+            ui.style.widget_pressed_fg_color
+        } else {
+            ui.style.widget_fg_color
+        };
+        ui.panel(
+            self.anchor,
+            glam::vec2(self.dimensions.x() * t, self.dimensions.y()),
+            fill_color,
+        );
+
+        if interaction.focused {
+            ui.draw_focus(self.anchor, self.dimensions);
+        }
+
+        if self.show_value {
+            let font_size = self.font_size.unwrap_or(ui.style.font_size);
+            let text_color = ui.style.button_text_color;
+            ui.sized_label(
+                self.anchor + glam::vec2(self.dimensions.x() + 10.0, 0.0),
+                format!("{:.2}", *value),
+                font_size,
+                text_color,
+            );
+        }
+
+        (*value - old_value).abs() > f32::EPSILON
+    }
+}
@@ -0,0 +1,107 @@
+use crate::render::ui::Gui;
+use glfw::Key;
+
+/// Single line text field. Click on it to get the focus, then type. Enter will submit the text.
+pub struct TextInput {
+    id: String,
+    /// top-left corner of the field
+    anchor: glam::Vec2,
+    width: f32,
+    /// Text displayed when the field is empty
+    placeholder: Option<String>,
+    max_length: Option<usize>,
+    font_size: Option<f32>,
+}
+
+impl TextInput {
+    pub fn new(id: &str, position: glam::Vec2, width: f32) -> Self {
+        Self {
+            id: id.to_string(),
+            anchor: position,
+            width,
+            placeholder: None,
+            max_length: None,
+            font_size: None,
+        }
+    }
+
+    pub fn set_placeholder(mut self, placeholder: String) -> Self {
+        self.placeholder = Some(placeholder);
+        self
+    }
+
+    pub fn set_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    pub fn set_font_size(mut self, size: f32) -> Self {
+        self.font_size = Some(size);
+        self
+    }
+
+    /// Returns true when the user pressed Enter.
+    pub fn build(self, ui: &mut Gui, value: &mut String) -> bool {
+        let font_size = self.font_size.unwrap_or(ui.style.font_size);
+        let padding = font_size / 4.0;
+        let dimensions = glam::vec2(self.width, font_size + 2.0 * padding);
+        let interaction = ui.interact(&self.id, self.anchor, dimensions);
+
+        let mut submitted = false;
+        if interaction.focused {
+            for c in ui.inputs.chars.iter() {
+                if self
+                    .max_length
+                    .map(|l| value.chars().count() < l)
+                    .unwrap_or(true)
+                {
+                    value.push(*c);
+                }
+            }
+            if ui.key_pressed(Key::Backspace) {
+                value.pop();
+            }
+            if ui.key_pressed(Key::Enter) || ui.key_pressed(Key::KpEnter) {
+                submitted = true;
+            }
+        }
+
+        let bg_color = if interaction.hovered || interaction.focused {
+            ui.style.widget_hover_bg_color
+        } else {
+            ui.style.widget_bg_color
+        };
+        ui.panel(self.anchor, dimensions, bg_color);
+        if interaction.focused {
+            ui.draw_focus(self.anchor, dimensions);
+        }
+
+        let text_pos = self.anchor + glam::vec2(padding, padding);
+        if value.is_empty() && !interaction.focused {
+            if let Some(placeholder) = self.placeholder {
+                let color = ui.style.disabled_text_color;
+                ui.sized_label(text_pos, placeholder, font_size, color);
+            }
+        } else {
+            let color = ui.style.button_text_color;
+            ui.sized_label(text_pos, value.clone(), font_size, color);
+        }
+
+        // Caret at the end of the text.
+        if interaction.focused {
+            let text_width = if value.is_empty() {
+                0.0
+            } else {
+                ui.text_bounds(value.as_str(), font_size).x()
+            };
+            let color = ui.style.widget_fg_color;
+            ui.panel(
+                text_pos + glam::vec2(text_width + 2.0, 0.0),
+                glam::vec2(2.0, font_size),
+                color,
+            );
+        }
+
+        submitted
+    }
+}
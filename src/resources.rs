@@ -93,6 +93,11 @@ impl Resources {
             .insert(TypeId::of::<T>(), RefCell::new(Box::new(v)));
     }
 
+    /// Returns true if a value of this type has been inserted.
+    pub fn contains_type_id(&self, ty: TypeId) -> bool {
+        self.inner.contains_key(&ty)
+    }
+
     /// Borrow data immutably from the map. Can panic if already borrowed mutably.
     pub fn fetch<T: Any + 'static>(&self) -> Option<Fetch<T>> {
         let cloned = {
@@ -0,0 +1,174 @@
+//! Developer console, opened with the grave key (`) while playing. It is drawn above the game and
+//! runs the commands registered in the `Console` resource.
+//!
+//! Up and Down go through the previous commands, Tab completes the command name. Escape or the
+//! grave key close the console.
+
+use crate::core::colors::RgbaColor;
+use crate::core::console::Console;
+use crate::core::scene::{Scene, SceneResult};
+use crate::render::ui::{Gui, GuiContext, TextInput};
+use crate::resources::Resources;
+use glfw::{Key, WindowEvent};
+use hecs::World;
+use std::time::Duration;
+
+const INPUT_ID: &str = "console_input";
+const FONT_SIZE: f32 = 16.0;
+const LINE_HEIGHT: f32 = 20.0;
+const MARGIN: f32 = 10.0;
+/// Number of lines of output to display.
+const VISIBLE_LINES: usize = 15;
+/// Number of lines of output to keep.
+const MAX_LINES: usize = 200;
+
+#[derive(Default)]
+pub struct ConsoleScene {
+    close: bool,
+    input: String,
+    /// Line submitted by the user, executed during the next update.
+    submitted: Option<String>,
+    /// Output of the commands, with their color.
+    output: Vec<(String, RgbaColor)>,
+    /// Commands typed before, most recent last.
+    previous: Vec<String>,
+    /// Position in `previous` while going through the previous commands.
+    previous_idx: Option<usize>,
+}
+
+impl ConsoleScene {
+    fn print(&mut self, text: String, color: RgbaColor) {
+        self.output
+            .extend(text.lines().map(|line| (line.to_string(), color)));
+        if self.output.len() > MAX_LINES {
+            self.output.drain(..self.output.len() - MAX_LINES);
+        }
+    }
+
+    fn recall(&mut self, older: bool) {
+        if self.previous.is_empty() {
+            return;
+        }
+        let idx = match (self.previous_idx, older) {
+            (None, true) => Some(self.previous.len() - 1),
+            (None, false) => None,
+            (Some(idx), true) => Some(idx.saturating_sub(1)),
+            (Some(idx), false) if idx + 1 < self.previous.len() => Some(idx + 1),
+            (Some(_), false) => None,
+        };
+        self.previous_idx = idx;
+        self.input = idx.map(|i| self.previous[i].clone()).unwrap_or_default();
+    }
+
+    fn complete(&mut self, resources: &Resources) {
+        if self.input.contains(' ') {
+            return;
+        }
+        let console = match resources.fetch::<Console>() {
+            Some(console) => console,
+            None => return,
+        };
+        let completions = console.completions(&self.input);
+        match completions.as_slice() {
+            [] => (),
+            [name] => self.input = format!("{} ", name),
+            names => {
+                let text = names.join("  ");
+                drop(console);
+                self.print(text, RgbaColor::new(150, 150, 150, 255));
+            }
+        }
+    }
+}
+
+impl Scene<WindowEvent> for ConsoleScene {
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn update(
+        &mut self,
+        _dt: Duration,
+        world: &mut World,
+        resources: &Resources,
+    ) -> SceneResult<WindowEvent> {
+        if let Some(line) = self.submitted.take() {
+            self.print(format!("> {}", line), RgbaColor::new(255, 255, 255, 255));
+            let result = match resources.fetch::<Console>() {
+                Some(console) => console.execute(&line, world, resources),
+                None => Ok("No console in the resources".to_string()),
+            };
+            match result {
+                Ok(text) => self.print(text, RgbaColor::new(180, 180, 180, 255)),
+                Err(e) => self.print(e.to_string(), RgbaColor::new(255, 80, 80, 255)),
+            }
+            if self.previous.last() != Some(&line) {
+                self.previous.push(line);
+            }
+            self.previous_idx = None;
+        }
+
+        if self.close {
+            SceneResult::Pop
+        } else {
+            SceneResult::Noop
+        }
+    }
+
+    fn prepare_gui(
+        &mut self,
+        _dt: Duration,
+        _world: &mut World,
+        _resources: &Resources,
+        gui_context: &GuiContext,
+    ) -> Option<Gui> {
+        let mut gui = gui_context.new_frame();
+        let width = gui.window_dim.width as f32;
+        let height = MARGIN * 3.0 + LINE_HEIGHT * (VISIBLE_LINES as f32 + 1.0);
+        gui.panel(
+            glam::Vec2::zero(),
+            glam::vec2(width, height),
+            RgbaColor::new(0, 0, 0, 220),
+        );
+
+        let first = self.output.len().saturating_sub(VISIBLE_LINES);
+        for (i, (line, color)) in self.output[first..].iter().enumerate() {
+            gui.sized_label(
+                glam::vec2(MARGIN, MARGIN + i as f32 * LINE_HEIGHT),
+                line.clone(),
+                FONT_SIZE,
+                *color,
+            );
+        }
+
+        gui.focus(INPUT_ID);
+        let input_pos = glam::vec2(MARGIN, height - MARGIN - LINE_HEIGHT - MARGIN);
+        let submitted = TextInput::new(INPUT_ID, input_pos, width - 2.0 * MARGIN)
+            .set_placeholder("Type help to see the commands".to_string())
+            .set_font_size(FONT_SIZE)
+            .build(&mut gui, &mut self.input);
+        // The key that opens and closes the console is not part of the commands.
+        self.input.retain(|c| c != '`');
+
+        if submitted {
+            let line = self.input.trim().to_string();
+            self.input.clear();
+            if !line.is_empty() {
+                self.submitted = Some(line);
+            }
+        }
+
+        Some(gui)
+    }
+
+    fn process_input(&mut self, _world: &mut World, input: WindowEvent, resources: &Resources) {
+        match input {
+            WindowEvent::Key(Key::Escape, _, glfw::Action::Press, _)
+            | WindowEvent::Key(Key::GraveAccent, _, glfw::Action::Press, _) => self.close = true,
+            WindowEvent::Key(Key::Up, _, glfw::Action::Press, _) => self.recall(true),
+            WindowEvent::Key(Key::Down, _, glfw::Action::Press, _) => self.recall(false),
+            WindowEvent::Key(Key::Tab, _, glfw::Action::Press, _) => self.complete(resources),
+            _ => (),
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! Controls scene lists all the actions with their bindings. A binding is removed by clicking on
+//! it and a new one is added by pressing a key, a mouse button or a gamepad input after
+//! clicking on "+". Changes are applied right away and saved when leaving the scene.
+
+use crate::config::{save_config, InputConfig};
+use crate::core::colors::RgbaColor;
+use crate::core::input::ser::{BasicKey, BasicMouseButton};
+use crate::core::input::{Binding, Input, InputMapping};
+use crate::core::scene::{Scene, SceneResult};
+use crate::gameplay::Action;
+use crate::render::ui::{Button, Gui, GuiContext, HorizontalAlign, NavInput, VerticalAlign};
+use crate::resources::Resources;
+use crate::ui::{draw_cursor, menu_button};
+use bitflags::_core::time::Duration;
+use glfw::{Key, WindowEvent};
+use hecs::World;
+use std::path::PathBuf;
+
+const FONT_SIZE: f32 = 24.0;
+const ROW_HEIGHT: f32 = 48.0;
+
+#[derive(Default)]
+pub struct ControlsScene {
+    mapping: Option<InputMapping<Action>>,
+    /// Action waiting for a new binding.
+    capturing: Option<Action>,
+    /// The inputs of this frame were used by the capture so the GUI should not react to them.
+    ignore_gui: bool,
+    /// Feedback for the player (e.g. conflicts).
+    message: Option<String>,
+    /// Mapping has changed and should be applied to the Input resource.
+    dirty: bool,
+    back: bool,
+}
+
+impl ControlsScene {
+    /// Bind the captured input to the action. If it was used by another action, it is removed
+    /// from that action.
+    fn bind(&mut self, binding: Binding) {
+        self.ignore_gui = true;
+        let action = match self.capturing.take() {
+            Some(action) => action,
+            None => return,
+        };
+
+        if let Some(ref mut mapping) = self.mapping {
+            self.message = mapping
+                .bind(binding, action)
+                .map(|previous| format!("{} was unbound from {:?}", binding, previous));
+        }
+        self.dirty = true;
+    }
+
+    fn save(&self) {
+        if let Some(ref mapping) = self.mapping {
+            let base_path = std::env::var("ASSET_PATH").unwrap_or("assets/".to_string());
+            let path = PathBuf::from(base_path).join("config").join("input.json");
+            if let Err(e) = save_config(&path, &InputConfig::from_mapping(mapping)) {
+                error!("Cannot save input config to {:?} = {}", path, e);
+            }
+        }
+    }
+}
+
+fn binding_button(text: String, id: &str, position: glam::Vec2, ui: &mut Gui) -> bool {
+    Button::new(text, position)
+        .set_id(id)
+        .set_font_size(FONT_SIZE)
+        .set_text_align(HorizontalAlign::Left, VerticalAlign::Top)
+        .set_bg_color(
+            RgbaColor::new(60, 60, 60, 200),
+            RgbaColor::new(80, 80, 80, 220),
+        )
+        .build(ui)
+}
+
+impl Scene<WindowEvent> for ControlsScene {
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn on_create(&mut self, _world: &mut World, resources: &mut Resources) {
+        self.mapping = Some(resources.fetch::<Input<Action>>().unwrap().mapping());
+    }
+
+    fn update(
+        &mut self,
+        _dt: Duration,
+        _world: &mut World,
+        resources: &Resources,
+    ) -> SceneResult<WindowEvent> {
+        if self.capturing.is_some() {
+            let pressed = resources
+                .fetch::<Input<Action>>()
+                .unwrap()
+                .gamepad_just_pressed()
+                .first()
+                .copied();
+            if let Some(input) = pressed {
+                self.bind(Binding::Gamepad(input));
+            }
+        }
+
+        // The Input resource is borrowed when processing the window events so the new mapping
+        // is applied here.
+        if self.dirty {
+            if let Some(ref mapping) = self.mapping {
+                let mut input = resources.fetch_mut::<Input<Action>>().unwrap();
+                input.set_mapping(mapping.clone());
+            }
+            self.dirty = false;
+        }
+
+        if self.back {
+            self.save();
+            SceneResult::Pop
+        } else {
+            SceneResult::Noop
+        }
+    }
+
+    fn prepare_gui(
+        &mut self,
+        _dt: Duration,
+        _world: &mut World,
+        _resources: &Resources,
+        gui_context: &GuiContext,
+    ) -> Option<Gui> {
+        let mut gui = gui_context.new_frame();
+        draw_cursor(&mut gui);
+        if self.ignore_gui {
+            gui.consume_inputs();
+            self.ignore_gui = false;
+        }
+
+        let mapping = self.mapping.as_mut()?;
+        let origin = glam::vec2(200.0, 100.0);
+        gui.sized_label(
+            origin,
+            "Controls".to_string(),
+            48.0,
+            RgbaColor::new(255, 255, 255, 255),
+        );
+
+        for (i, action) in Action::all().iter().enumerate() {
+            let row = origin + glam::vec2(0.0, 100.0 + i as f32 * ROW_HEIGHT);
+            gui.sized_label(
+                row,
+                format!("{:?}", action),
+                FONT_SIZE,
+                RgbaColor::new(255, 255, 255, 255),
+            );
+
+            // Click on a binding to remove it.
+            let mut x = 300.0;
+            for binding in mapping.bindings(action) {
+                let text = binding.to_string();
+                let width = gui.text_bounds(text.as_str(), FONT_SIZE).x();
+                let id = format!("{:?}_{}", action, text);
+                if binding_button(text, &id, row + glam::vec2(x, 0.0), &mut gui) {
+                    mapping.unbind(binding);
+                    self.dirty = true;
+                }
+                x += width + 20.0;
+            }
+
+            let id = format!("add_{:?}", action);
+            if binding_button("+".to_string(), &id, row + glam::vec2(x, 0.0), &mut gui) {
+                self.capturing = Some(*action);
+                self.message = None;
+            }
+        }
+
+        let bottom = origin + glam::vec2(0.0, 100.0 + Action::all().len() as f32 * ROW_HEIGHT);
+        if let Some(action) = self.capturing {
+            gui.sized_label(
+                bottom,
+                format!(
+                    "Press a key, mouse button or gamepad input for {:?} (Escape to cancel)",
+                    action
+                ),
+                FONT_SIZE,
+                RgbaColor::new(1, 255, 255, 255),
+            );
+        }
+        if let Some(ref message) = self.message {
+            gui.sized_label(
+                bottom + glam::vec2(0.0, 30.0),
+                message.clone(),
+                FONT_SIZE,
+                RgbaColor::new(255, 200, 0, 255),
+            );
+        }
+
+        if menu_button(
+            "Reset to defaults",
+            bottom + glam::vec2(0.0, 80.0),
+            32.0,
+            &mut gui,
+        ) {
+            *mapping = InputMapping {
+                deadzone: mapping.deadzone,
+                ..InputMapping::default()
+            };
+            self.message = None;
+            self.dirty = true;
+        }
+        if menu_button("Back", bottom + glam::vec2(0.0, 130.0), 32.0, &mut gui)
+            || (self.capturing.is_none() && gui.nav(NavInput::Cancel))
+        {
+            self.back = true;
+        }
+
+        Some(gui)
+    }
+
+    fn process_input(&mut self, _world: &mut World, input: WindowEvent, _resources: &Resources) {
+        if self.capturing.is_none() {
+            return;
+        }
+
+        match input {
+            WindowEvent::Key(Key::Escape, _, glfw::Action::Press, _) => {
+                self.capturing = None;
+                self.ignore_gui = true;
+            }
+            WindowEvent::Key(key, _, glfw::Action::Press, _) => {
+                if BasicKey::from_glfw(key).is_some() {
+                    self.bind(Binding::Key(key));
+                } else {
+                    self.message = Some(format!("{:?} cannot be bound", key));
+                }
+            }
+            WindowEvent::MouseButton(btn, glfw::Action::Press, _) => {
+                if BasicMouseButton::from_glfw(btn).is_some() {
+                    self.bind(Binding::Mouse(btn));
+                } else {
+                    self.message = Some(format!("{:?} cannot be bound", btn));
+                }
+            }
+            _ => (),
+        }
+    }
+}
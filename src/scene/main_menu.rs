@@ -1,17 +1,18 @@
+use crate::assets::preload::SceneAssets;
 use crate::core::audio;
-use crate::core::scene::{Scene, SceneResult};
+use crate::core::scene::{Scene, SceneResult, Transition};
 use crate::core::transform::Transform;
-use crate::prefab::enemies::ENEMY_PREFABS;
 use crate::render::particle::ParticleEmitter;
 use crate::render::ui::gui::GuiContext;
+use crate::render::ui::layout::Layout;
 use crate::render::ui::Gui;
 use crate::resources::Resources;
 use crate::save::is_infinite_unlocked;
-use crate::scene::loading::LoadingScene;
+use crate::scene::controls::ControlsScene;
 use crate::scene::story::StoryScene;
 use crate::scene::wave_selection::WaveSelectionScene;
-use crate::scene::MainScene;
-use crate::ui::{disabled_menu_button, draw_cursor, menu_button};
+use crate::scene::{MainScene, TRANSITION_DURATION};
+use crate::ui::draw_cursor;
 use bitflags::_core::time::Duration;
 use glfw::WindowEvent;
 use hecs::World;
@@ -27,10 +28,15 @@ enum GameMode {
 pub struct MainMenu {
     does_start: bool,
     game_mode: Option<GameMode>,
-    emitter_entity: Option<hecs::Entity>,
+    layout: Option<Layout>,
+    show_controls: bool,
 }
 
 impl Scene<WindowEvent> for MainMenu {
+    fn assets(&self) -> SceneAssets {
+        SceneAssets::new().with_audio(vec!["music/spacelifeNo14.ogg".to_string()])
+    }
+
     fn on_create(&mut self, world: &mut hecs::World, resources: &mut Resources) {
         //generate_terrain(world, resources);
         let base_path = std::env::var("ASSET_PATH").unwrap_or("assets/".to_string());
@@ -39,17 +45,20 @@ impl Scene<WindowEvent> for MainMenu {
         )
         .unwrap();
 
-        self.emitter_entity = Some(world.spawn((emitter, Transform::default())));
+        world.spawn((emitter, Transform::default()));
+
+        let mut layout =
+            Layout::load_from_path(PathBuf::from(&base_path).join("ui").join("main_menu.json"))
+                .unwrap();
+        layout.set_enabled("infinite", is_infinite_unlocked(resources));
+        self.layout = Some(layout);
 
         audio::play_background_music(resources, "music/spacelifeNo14.ogg");
     }
 
-    fn on_destroy(&mut self, world: &mut hecs::World) {
-        if let Some(e) = self.emitter_entity {
-            if let Err(e) = world.despawn(e) {
-                error!("Error despawning menu particle = {:?}", e);
-            }
-        }
+    fn on_enter(&mut self, _world: &mut World, _resources: &mut Resources) {
+        // The game could not be started.
+        self.game_mode = None;
     }
 
     fn update(
@@ -58,27 +67,21 @@ impl Scene<WindowEvent> for MainMenu {
         _world: &mut World,
         resources: &Resources,
     ) -> SceneResult<WindowEvent> {
-        let mut prefabs: Vec<String> = ENEMY_PREFABS.iter().map(|e| e.to_string()).collect();
-
-        prefabs.push("player".to_string());
-        if let Some(GameMode::Normal) = self.game_mode {
-            SceneResult::ReplaceScene(Box::new(LoadingScene::new(
-                prefabs,
-                vec![],
-                StoryScene::new(
-                    vec![
-                        "Humans discovered an alien artefact deep inside the moon.".to_string(),
-                        "It should be ours...".to_string(),
-                    ],
-                    MainScene::new(false, 0),
-                ),
+        if self.show_controls {
+            self.show_controls = false;
+            SceneResult::Push(Box::new(ControlsScene::default()))
+        } else if let Some(GameMode::Normal) = self.game_mode {
+            SceneResult::ReplaceScene(Box::new(StoryScene::new(
+                vec![
+                    "Humans discovered an alien artefact deep inside the moon.".to_string(),
+                    "It should be ours...".to_string(),
+                ],
+                MainScene::new(false, 0),
             )))
+            .with_transition(Transition::fade(TRANSITION_DURATION))
         } else if let Some(GameMode::Infinite) = self.game_mode {
-            SceneResult::ReplaceScene(Box::new(LoadingScene::new(
-                prefabs,
-                vec![],
-                WaveSelectionScene::new(resources),
-            )))
+            SceneResult::ReplaceScene(Box::new(WaveSelectionScene::new(resources)))
+                .with_transition(Transition::wipe(TRANSITION_DURATION))
         } else {
             SceneResult::Noop
         }
@@ -91,48 +94,19 @@ impl Scene<WindowEvent> for MainMenu {
         _resources: &Resources,
         gui_context: &GuiContext,
     ) -> Option<Gui> {
-        let w = gui_context.window_dim.width as f32;
-        let h = gui_context.window_dim.height as f32;
-        // panel should take up 20% of the window width and 80% of the window height
-        let panel_width = w * 0.2;
-        let panel_height = h * 0.6;
-
-        let anchor = glam::vec2(w / 2.0 - panel_width / 2.0, h / 2.0 - panel_height / 2.0);
-
         let mut gui = gui_context.new_frame();
         draw_cursor(&mut gui);
 
-        // START BUTTON
-        if menu_button("Start", anchor, 48.0, &mut gui) {
-            self.game_mode = Some(GameMode::Normal);
-        }
-
-        if is_infinite_unlocked(_resources) {
-            if menu_button(
-                "Infinite Mode",
-                anchor + 80.0 * glam::Vec2::unit_y(),
-                48.0,
-                &mut gui,
-            ) {
-                self.game_mode = Some(GameMode::Infinite);
+        if let Some(layout) = self.layout.as_mut() {
+            for clicked in layout.build(&mut gui) {
+                match clicked.as_str() {
+                    "start" => self.game_mode = Some(GameMode::Normal),
+                    "infinite" => self.game_mode = Some(GameMode::Infinite),
+                    "controls" => self.show_controls = true,
+                    "quit" => std::process::exit(0),
+                    _ => (),
+                }
             }
-        } else {
-            disabled_menu_button(
-                "Infinite Mode",
-                anchor + 80.0 * glam::Vec2::unit_y(),
-                48.0,
-                &mut gui,
-            );
-        }
-
-        // EXIT BUTTON
-        if menu_button(
-            "Quit to Desktop",
-            anchor + 160.0 * glam::Vec2::unit_y(),
-            48.0,
-            &mut gui,
-        ) {
-            std::process::exit(0);
         }
 
         Some(gui)
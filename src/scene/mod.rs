@@ -1,49 +1,68 @@
 use crate::assets::prefab::PrefabManager;
+use crate::assets::preload::SceneAssets;
 use crate::assets::Handle;
 use crate::core::animation::AnimationSystem;
 use crate::core::audio;
+use crate::core::audio::adaptive::AdaptiveMusic;
 use crate::core::colors::RgbaColor;
+use crate::core::console::Console;
+use crate::core::events::{register_reader, EventReader, Events};
+use crate::core::lifetime::StageBounds;
+use crate::core::profiler;
 use crate::core::random::RandomGenerator;
-use crate::core::scene::{Scene, SceneResult};
+use crate::core::scene::{Scene, SceneResult, Transition};
+use crate::core::system::{Scheduler, SchedulerBuilder};
 use crate::core::timer::Timer;
-use crate::core::transform::{HasChildren, HasParent, LocalTransform, Transform};
-use crate::event::GameEvent;
-use crate::gameplay::bullet::{Bullet, Missile};
-use crate::gameplay::camera::update_camera;
+use crate::core::window::WindowDim;
+use crate::event::{GameEvent, StageCommand};
+use crate::gameplay::bullet::BulletSystem;
+use crate::gameplay::camera::{reset_camera, CameraSystem};
+use crate::gameplay::collision::CollisionSystem;
+use crate::gameplay::enemy::EnemySystem;
 use crate::gameplay::explosion::ExplosionSystem;
 use crate::gameplay::health::{Health, HealthSystem, Shield};
 use crate::gameplay::inventory::Inventory;
 use crate::gameplay::level::{Stage, StageDescription};
-use crate::gameplay::physics::{PhysicConfig, PhysicSystem};
-use crate::gameplay::pickup::{process_pickups, spawn_pickup, Pickup};
-use crate::gameplay::player::get_player;
-use crate::gameplay::trail::update_trails;
-use crate::gameplay::{bullet, collision, enemy, player};
-use crate::render::mesh::{Material, MeshRender};
-use crate::render::particle::ParticleEmitter;
+use crate::gameplay::music;
+use crate::gameplay::physics::PhysicSystem;
+use crate::gameplay::pickup::{spawn_pickup, PickupSystem};
+use crate::gameplay::player::{get_player, PlayerSystem};
+use crate::gameplay::trail::TrailSystem;
+use crate::prefab::enemies::ENEMY_PREFABS;
 use crate::render::ui::gui::GuiContext;
-use crate::render::ui::{Button, Gui, HorizontalAlign, VerticalAlign};
+use crate::render::ui::Gui;
 use crate::resources::Resources;
 use crate::save::{get_wave_record, save_new_wave_record, save_unlocked};
+use crate::scene::console::ConsoleScene;
 use crate::scene::main_menu::MainMenu;
 use crate::scene::pause::PauseScene;
 use crate::scene::story::StoryScene;
-use crate::ui::draw_cursor;
+use crate::ui::{draw_cursor, menu_button};
 use glfw::{Key, WindowEvent};
 use hecs::World;
 use log::info;
 use luminance_glfw::GlfwSurface;
 use rand::Rng;
+use std::error::Error;
 use std::path::PathBuf;
 use std::time::Duration;
 
-pub mod loading;
+pub mod console;
+pub mod controls;
 pub mod main_menu;
 pub mod particle_scene;
 pub mod pause;
 pub mod story;
 pub mod wave_selection;
 
+/// Duration of the transitions between the menus and the game.
+pub const TRANSITION_DURATION: Duration = Duration::from_millis(800);
+
+/// Adaptive music played during a stage, relative to the asset directory.
+const BATTLE_MUSIC: &str = "music/battle.json";
+/// Played instead of the adaptive music when it cannot be loaded.
+const FALLBACK_MUSIC: &str = "music/Finding-Flora.wav";
+
 enum MainSceneState {
     Running,
     GameOver,
@@ -53,14 +72,14 @@ enum MainSceneState {
 
 pub struct MainScene {
     stage: Option<Stage>,
-    health_system: Option<HealthSystem>,
-    physic_system: PhysicSystem,
-    animation_system: AnimationSystem,
-    explosion_system: Option<ExplosionSystem>,
+    scheduler: Option<Scheduler>,
 
     state: MainSceneState,
     return_to_menu: bool,
     restart: bool,
+    /// The ending could not be loaded after the game was won. The player chooses when to try
+    /// again.
+    ending_cancelled: bool,
     player: Option<hecs::Entity>,
 
     info_text: Option<String>,
@@ -68,6 +87,10 @@ pub struct MainScene {
 
     is_infinite: bool,
     starting_wave_nb: usize,
+
+    /// Developer console is opened with the grave key.
+    open_console: bool,
+    stage_commands: Option<EventReader<StageCommand>>,
 }
 
 impl Default for MainScene {
@@ -86,30 +109,102 @@ impl MainScene {
             restart: false,
             state: MainSceneState::Running,
             return_to_menu: false,
-            animation_system: AnimationSystem,
+            ending_cancelled: false,
             stage: None,
-            health_system: None,
-            explosion_system: None,
-            physic_system: PhysicSystem::new(PhysicConfig { damping: 0.99 }),
+            scheduler: None,
             info_text_timer: Timer::of_seconds(3.0),
+            open_console: false,
+            stage_commands: None,
+        }
+    }
+
+    /// Replace the current stage by the one in assets/stages.
+    fn load_stage(
+        &mut self,
+        world: &mut World,
+        resources: &Resources,
+        stage_name: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let base_path = std::env::var("ASSET_PATH").unwrap_or("assets/".to_string());
+        let stage_desc: StageDescription = {
+            let p = PathBuf::from(&base_path).join("stages").join(stage_name);
+            let content = std::fs::read_to_string(p)?;
+            serde_json::from_str(&content)?
+        };
+        if let Some(stage) = self.stage.as_mut() {
+            stage.clean(world);
+        }
+        let stage = Stage::new(world, resources, stage_desc, 0);
+        if let Some(system) = self
+            .scheduler
+            .as_mut()
+            .and_then(|s| s.system_mut::<CameraSystem>())
+        {
+            system.set_bounds(stage.camera_bounds);
+        }
+        if let Some(mut bounds) = resources.fetch_mut::<StageBounds>() {
+            *bounds = stage.bounds(&resources.fetch::<WindowDim>().unwrap());
+        }
+        self.stage = Some(stage);
+        Ok(())
+    }
+
+    /// Requests sent from the developer console.
+    fn process_stage_commands(&mut self, world: &mut World, resources: &Resources) {
+        let commands: Vec<StageCommand> = match self.stage_commands.as_mut() {
+            Some(reader) => {
+                let chan = resources.fetch::<Events<StageCommand>>().unwrap();
+                chan.read(reader).cloned().collect()
+            }
+            None => return,
+        };
+
+        for command in commands {
+            match command {
+                StageCommand::NextWave => {
+                    if let Some(stage) = self.stage.as_mut() {
+                        if !stage.skip_wave(world) {
+                            warn!("No wave left to skip in this stage");
+                        }
+                    }
+                }
+                StageCommand::Load(stage_name) => {
+                    if let Err(e) = self.load_stage(world, resources, &stage_name) {
+                        error!("Cannot load stage {} = {}", stage_name, e);
+                    }
+                }
+            }
         }
     }
 }
 
 impl Scene<WindowEvent> for MainScene {
+    fn assets(&self) -> SceneAssets {
+        let mut prefabs: Vec<String> = ENEMY_PREFABS.iter().map(|e| e.to_string()).collect();
+        prefabs.push("player".to_string());
+        let base_path = std::env::var("ASSET_PATH").unwrap_or("assets/".to_string());
+        let music =
+            match AdaptiveMusic::load_from_path(PathBuf::from(&base_path).join(BATTLE_MUSIC)) {
+                Ok(music) => music.layers.into_iter().map(|layer| layer.name).collect(),
+                Err(_) => vec![FALLBACK_MUSIC.to_string()],
+            };
+        SceneAssets::new()
+            .with_prefabs(prefabs)
+            .with_banks(vec!["gameplay".to_string()])
+            .with_audio(music)
+            .with_effects(vec![
+                "explosion".to_string(),
+                "pickup".to_string(),
+                "shield_hit".to_string(),
+                "crit".to_string(),
+            ])
+    }
+
     fn on_create(&mut self, world: &mut hecs::World, resources: &mut Resources) {
         info!("Create MainScene");
-        self.health_system = Some(HealthSystem::new(resources));
-        self.explosion_system = Some(ExplosionSystem::new(resources));
 
         //generate_terrain(world, resources);
         let base_path = std::env::var("ASSET_PATH").unwrap_or("assets/".to_string());
-        let mut emitter: ParticleEmitter = serde_json::from_str(
-            &std::fs::read_to_string(PathBuf::from(&base_path).join("particle/trail.json"))
-                .unwrap(),
-        )
-        .unwrap();
-        emitter.init_pool();
 
         let stage_desc: StageDescription = if self.is_infinite {
             StageDescription::infinite()
@@ -119,105 +214,76 @@ impl Scene<WindowEvent> for MainScene {
             serde_json::from_str(&content).unwrap()
         };
         let stage = Stage::new(world, resources, stage_desc, self.starting_wave_nb);
+        let bounds = stage.bounds(&resources.fetch::<WindowDim>().unwrap());
+        resources.insert(bounds);
+        let scheduler = SchedulerBuilder::new()
+            .with_stage("logic")
+            .with_stage("physics")
+            .with_stage("resolve")
+            .with_system("logic", PlayerSystem)
+            .with_system("logic", CameraSystem::new(resources, stage.camera_bounds))
+            .with_system("logic", EnemySystem)
+            .with_system("logic", AnimationSystem)
+            .with_system("logic", TrailSystem)
+            .with_system("physics", PhysicSystem)
+            .with_system("resolve", BulletSystem)
+            .with_system("resolve", PickupSystem)
+            .with_system("resolve", CollisionSystem)
+            .with_system("resolve", HealthSystem::new(resources))
+            .with_system("resolve", ExplosionSystem::new(resources))
+            .with_ordering("player", "camera")
+            .with_ordering("collisions", "health")
+            .with_ordering("health", "explosions")
+            .with_parallel(true)
+            .build(resources)
+            .expect("Main scene systems should be valid");
+        self.scheduler = Some(scheduler);
         self.stage = Some(stage);
+        self.stage_commands = Some(register_reader::<StageCommand>(resources, "main_scene"));
 
-        self.player = Some({
+        self.player = {
             let prefab_manager = resources.fetch_mut::<PrefabManager<GlfwSurface>>().unwrap();
-            let asset = prefab_manager
+            prefab_manager
                 .get(&Handle("player".to_string()))
-                .expect("Player asset should have been loaded");
-            assert!(asset.is_loaded());
-            asset
-                .execute(|prefab| prefab.spawn(world))
-                .expect("Should be able to spawn player")
-        });
-
-        let player_scale = { world.get::<Transform>(self.player.unwrap()).unwrap().scale * 2.0 };
-
-        // add the shield to the player...
-        let shield_entity = world.spawn((
-            Transform {
-                translation: Default::default(),
-                scale: player_scale,
-                rotation: 0.0,
-                dirty: true,
-            },
-            LocalTransform {
-                translation: Default::default(),
-                scale: Default::default(),
-                rotation: 0.0,
-                dirty: true,
-            },
-            HasParent {
-                entity: self.player.unwrap(),
-            },
-            MeshRender {
-                enabled: true,
-                material: Material::Shader {
-                    vertex_shader_id: "simple-vs.glsl".to_string(),
-                    fragment_shader_id: "simple-fs.glsl".to_string(),
-                },
-            },
-        ));
-
-        world
-            .insert_one(
-                self.player.unwrap(),
-                HasChildren {
-                    children: vec![shield_entity],
-                },
-            )
-            .expect("Should be able to add shield");
-
-        // "music/Finding-Flora.wav"
-        audio::play_background_music(resources, "music/Finding-Flora.wav");
-    }
+                .and_then(|asset| asset.execute(|prefab| prefab.spawn(world)))
+        };
+        if self.player.is_none() {
+            // Nothing to play without the player.
+            error!("Player prefab is not loaded, go back to the menu");
+            self.return_to_menu = true;
+        }
 
-    fn on_destroy(&mut self, world: &mut hecs::World) {
-        // remove the player.
-        if let Some(p) = self.player {
-            let mut to_despawn = vec![];
-            if let Ok(children) = world.get::<HasChildren>(p) {
-                for c in &children.children {
-                    to_despawn.push(*c);
-                }
+        // Layered music that follows the action. Fall back to the simple music if it cannot be
+        // loaded.
+        match AdaptiveMusic::load_from_path(PathBuf::from(&base_path).join(BATTLE_MUSIC)) {
+            Ok(music) => audio::play_adaptive_music(resources, music),
+            Err(e) => {
+                error!("Cannot load adaptive music = {:?}", e);
+                audio::play_background_music(resources, FALLBACK_MUSIC);
             }
+        }
+    }
 
-            to_despawn.iter().for_each(|&c| {
-                if let Err(e) = world.despawn(c) {
-                    error!("Cannot despawn player's children = {:?}", e);
-                }
-            });
+    fn on_destroy(&mut self, world: &mut hecs::World) {
+        // The player, the stage and the projectiles are despawned with the scene.
+        reset_camera(world);
+    }
 
-            if let Err(e) = world.despawn(p) {
-                error!("Error while despawn player = {:?}", e);
-            }
-        }
+    fn on_enter(&mut self, _world: &mut World, _resources: &mut Resources) {
+        self.return_to_menu = false;
+        self.restart = false;
+    }
 
-        // Clean the stage.
-        if let Some(stage) = self.stage.as_mut() {
-            stage.clean(world);
+    fn on_transition_cancelled(
+        &mut self,
+        _result: SceneResult<WindowEvent>,
+        world: &mut World,
+        resources: &mut Resources,
+    ) {
+        if let MainSceneState::GameWon = self.state {
+            self.ending_cancelled = true;
         }
-
-        // remove all the bullets :)
-        let bullets: Vec<_> = world.query::<&Bullet>().iter().map(|(e, _)| e).collect();
-        let missiles: Vec<_> = world.query::<&Missile>().iter().map(|(e, _)| e).collect();
-        let pickups: Vec<_> = world.query::<&Pickup>().iter().map(|(e, _)| e).collect();
-        bullets.iter().for_each(|&e| {
-            if let Err(e) = world.despawn(e) {
-                error!("Error while despawn bullet = {:?}", e);
-            }
-        });
-        missiles.iter().for_each(|&e| {
-            if let Err(e) = world.despawn(e) {
-                error!("Error while despawn missiles = {:?}", e);
-            }
-        });
-        pickups.iter().for_each(|&e| {
-            if let Err(e) = world.despawn(e) {
-                error!("Error while despawn pickups = {:?}", e);
-            }
-        });
+        self.on_enter(world, resources);
     }
 
     fn update(
@@ -232,35 +298,26 @@ impl Scene<WindowEvent> for MainScene {
             self.info_text = None;
         }
 
+        self.process_stage_commands(world, resources);
+
         if let MainSceneState::Running = self.state {
-            player::update_player(world, dt, resources);
-            update_camera(world, resources);
-            enemy::update_enemies(world, &resources, dt);
-            self.animation_system.animate(world, resources);
-            update_trails(world);
-            self.physic_system.update(world, dt, resources);
-
-            bullet::process_bullets(world, resources);
-            bullet::process_missiles(world, resources);
-            process_pickups(world, resources);
-
-            let collisions = collision::find_collisions(world, resources);
-            collision::process_collisions(world, collisions, &resources);
-            if let Some(hs) = self.health_system.as_mut() {
-                hs.update(world, &resources, dt);
-            }
-            if let Some(system) = self.explosion_system.as_mut() {
-                system.update(world, &resources);
+            if let Some(scheduler) = self.scheduler.as_mut() {
+                scheduler.run(world, resources, dt);
             }
             if let Some(ref mut stage) = self.stage {
+                let _scope = profiler::scope(resources, "stage");
                 stage.update(world, resources, dt);
+                audio::set_music_intensity(resources, music::music_intensity(world, stage));
             }
         }
 
         if let MainSceneState::Paused = self.state {
             self.state = MainSceneState::Running;
             SceneResult::Push(Box::new(PauseScene::default()))
-        } else if let MainSceneState::GameWon = self.state {
+        } else if self.open_console {
+            self.open_console = false;
+            SceneResult::Push(Box::new(ConsoleScene::default()))
+        } else if matches!(self.state, MainSceneState::GameWon) && !self.ending_cancelled {
             SceneResult::ReplaceScene(Box::new(StoryScene::new(
                 vec![
                     "You reach the moon, with all its riches".to_string(),
@@ -268,13 +325,16 @@ impl Scene<WindowEvent> for MainScene {
                 ],
                 MainMenu::default(),
             )))
+            .with_transition(Transition::fade(TRANSITION_DURATION))
         } else if self.return_to_menu {
             SceneResult::ReplaceScene(Box::new(MainMenu::default()))
+                .with_transition(Transition::fade(TRANSITION_DURATION))
         } else if self.restart {
             SceneResult::ReplaceScene(Box::new(MainScene::new(
                 self.is_infinite,
                 self.starting_wave_nb,
             )))
+            .with_transition(Transition::dissolve(TRANSITION_DURATION))
         } else {
             SceneResult::Noop
         }
@@ -394,18 +454,50 @@ impl Scene<WindowEvent> for MainScene {
                     }
                 }
 
-                if game_button("Restart", center + glam::Vec2::unit_y() * 50.0, &mut gui) {
+                if menu_button(
+                    "Restart",
+                    center + glam::Vec2::unit_y() * 50.0,
+                    32.0,
+                    &mut gui,
+                ) {
                     self.restart = true;
                 }
-                if game_button(
+                if menu_button(
                     "Return to menu",
                     center + glam::Vec2::unit_y() * 100.0,
+                    32.0,
                     &mut gui,
                 ) {
                     self.return_to_menu = true;
                 }
             }
-            MainSceneState::GameWon => {}
+            MainSceneState::GameWon => {
+                if self.ending_cancelled {
+                    let center =
+                        gui_context.window_dim.to_vec2() / 2.0 - glam::Vec2::unit_y() * 100.0;
+                    gui.colored_label(
+                        center,
+                        "You win!".to_string(),
+                        RgbaColor::new(255, 255, 255, 255),
+                    );
+                    if menu_button(
+                        "Continue",
+                        center + glam::Vec2::unit_y() * 50.0,
+                        32.0,
+                        &mut gui,
+                    ) {
+                        self.ending_cancelled = false;
+                    }
+                    if menu_button(
+                        "Return to menu",
+                        center + glam::Vec2::unit_y() * 100.0,
+                        32.0,
+                        &mut gui,
+                    ) {
+                        self.return_to_menu = true;
+                    }
+                }
+            }
         }
         Some(gui)
     }
@@ -466,17 +558,9 @@ impl Scene<WindowEvent> for MainScene {
                 self.info_text = Some(info);
             }
             GameEvent::NextStage(stage_name) => {
-                let base_path = std::env::var("ASSET_PATH").unwrap_or("assets/".to_string());
-                let stage_desc: StageDescription = {
-                    let p = PathBuf::from(&base_path).join("stages").join(stage_name);
-                    let content = std::fs::read_to_string(p).unwrap();
-                    serde_json::from_str(&content).unwrap()
-                };
-                if let Some(stage) = self.stage.as_mut() {
-                    stage.clean(world);
+                if let Err(e) = self.load_stage(world, resources, &stage_name) {
+                    panic!("Cannot load stage {} = {}", stage_name, e);
                 }
-                let stage = Stage::new(world, resources, stage_desc, 0);
-                self.stage = Some(stage);
 
                 drain_scratch = true;
             }
@@ -491,22 +575,15 @@ impl Scene<WindowEvent> for MainScene {
         }
     }
 
-    fn process_input(&mut self, _world: &mut World, input: WindowEvent, _resources: &Resources) {
-        if let WindowEvent::Key(Key::Escape, _0, glfw::Action::Press, _2) = input {
-            self.state = MainSceneState::Paused;
+    fn process_input(&mut self, _world: &mut World, input: WindowEvent, resources: &Resources) {
+        match input {
+            WindowEvent::Key(Key::Escape, _, glfw::Action::Press, _) => {
+                self.state = MainSceneState::Paused;
+            }
+            WindowEvent::Key(Key::GraveAccent, _, glfw::Action::Press, _) => {
+                self.open_console = resources.fetch::<Console>().is_some();
+            }
+            _ => (),
         }
     }
 }
-
-fn game_button(text: &str, position: glam::Vec2, ui: &mut Gui) -> bool {
-    Button::new(text.to_string(), position)
-        .set_bg_color(RgbaColor::new(0, 0, 0, 0), RgbaColor::new(0, 0, 0, 0))
-        .set_text_color(
-            RgbaColor::from_hex("FFFFFFFF").unwrap(),
-            RgbaColor::from_hex("01FFFFFF").unwrap(),
-        )
-        .set_font_size(32.0)
-        .set_text_align(HorizontalAlign::Left, VerticalAlign::Top)
-        .set_padding(0.0)
-        .build(ui)
-}
@@ -4,12 +4,12 @@ use crate::core::camera::{screen_to_world, ProjectionMatrix};
 use crate::core::input::Input;
 use crate::core::scene::{Scene, SceneResult};
 use crate::core::transform::Transform;
+use crate::core::window::WindowDim;
 use crate::gameplay::Action;
 use crate::render::particle::ParticleEmitter;
 use crate::render::ui::gui::GuiContext;
 use crate::render::ui::Gui;
 use crate::resources::Resources;
-use crate::{HEIGHT, WIDTH};
 use bitflags::_core::time::Duration;
 use glam::Vec2;
 use glfw::WindowEvent;
@@ -42,9 +42,9 @@ impl ParticleScene {
 }
 
 impl Scene<WindowEvent> for ParticleScene {
-    fn on_create(&mut self, world: &mut World, _resources: &mut Resources) {
+    fn on_create(&mut self, world: &mut World, resources: &mut Resources) {
         let t = Transform {
-            translation: Vec2::new(WIDTH as f32 / 2.0, HEIGHT as f32 / 2.0),
+            translation: resources.fetch::<WindowDim>().unwrap().to_vec2() / 2.0,
             rotation: 0.0,
             scale: Vec2::one(),
             dirty: false,
@@ -68,7 +68,7 @@ impl Scene<WindowEvent> for ParticleScene {
 
             self.particle_emitter = emitter;
             let t = Transform {
-                translation: Vec2::new(WIDTH as f32 / 2.0, HEIGHT as f32 / 2.0),
+                translation: resources.fetch::<WindowDim>().unwrap().to_vec2() / 2.0,
                 rotation: 0.0,
                 scale: Vec2::one(),
                 dirty: false,
@@ -82,7 +82,7 @@ impl Scene<WindowEvent> for ParticleScene {
             let input = resources.fetch::<Input<Action>>().unwrap();
             let proj = resources.fetch::<ProjectionMatrix>().unwrap();
             if input.is_just_pressed(Action::Shoot) || self.should_follow {
-                let new_pos = screen_to_world(input.mouse_position(), proj.0, world);
+                let new_pos = screen_to_world(input.mouse_position(), proj.matrix(), world);
                 let mut transform = world.get_mut::<Transform>(self.entity.unwrap()).unwrap();
                 transform.translation = new_pos;
             }
@@ -1,33 +1,58 @@
 //! Pause scene is when the player presses the escape button while playing. It will just bring
 //! Some buttons to abandon or resume the game.
 
-use crate::core::colors::RgbaColor;
-use crate::core::scene::{Scene, SceneResult};
-use crate::render::ui::{Gui, GuiContext};
+use crate::core::scene::{Scene, SceneResult, Transition};
+use crate::render::ui::layout::Layout;
+use crate::render::ui::{Gui, GuiContext, NavInput};
 use crate::resources::Resources;
+use crate::scene::controls::ControlsScene;
 use crate::scene::main_menu::MainMenu;
-use crate::ui::{draw_cursor, menu_button};
+use crate::scene::TRANSITION_DURATION;
+use crate::ui::draw_cursor;
 use bitflags::_core::time::Duration;
-use glfw::{Key, WindowEvent};
+use glfw::WindowEvent;
 use hecs::World;
+use std::path::PathBuf;
 
 #[derive(Default)]
 pub struct PauseScene {
     resume: bool,
     go_to_menu: bool,
+    show_controls: bool,
+    layout: Option<Layout>,
 }
 
 impl Scene<WindowEvent> for PauseScene {
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn on_create(&mut self, _world: &mut World, _resources: &mut Resources) {
+        let base_path = std::env::var("ASSET_PATH").unwrap_or("assets/".to_string());
+        self.layout = Some(
+            Layout::load_from_path(PathBuf::from(&base_path).join("ui").join("pause.json"))
+                .unwrap(),
+        );
+    }
+
+    fn on_enter(&mut self, _world: &mut World, _resources: &mut Resources) {
+        self.go_to_menu = false;
+    }
+
     fn update(
         &mut self,
         _dt: Duration,
         _world: &mut World,
         _resources: &Resources,
     ) -> SceneResult<WindowEvent> {
-        if self.resume {
+        if self.show_controls {
+            self.show_controls = false;
+            SceneResult::Push(Box::new(ControlsScene::default()))
+        } else if self.resume {
             SceneResult::Pop
         } else if self.go_to_menu {
             SceneResult::ReplaceAll(Box::new(MainMenu::default()))
+                .with_transition(Transition::fade(TRANSITION_DURATION))
         } else {
             SceneResult::Noop
         }
@@ -43,32 +68,21 @@ impl Scene<WindowEvent> for PauseScene {
         let mut gui = gui_context.new_frame();
         draw_cursor(&mut gui);
 
-        let window_dim = gui.window_dim.to_vec2();
-        let anchor = window_dim / 2.0 - 40.0 * glam::Vec2::unit_x();
-        gui.panel(
-            glam::vec2(0.0, 0.0),
-            window_dim,
-            RgbaColor::new(133, 133, 133, 133),
-        );
-        if menu_button("Resume", anchor, 32.0, &mut gui) {
+        if gui.nav(NavInput::Cancel) {
             self.resume = true;
         }
 
-        if menu_button(
-            "Quit to Menu",
-            anchor + 64.0 * glam::Vec2::unit_y(),
-            32.0,
-            &mut gui,
-        ) {
-            self.go_to_menu = true;
+        if let Some(layout) = self.layout.as_mut() {
+            for clicked in layout.build(&mut gui) {
+                match clicked.as_str() {
+                    "resume" => self.resume = true,
+                    "menu" => self.go_to_menu = true,
+                    "controls" => self.show_controls = true,
+                    _ => (),
+                }
+            }
         }
 
         Some(gui)
     }
-
-    fn process_input(&mut self, _world: &mut World, input: WindowEvent, _resources: &Resources) {
-        if let WindowEvent::Key(Key::Escape, _0, glfw::Action::Press, _2) = input {
-            self.resume = true;
-        }
-    }
 }
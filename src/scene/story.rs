@@ -1,37 +1,46 @@
-use crate::core::scene::{Scene, SceneResult};
+use crate::assets::preload::SceneAssets;
+use crate::core::scene::{Scene, SceneResult, Transition};
 use crate::core::timer::Timer;
 use crate::render::ui::{Gui, GuiContext};
 use crate::resources::Resources;
+use crate::scene::TRANSITION_DURATION;
 use bitflags::_core::time::Duration;
 use glfw::{Key, WindowEvent};
 use hecs::World;
 
-pub struct StoryScene<S: Scene<WindowEvent>> {
+pub struct StoryScene {
     sentences: Vec<String>,
     current_sentence: usize,
     timer_before_instruction: Timer,
-    next_scene: Option<S>,
+    /// Given to the scene stack with the transition, and given back if the transition is
+    /// cancelled.
+    next_scene: Option<Box<dyn Scene<WindowEvent>>>,
 }
 
-impl<S> StoryScene<S>
-where
-    S: Scene<WindowEvent> + 'static,
-{
-    pub fn new(sentences: Vec<String>, next_scene: S) -> Self {
+impl StoryScene {
+    pub fn new<S>(sentences: Vec<String>, next_scene: S) -> Self
+    where
+        S: Scene<WindowEvent> + 'static,
+    {
         assert!(sentences.len() > 0);
         Self {
             sentences,
             current_sentence: 0,
-            next_scene: Some(next_scene),
+            next_scene: Some(Box::new(next_scene)),
             timer_before_instruction: Timer::of_seconds(2.0),
         }
     }
 }
 
-impl<S> Scene<WindowEvent> for StoryScene<S>
-where
-    S: Scene<WindowEvent> + 'static,
-{
+impl Scene<WindowEvent> for StoryScene {
+    /// The next scene is created right after this one, so its assets are loaded now.
+    fn assets(&self) -> SceneAssets {
+        self.next_scene
+            .as_ref()
+            .map(|scene| scene.assets())
+            .unwrap_or_default()
+    }
+
     fn update(
         &mut self,
         dt: Duration,
@@ -40,11 +49,25 @@ where
     ) -> SceneResult<WindowEvent> {
         self.timer_before_instruction.tick(dt);
 
-        if self.current_sentence == self.sentences.len() {
-            SceneResult::ReplaceScene(Box::new(self.next_scene.take().unwrap()))
-        } else {
-            SceneResult::Noop
+        if self.current_sentence >= self.sentences.len() {
+            if let Some(next_scene) = self.next_scene.take() {
+                return SceneResult::ReplaceScene(next_scene)
+                    .with_transition(Transition::fade(TRANSITION_DURATION));
+            }
         }
+        SceneResult::Noop
+    }
+
+    /// Show the last sentence again so that the player can retry with Enter.
+    fn on_transition_cancelled(
+        &mut self,
+        result: SceneResult<WindowEvent>,
+        _world: &mut World,
+        _resources: &mut Resources,
+    ) {
+        self.next_scene = result.into_incoming_scene();
+        self.current_sentence = self.sentences.len() - 1;
+        self.timer_before_instruction.reset();
     }
 
     fn prepare_gui(
@@ -1,10 +1,13 @@
-use crate::core::scene::{Scene, SceneResult};
-use crate::render::ui::{Gui, GuiContext};
+use crate::core::colors::RgbaColor;
+use crate::core::scene::{Scene, SceneResult, Transition};
+use crate::render::ui::{Button, Gui, GuiContext, NavInput};
 use crate::resources::Resources;
 use crate::save::get_wave_record;
-use crate::scene::MainScene;
+use crate::scene::main_menu::MainMenu;
+use crate::scene::{MainScene, TRANSITION_DURATION};
+use crate::ui::draw_cursor;
 use bitflags::_core::time::Duration;
-use glfw::{Key, WindowEvent};
+use glfw::WindowEvent;
 use hecs::World;
 
 #[derive(Default)]
@@ -12,6 +15,8 @@ pub struct WaveSelectionScene {
     selected: usize,
     possible: Vec<usize>,
     start: bool,
+    back: bool,
+    focus_initialized: bool,
 }
 
 impl WaveSelectionScene {
@@ -30,11 +35,18 @@ impl WaveSelectionScene {
             selected: 0,
             possible,
             start: false,
+            back: false,
+            focus_initialized: false,
         }
     }
 }
 
 impl Scene<WindowEvent> for WaveSelectionScene {
+    fn on_enter(&mut self, _world: &mut World, _resources: &mut Resources) {
+        self.start = false;
+        self.back = false;
+    }
+
     fn update(
         &mut self,
         _dt: Duration,
@@ -43,6 +55,10 @@ impl Scene<WindowEvent> for WaveSelectionScene {
     ) -> SceneResult<WindowEvent> {
         if self.start {
             SceneResult::ReplaceScene(Box::new(MainScene::new(true, self.possible[self.selected])))
+                .with_transition(Transition::dissolve(TRANSITION_DURATION))
+        } else if self.back {
+            SceneResult::ReplaceScene(Box::new(MainMenu::default()))
+                .with_transition(Transition::wipe(TRANSITION_DURATION))
         } else {
             SceneResult::Noop
         }
@@ -56,41 +72,48 @@ impl Scene<WindowEvent> for WaveSelectionScene {
         gui_context: &GuiContext,
     ) -> Option<Gui> {
         let mut gui = gui_context.new_frame();
+        draw_cursor(&mut gui);
 
         let center = gui_context.window_dim.to_vec2() / 2.0 - 100.0 * glam::Vec2::unit_y();
 
         gui.centered_label(center, "Choose starting wave".to_string());
         gui.centered_label(
             center + 20.0 * glam::Vec2::unit_y(),
-            "Left/Right arrow to change, Enter to select".to_string(),
-        );
-
-        gui.centered_label(
-            center + 60.0 * glam::Vec2::unit_y(),
-            self.possible[self.selected].to_string(),
+            "Arrows to change, Enter to select".to_string(),
         );
 
-        Some(gui)
-    }
-
-    fn process_input(&mut self, _world: &mut World, input: WindowEvent, _resources: &Resources) {
-        match input {
-            WindowEvent::Key(Key::Left, _, glfw::Action::Release, _) => {
-                if self.selected > 0 {
-                    self.selected -= 1;
-                }
-            }
-
-            WindowEvent::Key(Key::Right, _, glfw::Action::Release, _) => {
-                if self.selected < self.possible.len() - 1 {
-                    self.selected += 1;
-                }
-            }
+        // Start with the first wave selected so that Enter works right away.
+        if !self.focus_initialized {
+            gui.focus(&wave_button_id(0));
+            self.focus_initialized = true;
+        }
 
-            WindowEvent::Key(Key::Enter, _, glfw::Action::Release, _) => {
+        let spacing = 80.0;
+        let row_width = spacing * (self.possible.len() - 1) as f32;
+        for (i, wave) in self.possible.iter().enumerate() {
+            let position = center + glam::vec2(i as f32 * spacing - row_width / 2.0 - 10.0, 60.0);
+            let clicked = Button::new(wave.to_string(), position)
+                .set_id(&wave_button_id(i))
+                .set_bg_color(RgbaColor::new(0, 0, 0, 0), RgbaColor::new(0, 0, 0, 0))
+                .set_text_color(
+                    RgbaColor::from_hex("FFFFFFFF").unwrap(),
+                    RgbaColor::from_hex("01FFFFFF").unwrap(),
+                )
+                .build(&mut gui);
+            if clicked {
+                self.selected = i;
                 self.start = true;
             }
-            _ => (),
         }
+
+        if gui.nav(NavInput::Cancel) {
+            self.back = true;
+        }
+
+        Some(gui)
     }
 }
+
+fn wave_button_id(index: usize) -> String {
+    format!("wave_{}", index)
+}
@@ -16,24 +16,6 @@ pub(crate) fn menu_button(text: &str, position: glam::Vec2, font_size: f32, ui:
         .build(ui)
 }
 
-pub(crate) fn disabled_menu_button(
-    text: &str,
-    position: glam::Vec2,
-    font_size: f32,
-    ui: &mut Gui,
-) -> bool {
-    Button::new(text.to_string(), position)
-        .set_bg_color(RgbaColor::new(0, 0, 0, 0), RgbaColor::new(0, 0, 0, 0))
-        .set_text_color(
-            RgbaColor::from_hex("808080FF").unwrap(),
-            RgbaColor::from_hex("808080FF").unwrap(),
-        )
-        .set_font_size(font_size)
-        .set_text_align(HorizontalAlign::Left, VerticalAlign::Top)
-        .set_padding(0.0)
-        .build(ui)
-}
-
 pub(crate) fn draw_cursor(gui: &mut Gui) {
     gui.panel(
         gui.mouse_pos - glam::vec2(2.0, 15.0),